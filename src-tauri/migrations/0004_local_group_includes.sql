@@ -0,0 +1,12 @@
+-- 分组嵌套：父分组包含子分组（由应用层做环检测）
+CREATE TABLE IF NOT EXISTS local_group_includes (
+  parent_id   INTEGER NOT NULL,
+  child_id    INTEGER NOT NULL,
+  created_at  TEXT NOT NULL,
+  PRIMARY KEY (parent_id, child_id),
+  FOREIGN KEY (parent_id) REFERENCES local_groups(id) ON DELETE CASCADE,
+  FOREIGN KEY (child_id) REFERENCES local_groups(id) ON DELETE CASCADE
+);
+
+-- 分组成员的默认权限（可空，展开嵌套分组时取最高值）
+ALTER TABLE local_group_members ADD COLUMN access_level INTEGER;
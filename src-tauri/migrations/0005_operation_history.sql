@@ -0,0 +1,10 @@
+-- 操作历史（离职清理等关键操作留痕）
+CREATE TABLE IF NOT EXISTS operation_history (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  action      TEXT NOT NULL,
+  target      TEXT NOT NULL,
+  detail      TEXT NOT NULL,
+  created_at  TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_operation_history_created_at ON operation_history (created_at);
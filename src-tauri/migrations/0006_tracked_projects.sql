@@ -0,0 +1,6 @@
+-- 纳入过期巡检的项目（project 为项目 ID 或 path_with_namespace）
+CREATE TABLE IF NOT EXISTS tracked_projects (
+  project     TEXT PRIMARY KEY,
+  name        TEXT,
+  created_at  TEXT NOT NULL
+);
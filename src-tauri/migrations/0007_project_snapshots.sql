@@ -0,0 +1,23 @@
+-- 项目成员快照（批量修改前自动生成，可用于对比与恢复）
+CREATE TABLE IF NOT EXISTS project_snapshots (
+  id            INTEGER PRIMARY KEY AUTOINCREMENT,
+  project       TEXT NOT NULL,
+  reason        TEXT NOT NULL,
+  member_count  INTEGER NOT NULL,
+  created_at    TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_snapshots_project ON project_snapshots (project, created_at);
+
+-- source：direct（项目直接成员）/ inherited（继承自上级 GitLab 分组）
+CREATE TABLE IF NOT EXISTS project_snapshot_members (
+  snapshot_id   INTEGER NOT NULL,
+  user_id       INTEGER NOT NULL,
+  username      TEXT NOT NULL,
+  name          TEXT NOT NULL,
+  access_level  INTEGER NOT NULL,
+  expires_at    TEXT,
+  source        TEXT NOT NULL,
+  PRIMARY KEY (snapshot_id, user_id),
+  FOREIGN KEY (snapshot_id) REFERENCES project_snapshots(id) ON DELETE CASCADE
+);
@@ -0,0 +1,27 @@
+-- 批量任务：kind 为 add / remove；undo_of 指向被撤销的任务
+CREATE TABLE IF NOT EXISTS batch_jobs (
+  id            INTEGER PRIMARY KEY AUTOINCREMENT,
+  kind          TEXT NOT NULL,
+  project       TEXT NOT NULL,
+  status        TEXT NOT NULL,
+  snapshot_id   INTEGER,
+  undo_of       INTEGER,
+  created_at    TEXT NOT NULL,
+  finished_at   TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_batch_jobs_created_at ON batch_jobs (created_at);
+
+-- 每个用户一条；prior_* 为执行前的权限与过期时间（移除任务撤销时按此重新加入）
+CREATE TABLE IF NOT EXISTS batch_job_items (
+  job_id              INTEGER NOT NULL,
+  user_id             INTEGER NOT NULL,
+  access_level        INTEGER,
+  expires_at          TEXT,
+  prior_access_level  INTEGER,
+  prior_expires_at    TEXT,
+  status              TEXT NOT NULL,
+  message             TEXT,
+  PRIMARY KEY (job_id, user_id),
+  FOREIGN KEY (job_id) REFERENCES batch_jobs(id) ON DELETE CASCADE
+);
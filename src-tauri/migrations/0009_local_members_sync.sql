@@ -0,0 +1,4 @@
+-- 本地成员与 GitLab 同步：用户状态、最近同步时间、GitLab 中已不存在的标记
+ALTER TABLE local_members ADD COLUMN state TEXT;
+ALTER TABLE local_members ADD COLUMN last_synced_at TEXT;
+ALTER TABLE local_members ADD COLUMN missing INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,18 @@
+-- 本地成员出现过的所有项目（local_members.project_id 只保留最近一次）
+CREATE TABLE IF NOT EXISTS local_member_sources (
+  user_id       INTEGER NOT NULL,
+  project_id    INTEGER NOT NULL,
+  project_path  TEXT,
+  project_name  TEXT,
+  access_level  INTEGER,
+  first_seen    TEXT NOT NULL,
+  last_seen     TEXT NOT NULL,
+  PRIMARY KEY (user_id, project_id),
+  FOREIGN KEY (user_id) REFERENCES local_members(user_id) ON DELETE CASCADE
+);
+
+-- 已有数据回填
+INSERT OR IGNORE INTO local_member_sources (user_id, project_id, project_path, project_name, access_level, first_seen, last_seen)
+SELECT user_id, project_id, project_name, project_name, NULL, updated_at, updated_at
+FROM local_members
+WHERE project_id IS NOT NULL;
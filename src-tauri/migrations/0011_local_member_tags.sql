@@ -0,0 +1,19 @@
+-- 本地成员备注与标签
+ALTER TABLE local_members ADD COLUMN notes TEXT;
+
+CREATE TABLE IF NOT EXISTS local_tags (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  name        TEXT NOT NULL UNIQUE,
+  created_at  TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS local_member_tags (
+  user_id     INTEGER NOT NULL,
+  tag_id      INTEGER NOT NULL,
+  created_at  TEXT NOT NULL,
+  PRIMARY KEY (user_id, tag_id),
+  FOREIGN KEY (user_id) REFERENCES local_members(user_id) ON DELETE CASCADE,
+  FOREIGN KEY (tag_id) REFERENCES local_tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_local_member_tags_tag ON local_member_tags (tag_id);
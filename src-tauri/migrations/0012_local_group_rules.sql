@@ -0,0 +1,2 @@
+-- 动态分组：rule 为 JSON 规则，非空时成员在查询时按规则计算
+ALTER TABLE local_groups ADD COLUMN rule TEXT;
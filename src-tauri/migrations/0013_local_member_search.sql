@@ -0,0 +1,21 @@
+-- 成员库全文检索：rowid 为 user_id，由应用在成员、备注、标签、来源变更时重建对应行
+ALTER TABLE local_members ADD COLUMN email TEXT;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS local_members_fts USING fts5(
+  username,
+  name,
+  email,
+  notes,
+  tags,
+  projects,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- 已有数据回填
+INSERT INTO local_members_fts (rowid, username, name, email, notes, tags, projects)
+SELECT m.user_id, m.username, m.name, '', COALESCE(m.notes, ''),
+  COALESCE((SELECT group_concat(t.name, ' ') FROM local_member_tags mt INNER JOIN local_tags t ON t.id = mt.tag_id
+            WHERE mt.user_id = m.user_id), ''),
+  COALESCE((SELECT group_concat(COALESCE(s.project_path, '') || ' ' || COALESCE(s.project_name, ''), ' ')
+            FROM local_member_sources s WHERE s.user_id = m.user_id), '')
+FROM local_members m;
@@ -0,0 +1,17 @@
+-- 需要审批的变更：uid 在导出 / 导入之间保持不变；operations / reasons / result 为 JSON
+CREATE TABLE IF NOT EXISTS change_requests (
+  id           INTEGER PRIMARY KEY AUTOINCREMENT,
+  uid          TEXT NOT NULL UNIQUE,
+  status       TEXT NOT NULL,
+  created_at   TEXT NOT NULL,
+  created_by   TEXT NOT NULL,
+  reasons      TEXT NOT NULL,
+  operations   TEXT NOT NULL,
+  signature    TEXT,
+  approved_by  TEXT,
+  approved_at  TEXT,
+  executed_at  TEXT,
+  result       TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_change_requests_status ON change_requests (status);
@@ -0,0 +1,11 @@
+-- 批量操作的策略规则：pattern 为项目路径或用户名的通配符（* / ?），value 含义取决于 kind
+CREATE TABLE IF NOT EXISTS policy_rules (
+  id           INTEGER PRIMARY KEY AUTOINCREMENT,
+  kind         TEXT NOT NULL,
+  pattern      TEXT NOT NULL,
+  value        INTEGER,
+  enabled      INTEGER NOT NULL DEFAULT 1,
+  description  TEXT,
+  created_at   TEXT NOT NULL,
+  updated_at   TEXT NOT NULL
+);
@@ -0,0 +1,4 @@
+-- 自定义成员角色：批量任务条目记录要授予的角色与移除前的角色，快照记录成员当时的角色
+ALTER TABLE batch_job_items ADD COLUMN member_role_id INTEGER;
+ALTER TABLE batch_job_items ADD COLUMN prior_member_role_id INTEGER;
+ALTER TABLE project_snapshot_members ADD COLUMN member_role_id INTEGER;
@@ -0,0 +1,429 @@
+use crate::db;
+use crate::gitlab::{self, GitLabConfig, MemberOutcome};
+use crate::models::{
+  ApprovalPolicy, BatchItemError, BatchItemStatus, BatchJobItem, BatchJobKind, BatchResult,
+  ChangeOperation, ChangeRequest, ChangeRequestFile, ChangeRequestStatus, GroupShareItem,
+  MembershipSourceType, ProjectBatchResult,
+};
+use crate::policy;
+use anyhow::{anyhow, bail, Result};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+
+/// config 表中的 key；SECRET_KEY 为团队共享的签名密钥，备份时可排除
+pub const POLICY_KEY: &str = "approval_policy";
+pub const SECRET_KEY: &str = "approval_secret";
+
+/// 变更请求文件格式版本
+const FILE_VERSION: u32 = 1;
+
+/// 按策略判断一组操作是否需要审批，返回触发的原因（为空表示无需审批）。
+/// project_ids 为 resolve_project_ids 得到的项目标识 → 项目 ID，受保护项目按项目 ID 比较
+pub fn evaluate(policy: &ApprovalPolicy, operations: &[ChangeOperation], project_ids: &HashMap<String, String>) -> Vec<String> {
+  if !policy.enabled {
+    return Vec::new();
+  }
+  let mut reasons = Vec::new();
+
+  if operations.iter().any(|op| op.policy.is_some()) {
+    reasons.push("changes the approval policy".to_string());
+  }
+
+  let max_level = operations
+    .iter()
+    .filter(|op| op.kind != BatchJobKind::Remove)
+    .flat_map(|op| {
+      let shares = op.shares.iter().map(|s| s.group_access);
+      op.items.iter().chain(&op.updates).map(|i| i.access_level).chain(shares)
+    })
+    .max();
+  if let Some(level) = max_level.filter(|l| *l >= policy.min_access_level) {
+    reasons.push(format!("grants access level {} (threshold {})", level, policy.min_access_level));
+  }
+
+  // 共享给分组按一个用户计算
+  let users: usize = operations
+    .iter()
+    .map(|op| op.items.len() + op.user_ids.len() + op.updates.len() + op.shares.len())
+    .sum();
+  if users > policy.max_users {
+    reasons.push(format!("affects {} users (limit {})", users, policy.max_users));
+  }
+
+  let canonical = |project: &str| {
+    let key = project.trim().to_lowercase();
+    project_ids.get(&key).cloned().unwrap_or(key)
+  };
+  let protected: HashSet<String> = policy.protected_projects.iter().map(|p| canonical(p)).collect();
+  let mut seen = HashSet::new();
+  for op in operations.iter().filter(|op| op.policy.is_none()) {
+    let project = canonical(&op.project);
+    if protected.contains(&project) && seen.insert(project) {
+      reasons.push(format!("project {} is protected", op.project));
+    }
+  }
+  reasons
+}
+
+/// 把受保护项目与操作的目标项目（ID 或 path_with_namespace）都解析为项目 ID，供 evaluate 比较。
+/// GitLab 中已不存在的项目只按字符串比较；解析出错时返回错误，不放行
+pub async fn resolve_project_ids(
+  cfg: &GitLabConfig,
+  policy: &ApprovalPolicy,
+  operations: &[ChangeOperation],
+) -> Result<HashMap<String, String>> {
+  let mut ids = HashMap::new();
+  if !policy.enabled || policy.protected_projects.is_empty() {
+    return Ok(ids);
+  }
+  let targets = operations
+    .iter()
+    .filter(|op| op.policy.is_none() && op.source_type == MembershipSourceType::Project)
+    .map(|op| op.project.as_str());
+  for project in policy.protected_projects.iter().map(String::as_str).chain(targets) {
+    let key = project.trim().to_lowercase();
+    if key.is_empty() || ids.contains_key(&key) {
+      continue;
+    }
+    let id = if key.chars().all(|c| c.is_ascii_digit()) {
+      Some(key.clone())
+    } else {
+      gitlab::get_project(cfg, project.trim()).await?.map(|p| p.id.to_string())
+    };
+    match id {
+      Some(id) => {
+        ids.insert(key, id);
+      }
+      None => tracing::warn!(project = %project, "[approval] project not found, compared by name"),
+    }
+  }
+  Ok(ids)
+}
+
+/// 解析项目 ID 后按策略判断，返回需要审批的原因
+pub async fn reasons(cfg: &GitLabConfig, policy: &ApprovalPolicy, operations: &[ChangeOperation]) -> Result<Vec<String>> {
+  let project_ids = resolve_project_ids(cfg, policy, operations).await?;
+  Ok(evaluate(policy, operations, &project_ids))
+}
+
+/// 需要审批时保存待审批的变更请求并返回其 ID；无需审批时返回 None
+pub async fn gate(pool: &SqlitePool, cfg: &GitLabConfig, operations: Vec<ChangeOperation>) -> Result<Option<i64>> {
+  let policy: ApprovalPolicy = db::get_config_json(pool, POLICY_KEY).await?.unwrap_or_default();
+  let reasons = reasons(cfg, &policy, &operations).await?;
+  if reasons.is_empty() {
+    return Ok(None);
+  }
+
+  let created_by = gitlab::current_user(cfg).await?.username;
+  let created_at = chrono::Utc::now().to_rfc3339();
+  let uid = new_uid(&created_at, &created_by, &operations)?;
+  let id = db::create_change_request(pool, &uid, &created_at, &created_by, &reasons, &operations, None).await?;
+
+  tracing::info!(id = id, reasons = ?reasons, "[approval] change request created");
+  Ok(Some(id))
+}
+
+fn new_uid(created_at: &str, created_by: &str, operations: &[ChangeOperation]) -> Result<String> {
+  let mut hasher = Sha256::new();
+  hasher.update(created_at.as_bytes());
+  hasher.update(created_by.as_bytes());
+  hasher.update(serde_json::to_vec(operations)?);
+  hasher.update(chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default().to_le_bytes());
+  Ok(to_hex(&hasher.finalize()[..16]))
+}
+
+/// 参与签名的内容：除 signature 外的全部字段，字段顺序固定
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedContent<'a> {
+  version: u32,
+  uid: &'a str,
+  created_at: &'a str,
+  created_by: &'a str,
+  reasons: &'a [String],
+  operations: &'a [ChangeOperation],
+}
+
+fn mac(secret: &str, file: &ChangeRequestFile) -> Result<Hmac<Sha256>> {
+  let content = serde_json::to_vec(&SignedContent {
+    version: file.version,
+    uid: &file.uid,
+    created_at: &file.created_at,
+    created_by: &file.created_by,
+    reasons: &file.reasons,
+    operations: &file.operations,
+  })?;
+  let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|e| anyhow!("invalid approval secret: {}", e))?;
+  mac.update(&content);
+  Ok(mac)
+}
+
+/// 导出为签名的 JSON 文件内容
+pub fn export(request: &ChangeRequest, secret: &str) -> Result<String> {
+  let mut file = ChangeRequestFile {
+    version: FILE_VERSION,
+    uid: request.uid.clone(),
+    created_at: request.created_at.clone(),
+    created_by: request.created_by.clone(),
+    reasons: request.reasons.clone(),
+    operations: request.operations.clone(),
+    signature: String::new(),
+  };
+  file.signature = to_hex(&mac(secret, &file)?.finalize().into_bytes());
+  Ok(serde_json::to_string_pretty(&file)?)
+}
+
+/// 解析变更请求文件并校验签名
+pub fn verify(content: &str, secret: &str) -> Result<ChangeRequestFile> {
+  let file: ChangeRequestFile = serde_json::from_str(content).map_err(|e| anyhow!("invalid change request file: {}", e))?;
+  if file.version == 0 || file.version > FILE_VERSION {
+    bail!("unsupported change request file version {}", file.version);
+  }
+  let signature = from_hex(&file.signature).ok_or_else(|| anyhow!("malformed signature"))?;
+  mac(secret, &file)?
+    .verify_slice(&signature)
+    .map_err(|_| anyhow!("signature mismatch: file was modified or signed with a different secret"))?;
+  Ok(file)
+}
+
+/// 导入签名的变更请求文件，保存为待审批
+pub async fn import(pool: &SqlitePool, content: &str, secret: &str) -> Result<i64> {
+  let file = verify(content, secret)?;
+  db::create_change_request(
+    pool,
+    &file.uid,
+    &file.created_at,
+    &file.created_by,
+    &file.reasons,
+    &file.operations,
+    Some(&file.signature),
+  )
+  .await
+}
+
+/// 审批或驳回。审批人以当前 token 对应的 GitLab 用户为准，且不能是发起人
+pub async fn review(pool: &SqlitePool, cfg: &GitLabConfig, id: i64, approve: bool) -> Result<ChangeRequest> {
+  let request = db::get_change_request(pool, id)
+    .await?
+    .ok_or_else(|| anyhow!("change request {} not found", id))?;
+  if request.status != ChangeRequestStatus::Pending {
+    bail!("change request {} is {}, not pending", id, request.status.as_str());
+  }
+
+  let reviewer = gitlab::current_user(cfg).await?.username;
+  if approve && reviewer.eq_ignore_ascii_case(&request.created_by) {
+    bail!("change request must be approved by an operator other than {}", request.created_by);
+  }
+
+  let status = if approve {
+    ChangeRequestStatus::Approved
+  } else {
+    ChangeRequestStatus::Rejected
+  };
+  if !db::review_change_request(pool, id, status, &reviewer).await? {
+    bail!("change request {} was reviewed concurrently", id);
+  }
+  db::get_change_request(pool, id)
+    .await?
+    .ok_or_else(|| anyhow!("change request {} not found", id))
+}
+
+/// 执行已审批的变更请求：每个操作先生成快照，再走批量任务（可撤销），shares 逐个执行；
+/// 分组操作与审批策略变更直接执行。这里不再经过审批策略
+pub async fn execute(pool: &SqlitePool, cfg: &GitLabConfig, id: i64) -> Result<ChangeRequest> {
+  let request = db::get_change_request(pool, id)
+    .await?
+    .ok_or_else(|| anyhow!("change request {} not found", id))?;
+  if !db::mark_change_request_executed(pool, id).await? {
+    bail!("change request {} is {}, only approved requests can be executed", id, request.status.as_str());
+  }
+
+  let mut results = Vec::new();
+  for op in &request.operations {
+    let result = match (op.source_type, op.kind) {
+      _ if op.policy.is_some() => {
+        set_policy(pool, op).await?;
+        empty_result(None)
+      }
+      (MembershipSourceType::Project, _) => execute_project_operation(pool, cfg, op).await,
+      // 分组操作来自批准访问申请与离职清理，没有快照与批量任务
+      (MembershipSourceType::Group, BatchJobKind::Add | BatchJobKind::Approve) => {
+        crate::approve_request_items(pool, cfg, op.source_type, &op.project, crate::add_job_items(&op.items)).await
+      }
+      (MembershipSourceType::Group, BatchJobKind::Remove) => remove_group_members(pool, cfg, op).await,
+      (MembershipSourceType::Group, BatchJobKind::Update) => {
+        failed_result(op, "updating GitLab group members is not supported in change requests")
+      }
+    };
+    results.push(ProjectBatchResult {
+      project: op.project.clone(),
+      result,
+    });
+  }
+  db::set_change_request_result(pool, id, &results).await?;
+
+  tracing::info!(id = id, operations = results.len(), "[approval] change request executed");
+  db::get_change_request(pool, id)
+    .await?
+    .ok_or_else(|| anyhow!("change request {} not found", id))
+}
+
+async fn execute_project_operation(pool: &SqlitePool, cfg: &GitLabConfig, op: &ChangeOperation) -> BatchResult {
+  match crate::take_snapshot(pool, cfg, &op.project, "execute_change_request").await {
+    Ok(snapshot_id) => {
+      let mut result = match op.kind {
+        BatchJobKind::Add | BatchJobKind::Update if op.items.is_empty() => empty_result(Some(snapshot_id)),
+        BatchJobKind::Remove if op.user_ids.is_empty() => empty_result(Some(snapshot_id)),
+        BatchJobKind::Add => crate::run_batch_add(pool, cfg, &op.project, snapshot_id, None, &op.items).await,
+        BatchJobKind::Remove => crate::run_batch_remove(pool, cfg, &op.project, snapshot_id, None, &op.user_ids).await,
+        BatchJobKind::Update => crate::run_batch_update(pool, cfg, &op.project, snapshot_id, None, &op.items).await,
+        BatchJobKind::Approve => crate::run_batch_approve(pool, cfg, &op.project, snapshot_id, &op.items).await,
+      };
+      if !op.updates.is_empty() {
+        let updated = crate::run_batch_update(pool, cfg, &op.project, snapshot_id, None, &op.updates).await;
+        result.success_user_ids.extend(updated.success_user_ids);
+        result.skipped_user_ids.extend(updated.skipped_user_ids);
+        result.failed.extend(updated.failed);
+      }
+      for share in &op.shares {
+        let shared = match op.kind {
+          BatchJobKind::Remove => unshare_project(pool, cfg, &op.project, share.group_id).await,
+          _ => share_project(pool, cfg, &op.project, share).await,
+        };
+        if let Err(e) = shared {
+          tracing::warn!(project = %op.project, group_id = share.group_id, error = %e, "[approval] share failed");
+          result.failed.push(BatchItemError {
+            user_id: share.group_id,
+            message: e.to_string(),
+          });
+        }
+      }
+      result
+    }
+    Err(message) => failed_result(op, &message),
+  }
+}
+
+/// 共享前按策略护栏检查；结果中以分组 ID 记录失败
+async fn share_project(pool: &SqlitePool, cfg: &GitLabConfig, project: &str, share: &GroupShareItem) -> Result<()> {
+  let expires_at = share.expires_at.as_deref();
+  if let Some(reason) =
+    policy::check_share(pool, cfg, BatchJobKind::Add, project, share.group_id, Some(share.group_access), expires_at).await
+  {
+    bail!("blocked by policy: {}", reason);
+  }
+  gitlab::share_project_with_group(cfg, project, share.group_id, share.group_access, share.expires_at.clone()).await?;
+  Ok(())
+}
+
+/// 取消共享前按策略护栏检查（禁止的命名空间）
+async fn unshare_project(pool: &SqlitePool, cfg: &GitLabConfig, project: &str, group_id: u64) -> Result<()> {
+  if let Some(reason) = policy::check_share(pool, cfg, BatchJobKind::Remove, project, group_id, None, None).await {
+    bail!("blocked by policy: {}", reason);
+  }
+  gitlab::unshare_project_with_group(cfg, project, group_id).await?;
+  Ok(())
+}
+
+/// 从 GitLab 分组中移除用户（离职清理），移除前按策略护栏检查；本就不是成员的记为跳过
+async fn remove_group_members(pool: &SqlitePool, cfg: &GitLabConfig, op: &ChangeOperation) -> BatchResult {
+  let items: Vec<BatchJobItem> = op
+    .user_ids
+    .iter()
+    .map(|uid| BatchJobItem {
+      user_id: *uid,
+      access_level: None,
+      expires_at: None,
+      prior_access_level: None,
+      prior_expires_at: None,
+      member_role_id: None,
+      prior_member_role_id: None,
+      status: BatchItemStatus::Pending,
+      message: None,
+    })
+    .collect();
+  let blocked = policy::check_group_items(pool, cfg, BatchJobKind::Remove, &op.project, &items).await;
+
+  let mut result = empty_result(None);
+  for uid in &op.user_ids {
+    let outcome = match blocked.get(uid) {
+      Some(reason) => Err(anyhow!("blocked by policy: {}", reason)),
+      None => gitlab::remove_group_member(cfg, &op.project, *uid).await,
+    };
+    match outcome {
+      Ok(MemberOutcome::Changed) => result.success_user_ids.push(*uid),
+      Ok(MemberOutcome::Unchanged) => result.skipped_user_ids.push(*uid),
+      Err(e) => {
+        tracing::warn!(group = %op.project, user_id = uid, error = %e, "[approval] remove group member failed");
+        result.failed.push(BatchItemError {
+          user_id: *uid,
+          message: e.to_string(),
+        });
+      }
+    }
+  }
+  result
+}
+
+/// 保存审批通过的审批策略，并写入操作历史
+async fn set_policy(pool: &SqlitePool, op: &ChangeOperation) -> Result<()> {
+  let Some(policy) = &op.policy else {
+    return Ok(());
+  };
+  db::set_config_json(pool, POLICY_KEY, policy).await?;
+  let detail = serde_json::json!({ "policy": policy });
+  if let Err(e) = db::record_history(pool, "set_approval_policy", "approval_policy", &detail).await {
+    tracing::error!(error = %e, "[approval] record policy history failed");
+  }
+  tracing::info!("[approval] approval policy updated");
+  Ok(())
+}
+
+fn empty_result(snapshot_id: Option<i64>) -> BatchResult {
+  BatchResult {
+    success_user_ids: Vec::new(),
+    failed: Vec::new(),
+    skipped_user_ids: Vec::new(),
+    snapshot_id,
+    job_id: None,
+  }
+}
+
+/// 整个操作未执行时，每个用户都记为失败
+fn failed_result(op: &ChangeOperation, message: &str) -> BatchResult {
+  BatchResult {
+    success_user_ids: Vec::new(),
+    failed: op
+      .items
+      .iter()
+      .chain(&op.updates)
+      .map(|i| i.user_id)
+      .chain(op.user_ids.iter().copied())
+      .chain(op.shares.iter().map(|s| s.group_id))
+      .map(|user_id| BatchItemError {
+        user_id,
+        message: message.to_string(),
+      })
+      .collect(),
+    skipped_user_ids: Vec::new(),
+    snapshot_id: None,
+    job_id: None,
+  }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+  if !s.len().is_multiple_of(2) {
+    return None;
+  }
+  (0..s.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+    .collect()
+}
@@ -0,0 +1,234 @@
+use crate::approval;
+use crate::db;
+use crate::models::BackupInfo;
+use anyhow::{bail, Context, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{ConnectOptions, Connection, SqlitePool};
+use std::path::{Path, PathBuf};
+
+/// 自动备份目录（位于 app_data_dir 下）与各类自动备份保留的份数
+const BACKUP_DIR: &str = "backups";
+const KEEP_AUTO_BACKUPS: usize = 5;
+const PRE_MIGRATION_PREFIX: &str = "pre-migration-";
+const PRE_RESTORE_PREFIX: &str = "pre-restore-";
+
+/// 等待下次启动时替换数据库的恢复文件
+fn pending_restore_path(db_path: &Path) -> PathBuf {
+  db_path.with_extension("sqlite3.restore")
+}
+
+async fn open(path: &Path) -> Result<SqliteConnection> {
+  SqliteConnectOptions::new()
+    .filename(path)
+    .connect()
+    .await
+    .with_context(|| format!("failed to open sqlite file: {}", path.display()))
+}
+
+/// 用 VACUUM INTO 在线备份到 path（先写临时文件再替换，不阻塞读写）；
+/// exclude_secrets=true 时清除备份中的敏感配置。
+/// VACUUM INTO 在一个读事务内完成，读到的是含 WAL 中已提交数据的一致快照，效果等同 SQLite 在线备份 API
+/// （sqlx 未暴露该 API），且产出的文件不依赖 -wal / -shm
+pub async fn backup_to(pool: &SqlitePool, path: &Path, exclude_secrets: bool) -> Result<BackupInfo> {
+  let tmp = path.with_extension("partial");
+  if tmp.exists() {
+    std::fs::remove_file(&tmp)?;
+  }
+
+  sqlx::query(r#"VACUUM INTO ?1"#)
+    .bind(tmp.to_string_lossy().to_string())
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to back up database to {}", tmp.display()))?;
+
+  if exclude_secrets {
+    let mut conn = open(&tmp).await?;
+    scrub_secrets(&mut conn).await?;
+    conn.close().await?;
+  }
+
+  let schema_version = inspect(&tmp).await?;
+  std::fs::rename(&tmp, path).with_context(|| format!("failed to move backup to {}", path.display()))?;
+
+  tracing::info!(path = %path.display(), schema_version = schema_version, exclude_secrets = exclude_secrets, "[backup] backup completed");
+  Ok(BackupInfo {
+    path: path.display().to_string(),
+    schema_version,
+    size_bytes: std::fs::metadata(path)?.len(),
+    secrets_excluded: exclude_secrets,
+    created_at: chrono::Utc::now().to_rfc3339(),
+  })
+}
+
+/// 清除敏感配置（GitLab token 与审批签名密钥），并 VACUUM 以免旧值残留在空闲页中
+async fn scrub_secrets(conn: &mut SqliteConnection) -> Result<()> {
+  sqlx::query(r#"UPDATE config SET value = json_set(value, '$.token', '') WHERE key = 'gitlab'"#)
+    .execute(&mut *conn)
+    .await?;
+  sqlx::query(r#"DELETE FROM config WHERE key = ?"#)
+    .bind(approval::SECRET_KEY)
+    .execute(&mut *conn)
+    .await?;
+  sqlx::query(r#"VACUUM"#).execute(&mut *conn).await?;
+  Ok(())
+}
+
+/// 校验备份文件：完整性检查通过，且已执行的迁移都是当前版本已知的（checksum 一致）。返回 schema 版本。
+/// FTS5 的完整性检查需要可写连接，因此只对应用自己生成的副本调用
+pub async fn inspect(path: &Path) -> Result<i64> {
+  let mut conn = open(path).await?;
+
+  let (check,): (String,) = sqlx::query_as(r#"PRAGMA quick_check"#).fetch_one(&mut conn).await?;
+  if check != "ok" {
+    bail!("backup integrity check failed: {}", check);
+  }
+
+  let has_migrations: Option<(String,)> =
+    sqlx::query_as(r#"SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'"#)
+      .fetch_optional(&mut conn)
+      .await?;
+  if has_migrations.is_none() {
+    bail!("not a database of this app: migration table missing");
+  }
+
+  let applied = sqlx::query_as::<_, (i64, Vec<u8>)>(
+    r#"SELECT version, checksum FROM _sqlx_migrations WHERE success = 1 ORDER BY version ASC"#,
+  )
+  .fetch_all(&mut conn)
+  .await?;
+  conn.close().await?;
+
+  for (version, checksum) in &applied {
+    match db::MIGRATOR.iter().find(|m| m.version == *version) {
+      None => bail!("backup schema version {} is newer than this app supports", version),
+      Some(m) if m.checksum.as_ref() != checksum.as_slice() => bail!("migration {} checksum mismatch", version),
+      Some(_) => {}
+    }
+  }
+
+  Ok(applied.last().map(|(v, _)| *v).unwrap_or(0))
+}
+
+/// 把备份复制为恢复文件并校验，下次启动时在连接数据库之前替换；校验失败时删除恢复文件
+pub async fn stage_restore(db_path: &Path, path: &Path) -> Result<BackupInfo> {
+  let pending = pending_restore_path(db_path);
+  std::fs::copy(path, &pending).with_context(|| format!("failed to stage restore file {}", pending.display()))?;
+  let schema_version = match inspect(&pending).await {
+    Ok(v) => v,
+    Err(e) => {
+      let _ = std::fs::remove_file(&pending);
+      return Err(e);
+    }
+  };
+
+  tracing::info!(path = %path.display(), schema_version = schema_version, "[backup] restore staged");
+  Ok(BackupInfo {
+    path: path.display().to_string(),
+    schema_version,
+    size_bytes: std::fs::metadata(path)?.len(),
+    secrets_excluded: false,
+    created_at: chrono::Utc::now().to_rfc3339(),
+  })
+}
+
+/// 启动时已应用的恢复：pre_restore_backup 为替换前当前数据库的备份（原先没有数据库时为空）
+pub struct AppliedRestore {
+  pub pre_restore_backup: Option<PathBuf>,
+}
+
+/// 启动时应用暂存的恢复文件：先用 VACUUM INTO 把当前数据库（含 WAL 中尚未检查点的数据）备份为 pre-restore，
+/// 再删除 -wal / -shm 并替换数据库文件。未执行恢复时返回 None
+pub async fn apply_pending_restore(db_path: &Path) -> Result<Option<AppliedRestore>> {
+  let pending = pending_restore_path(db_path);
+  if !pending.exists() {
+    return Ok(None);
+  }
+
+  let mut pre_restore_backup = None;
+  if db_path.exists() {
+    let dir = backup_dir(db_path)?;
+    let target = dir.join(format!("{}{}.sqlite3", PRE_RESTORE_PREFIX, timestamp()));
+    let mut conn = open(db_path).await?;
+    sqlx::query(r#"VACUUM INTO ?1"#)
+      .bind(target.to_string_lossy().to_string())
+      .execute(&mut conn)
+      .await
+      .context("failed to back up current database before restore")?;
+    conn.close().await?;
+    if let Err(e) = rotate(&dir, PRE_RESTORE_PREFIX) {
+      tracing::warn!(error = %e, "[backup] rotate pre-restore backups failed");
+    }
+    pre_restore_backup = Some(target);
+  }
+  for suffix in ["sqlite3-wal", "sqlite3-shm"] {
+    let _ = std::fs::remove_file(db_path.with_extension(suffix));
+  }
+  std::fs::rename(&pending, db_path).context("failed to replace database with restore file")?;
+
+  tracing::info!(db_path = %db_path.display(), "[backup] pending restore applied");
+  Ok(Some(AppliedRestore { pre_restore_backup }))
+}
+
+/// 有待执行的迁移时先备份当前数据库（全新数据库跳过），按 KEEP_AUTO_BACKUPS 轮转
+pub async fn backup_before_migrations(pool: &SqlitePool, db_path: &Path) -> Result<Option<PathBuf>> {
+  let has_migrations: Option<(String,)> =
+    sqlx::query_as(r#"SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'"#)
+      .fetch_optional(pool)
+      .await?;
+  if has_migrations.is_none() {
+    return Ok(None);
+  }
+
+  let applied: Vec<(i64,)> = sqlx::query_as(r#"SELECT version FROM _sqlx_migrations WHERE success = 1"#)
+    .fetch_all(pool)
+    .await?;
+  let pending = db::MIGRATOR
+    .iter()
+    .any(|m| !applied.iter().any(|(v,)| *v == m.version));
+  if !pending {
+    return Ok(None);
+  }
+
+  let dir = backup_dir(db_path)?;
+  let target = dir.join(format!("{}{}.sqlite3", PRE_MIGRATION_PREFIX, timestamp()));
+  sqlx::query(r#"VACUUM INTO ?1"#)
+    .bind(target.to_string_lossy().to_string())
+    .execute(pool)
+    .await
+    .context("failed to back up database before migrations")?;
+  if let Err(e) = rotate(&dir, PRE_MIGRATION_PREFIX) {
+    tracing::warn!(error = %e, "[backup] rotate pre-migration backups failed");
+  }
+
+  tracing::info!(path = %target.display(), "[backup] pre-migration backup created");
+  Ok(Some(target))
+}
+
+fn backup_dir(db_path: &Path) -> Result<PathBuf> {
+  let dir = db_path
+    .parent()
+    .context("database path has no parent directory")?
+    .join(BACKUP_DIR);
+  std::fs::create_dir_all(&dir).context("failed to create backup dir")?;
+  Ok(dir)
+}
+
+fn timestamp() -> String {
+  chrono::Local::now().format("%Y%m%d-%H%M%S").to_string()
+}
+
+/// 只保留最新的 KEEP_AUTO_BACKUPS 份（文件名带时间戳，按名称排序即按时间排序）
+fn rotate(dir: &Path, prefix: &str) -> Result<()> {
+  let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+    .filter_map(|e| e.ok().map(|e| e.path()))
+    .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(prefix)))
+    .collect();
+  files.sort();
+  let excess = files.len().saturating_sub(KEEP_AUTO_BACKUPS);
+  for old in files.into_iter().take(excess) {
+    if let Err(e) = std::fs::remove_file(&old) {
+      tracing::warn!(path = %old.display(), error = %e, "[backup] remove old backup failed");
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,245 @@
+use crate::db;
+use crate::gitlab::{self, GitLabConfig};
+use crate::models::{
+  AccessLevel, BundleConflict, BundleConflictKind, BundleFormat, BundleGroup, BundleImportResult,
+  BundleMember, GitLabUser, GroupBundle, LocalGroup, LocalMemberUpsert,
+};
+use crate::smart_group;
+use anyhow::{anyhow, bail, Context, Result};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// 当前共享包格式版本；导入时拒绝更新的版本
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// 导出分组（连同其嵌套的子分组），成员以用户名为准
+pub async fn export(pool: &SqlitePool, instance_url: Option<&str>, group_ids: &[i64]) -> Result<GroupBundle> {
+  let all: HashMap<i64, LocalGroup> = db::list_local_groups(pool).await?.into_iter().map(|g| (g.id, g)).collect();
+
+  let mut queue: VecDeque<i64> = group_ids.iter().copied().collect();
+  let mut seen: HashSet<i64> = HashSet::new();
+  let mut groups = Vec::new();
+  while let Some(id) = queue.pop_front() {
+    if !seen.insert(id) {
+      continue;
+    }
+    let group = all.get(&id).ok_or_else(|| anyhow!("local group {} not found", id))?;
+    let includes = db::list_group_includes(pool, id).await?;
+    let members = db::list_group_members(pool, id, false).await?;
+
+    queue.extend(includes.iter().map(|g| g.id));
+    groups.push(BundleGroup {
+      name: group.name.clone(),
+      rule: group.rule.clone(),
+      members: members
+        .into_iter()
+        .map(|m| BundleMember {
+          username: m.member.username,
+          user_id: Some(m.member.user_id),
+          name: Some(m.member.name),
+          access_level: m.access_level,
+        })
+        .collect(),
+      includes: includes.into_iter().map(|g| g.name).collect(),
+    });
+  }
+
+  Ok(GroupBundle {
+    version: BUNDLE_VERSION,
+    instance_url: instance_url.map(normalize_url),
+    exported_at: Some(chrono::Utc::now().to_rfc3339()),
+    groups,
+  })
+}
+
+pub fn render(bundle: &GroupBundle, format: BundleFormat) -> Result<String> {
+  Ok(match format {
+    BundleFormat::Json => serde_json::to_string_pretty(bundle)?,
+    BundleFormat::Yaml => serde_yaml::to_string(bundle)?,
+  })
+}
+
+/// 解析 JSON 或 YAML 共享包并检查版本
+pub fn parse(content: &str) -> Result<GroupBundle> {
+  let bundle: GroupBundle = if content.trim_start().starts_with('{') {
+    serde_json::from_str(content).context("invalid JSON group bundle")?
+  } else {
+    serde_yaml::from_str(content).context("invalid YAML group bundle")?
+  };
+  if bundle.version == 0 || bundle.version > BUNDLE_VERSION {
+    bail!("unsupported group bundle version {} (supported: {})", bundle.version, BUNDLE_VERSION);
+  }
+  Ok(bundle)
+}
+
+fn normalize_url(url: &str) -> String {
+  url.trim().trim_end_matches('/').to_lowercase()
+}
+
+/// 在当前实例中解析共享包里的用户名，返回 用户名 → 用户 以及解析中发现的冲突
+pub async fn resolve_users(cfg: &GitLabConfig, bundle: &GroupBundle) -> Result<(HashMap<String, GitLabUser>, Vec<BundleConflict>)> {
+  let same_instance = bundle
+    .instance_url
+    .as_deref()
+    .is_some_and(|u| normalize_url(u) == normalize_url(&cfg.base_url));
+
+  let mut users = HashMap::new();
+  let mut conflicts = Vec::new();
+  let mut seen = HashSet::new();
+  for member in bundle.groups.iter().flat_map(|g| &g.members) {
+    if !seen.insert(member.username.clone()) {
+      continue;
+    }
+
+    // 同一实例优先按 user_id 查找，以识别改名
+    if let (true, Some(user_id)) = (same_instance, member.user_id) {
+      if let Some(user) = gitlab::get_user(cfg, user_id).await? {
+        if user.username != member.username {
+          conflicts.push(BundleConflict {
+            kind: BundleConflictKind::Renamed,
+            group: None,
+            username: Some(member.username.clone()),
+            message: format!("user {} was renamed to {}", user_id, user.username),
+          });
+        }
+        users.insert(member.username.clone(), user);
+        continue;
+      }
+    }
+
+    match gitlab::find_user_by_username(cfg, &member.username).await? {
+      Some(user) if same_instance && member.user_id.is_some_and(|id| id != user.id) => conflicts.push(BundleConflict {
+        kind: BundleConflictKind::IdMismatch,
+        group: None,
+        username: Some(member.username.clone()),
+        message: format!(
+          "username now belongs to user {} instead of {}",
+          user.id,
+          member.user_id.unwrap_or_default()
+        ),
+      }),
+      Some(user) => {
+        users.insert(member.username.clone(), user);
+      }
+      None => conflicts.push(BundleConflict {
+        kind: BundleConflictKind::UserNotFound,
+        group: None,
+        username: Some(member.username.clone()),
+        message: "user not found on this GitLab instance".to_string(),
+      }),
+    }
+  }
+
+  Ok((users, conflicts))
+}
+
+/// 把共享包合并到本地分组：不存在的分组新建，已有分组只补充缺少的成员与嵌套关系，不覆盖本地权限与规则
+pub async fn merge(pool: &SqlitePool, bundle: &GroupBundle, users: &HashMap<String, GitLabUser>) -> Result<BundleImportResult> {
+  let mut result = BundleImportResult::default();
+
+  db::upsert_local_members(
+    pool,
+    users
+      .values()
+      .map(|u| LocalMemberUpsert {
+        user_id: u.id,
+        username: u.username.clone(),
+        name: u.name.clone(),
+        avatar_url: u.avatar_url.clone(),
+        project_id: None,
+        project_name: None,
+        project_path: None,
+        access_level: None,
+      })
+      .collect(),
+  )
+  .await?;
+
+  let mut by_name: HashMap<String, LocalGroup> =
+    db::list_local_groups(pool).await?.into_iter().map(|g| (g.name.clone(), g)).collect();
+
+  for bg in &bundle.groups {
+    let group = match by_name.get(&bg.name) {
+      Some(existing) => {
+        let same_rule = serde_json::to_value(&existing.rule)? == serde_json::to_value(&bg.rule)?;
+        if bg.rule.is_some() && !same_rule {
+          result.conflicts.push(BundleConflict {
+            kind: BundleConflictKind::RuleDiffers,
+            group: Some(bg.name.clone()),
+            username: None,
+            message: "local group has a different rule, kept local rule".to_string(),
+          });
+        }
+        result.groups_merged.push(bg.name.clone());
+        existing.clone()
+      }
+      None => {
+        let created = match &bg.rule {
+          Some(rule) => {
+            smart_group::validate_rule(rule).with_context(|| format!("invalid rule of group {}", bg.name))?;
+            db::create_smart_group(pool, bg.name.clone(), rule.clone()).await?
+          }
+          None => db::create_local_group(pool, bg.name.clone()).await?,
+        };
+        result.groups_created.push(bg.name.clone());
+        by_name.insert(bg.name.clone(), created.clone());
+        created
+      }
+    };
+
+    let current: HashMap<u64, Option<AccessLevel>> = db::list_group_members(pool, group.id, false)
+      .await?
+      .into_iter()
+      .map(|m| (m.member.user_id, m.access_level))
+      .collect();
+    for member in &bg.members {
+      let Some(user) = users.get(&member.username) else {
+        continue;
+      };
+      match current.get(&user.id) {
+        Some(level) => {
+          if member.access_level.is_some() && *level != member.access_level {
+            result.conflicts.push(BundleConflict {
+              kind: BundleConflictKind::LevelDiffers,
+              group: Some(bg.name.clone()),
+              username: Some(member.username.clone()),
+              message: format!("local access level {:?} kept (bundle: {:?})", level, member.access_level),
+            });
+          }
+        }
+        None => {
+          db::add_members_to_group(pool, group.id, vec![user.id], member.access_level).await?;
+          result.members_added += 1;
+        }
+      }
+    }
+  }
+
+  // 所有分组都已存在后再建立嵌套关系
+  for bg in &bundle.groups {
+    let parent_id = by_name[&bg.name].id;
+    for child in &bg.includes {
+      let outcome = match by_name.get(child) {
+        Some(c) => db::include_groups(pool, parent_id, vec![c.id]).await,
+        None => Err(anyhow!("group {} not found", child)),
+      };
+      if let Err(e) = outcome {
+        result.conflicts.push(BundleConflict {
+          kind: BundleConflictKind::IncludeFailed,
+          group: Some(bg.name.clone()),
+          username: None,
+          message: format!("include {}: {}", child, e),
+        });
+      }
+    }
+  }
+
+  tracing::info!(
+    created = result.groups_created.len(),
+    merged = result.groups_merged.len(),
+    members_added = result.members_added,
+    conflicts = result.conflicts.len(),
+    "[bundle] import merged"
+  );
+  Ok(result)
+}
@@ -1,22 +1,124 @@
-use crate::models::{LocalGroup, LocalMember, LocalMemberUpsert};
+use crate::backup;
+use crate::models::{
+  AccessLevel, BatchItemStatus, BatchJob, BatchJobItem, BatchJobKind, BatchJobStatus,
+  BatchJobSummary, ChangeOperation, ChangeRequest, ChangeRequestStatus, GitLabUser, GroupMember,
+  GroupRule, HistoryEntry, LocalGroup, LocalMember, LocalMemberQuery, LocalMemberSort,
+  LocalMemberUpsert, LocalTag, MemberSource, PolicyRule, PolicyRuleInput, PolicyRuleKind,
+  ProjectBatchResult, ProjectSnapshot, SnapshotMember, SnapshotMemberSource, TrackedProject,
+};
 use anyhow::{Context, Result};
 use chrono::Utc;
 use sqlx::{
   migrate::Migrator,
   sqlite::{SqliteConnectOptions, SqlitePoolOptions},
-  SqlitePool,
+  SqliteConnection, SqlitePool,
 };
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use std::str::FromStr;
 use tauri::Manager;
 
-pub async fn init_db(app: &tauri::AppHandle) -> Result<SqlitePool> {
+type LocalMemberRow = (
+  i64,
+  String,
+  String,
+  Option<String>,
+  String,
+  Option<i64>,
+  Option<String>,
+  Option<String>,
+  Option<String>,
+  i64,
+  Option<String>,
+  Option<String>,
+);
+
+fn local_member_from_row(r: LocalMemberRow) -> LocalMember {
+  LocalMember {
+    user_id: r.0 as u64,
+    username: r.1,
+    name: r.2,
+    avatar_url: r.3,
+    updated_at: r.4,
+    project_id: r.5.map(|x| x as u64),
+    project_name: r.6,
+    state: r.7,
+    last_synced_at: r.8,
+    missing: r.9 != 0,
+    notes: r.10,
+    email: r.11,
+    tags: Vec::new(),
+  }
+}
+
+type LocalGroupRow = (i64, String, String, i64, i64, Option<String>);
+
+/// 规则 JSON 无法解析时记录日志并在 rule_error 中标出，不当作静态分组
+fn local_group_from_row(r: LocalGroupRow) -> LocalGroup {
+  let (rule, rule_error) = match r.5.as_deref().map(serde_json::from_str::<GroupRule>) {
+    None => (None, None),
+    Some(Ok(rule)) => (Some(rule), None),
+    Some(Err(e)) => {
+      tracing::error!(group_id = r.0, error = %e, "[db] invalid group rule");
+      (None, Some(e.to_string()))
+    }
+  };
+  LocalGroup {
+    id: r.0,
+    name: r.1,
+    created_at: r.2,
+    members_count: r.3,
+    includes_count: r.4,
+    rule,
+    rule_error,
+  }
+}
+
+/// 为成员列表补充标签
+async fn attach_tags(pool: &SqlitePool, mut members: Vec<&mut LocalMember>) -> Result<()> {
+  if members.is_empty() {
+    return Ok(());
+  }
+  let ids = serde_json::to_string(&members.iter().map(|m| m.user_id).collect::<Vec<_>>())?;
+  let rows = sqlx::query_as::<_, (i64, String)>(
+    r#"SELECT mt.user_id, t.name
+       FROM local_member_tags mt
+       INNER JOIN local_tags t ON t.id = mt.tag_id
+       WHERE mt.user_id IN (SELECT value FROM json_each(?1))
+       ORDER BY t.name ASC"#,
+  )
+  .bind(ids)
+  .fetch_all(pool)
+  .await?;
+
+  let mut tags: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+  for (uid, name) in rows {
+    tags.entry(uid as u64).or_default().push(name);
+  }
+  for m in members.iter_mut() {
+    m.tags = tags.remove(&m.user_id).unwrap_or_default();
+  }
+  Ok(())
+}
+
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// 数据库文件路径：app_data_dir/gitlab_member_manager.sqlite3
+pub fn db_path(app: &tauri::AppHandle) -> Result<PathBuf> {
   let dir = app
     .path()
     .app_data_dir()
     .context("failed to resolve app_data_dir")?;
   std::fs::create_dir_all(&dir).context("failed to create app data dir")?;
+  Ok(dir.join("gitlab_member_manager.sqlite3"))
+}
 
-  let db_path = dir.join("gitlab_member_manager.sqlite3");
+pub async fn init_db(app: &tauri::AppHandle) -> Result<SqlitePool> {
+  let db_path = db_path(app)?;
+  let restored = backup::apply_pending_restore(&db_path).await?;
+  if restored.is_some() {
+    tracing::warn!("[db] database replaced by staged restore");
+  }
   // sqlx sqlite URL 在 Windows 需要使用正斜杠，否则会因反斜杠被当成转义而连接失败
   let db_url = format!(
     "sqlite://{}",
@@ -37,10 +139,24 @@ pub async fn init_db(app: &tauri::AppHandle) -> Result<SqlitePool> {
     .await
     .with_context(|| format!("failed to connect sqlite: {}", db_url))?;
 
+  // 没有备份时不执行迁移，启动直接失败，避免迁移出错后无法回退
+  backup::backup_before_migrations(&pool, &db_path)
+    .await
+    .context("pre-migration backup failed, migrations were not run")?;
+
   tracing::info!("[db] running migrations");
-  static MIGRATOR: Migrator = sqlx::migrate!();
   MIGRATOR.run(&pool).await?;
 
+  // 恢复的历史记录只能写入恢复后的数据库，暂存时写入的会随旧库一起被替换
+  if let Some(restored) = restored {
+    let detail = serde_json::json!({
+      "preRestoreBackup": restored.pre_restore_backup.map(|p| p.display().to_string()),
+    });
+    if let Err(e) = record_history(&pool, "restore_database", "database", &detail).await {
+      tracing::error!(error = %e, "[db] record restore history failed");
+    }
+  }
+
   tracing::info!("[db] database initialized successfully");
   Ok(pool)
 }
@@ -51,6 +167,7 @@ pub async fn upsert_local_members(pool: &SqlitePool, members: Vec<LocalMemberUps
   
   let mut tx = pool.begin().await?;
   let now = Utc::now().to_rfc3339();
+  let user_ids: Vec<u64> = members.iter().map(|m| m.user_id).collect();
 
   for m in members {
     sqlx::query(
@@ -66,91 +183,353 @@ pub async fn upsert_local_members(pool: &SqlitePool, members: Vec<LocalMemberUps
       "#,
     )
     .bind(m.user_id as i64)
-    .bind(m.username)
-    .bind(m.name)
-    .bind(m.avatar_url)
+    .bind(&m.username)
+    .bind(&m.name)
+    .bind(&m.avatar_url)
     .bind(&now)
     .bind(m.project_id.map(|x| x as i64))
     .bind(m.project_name.as_deref())
     .execute(&mut *tx)
     .await?;
+
+    if let Some(project_id) = m.project_id {
+      sqlx::query(
+        r#"INSERT INTO local_member_sources (user_id, project_id, project_path, access_level, first_seen, last_seen, project_name)
+           VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?6)
+           ON CONFLICT(user_id, project_id) DO UPDATE SET
+             project_path = COALESCE(excluded.project_path, local_member_sources.project_path),
+             project_name = COALESCE(excluded.project_name, local_member_sources.project_name),
+             access_level = COALESCE(excluded.access_level, local_member_sources.access_level),
+             last_seen = excluded.last_seen
+        "#,
+      )
+      .bind(m.user_id as i64)
+      .bind(project_id as i64)
+      .bind(m.project_path.as_deref().or(m.project_name.as_deref()))
+      .bind(m.access_level.map(AccessLevel::value))
+      .bind(&now)
+      .bind(m.project_name.as_deref())
+      .execute(&mut *tx)
+      .await?;
+    }
   }
 
+  reindex_local_members_fts(&mut tx, &user_ids).await?;
   tx.commit().await?;
   tracing::info!(count = count, "[db] upsert_local_members completed");
   Ok(())
 }
 
-/// 分页列出本地成员，返回 (列表, 总条数)
+/// 用 GitLab 用户信息刷新本地成员，并清除“已不存在”标记
+pub async fn sync_local_member(pool: &SqlitePool, user: &GitLabUser) -> Result<()> {
+  let now = Utc::now().to_rfc3339();
+  let mut tx = pool.begin().await?;
+  sqlx::query(
+    r#"UPDATE local_members
+       SET username = ?1, name = ?2, avatar_url = ?3, state = ?4, last_synced_at = ?5, missing = 0,
+         email = COALESCE(?6, email)
+       WHERE user_id = ?7"#,
+  )
+  .bind(&user.username)
+  .bind(&user.name)
+  .bind(&user.avatar_url)
+  .bind(&user.state)
+  .bind(&now)
+  .bind(&user.email)
+  .bind(user.id as i64)
+  .execute(&mut *tx)
+  .await?;
+  reindex_local_members_fts(&mut tx, &[user.id]).await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 标记本地成员在 GitLab 中已不存在
+pub async fn mark_local_member_missing(pool: &SqlitePool, user_id: u64) -> Result<()> {
+  let now = Utc::now().to_rfc3339();
+  sqlx::query(r#"UPDATE local_members SET missing = 1, last_synced_at = ?1 WHERE user_id = ?2"#)
+    .bind(&now)
+    .bind(user_id as i64)
+    .execute(pool)
+    .await?;
+  tracing::info!(user_id = user_id, "[db] mark_local_member_missing");
+  Ok(())
+}
+
+/// 列出本地成员出现过的所有项目（最近看到的在前）
+pub async fn list_member_sources(pool: &SqlitePool, user_id: u64) -> Result<Vec<MemberSource>> {
+  let rows = sqlx::query_as::<_, (i64, Option<String>, Option<i64>, String, String)>(
+    r#"SELECT project_id, project_path, access_level, first_seen, last_seen
+       FROM local_member_sources
+       WHERE user_id = ?1
+       ORDER BY last_seen DESC"#,
+  )
+  .bind(user_id as i64)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|r| MemberSource {
+        project_id: r.0 as u64,
+        project_path: r.1,
+        access_level: r.2.map(AccessLevel::from_value_lossy),
+        first_seen: r.3,
+        last_seen: r.4,
+      })
+      .collect(),
+  )
+}
+
+pub async fn list_local_member_ids(pool: &SqlitePool) -> Result<Vec<u64>> {
+  let rows = sqlx::query_as::<_, (i64,)>(r#"SELECT user_id FROM local_members ORDER BY user_id ASC"#)
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.into_iter().map(|r| r.0 as u64).collect())
+}
+
+/// 重建成员的全文检索行；成员已删除时只清除对应行。
+/// 在调用方修改数据的同一事务中执行，索引与数据一起提交或回滚
+async fn reindex_local_members_fts(conn: &mut SqliteConnection, user_ids: &[u64]) -> Result<()> {
+  if user_ids.is_empty() {
+    return Ok(());
+  }
+  let ids = serde_json::to_string(user_ids)?;
+
+  sqlx::query(r#"DELETE FROM local_members_fts WHERE rowid IN (SELECT value FROM json_each(?1))"#)
+    .bind(&ids)
+    .execute(&mut *conn)
+    .await?;
+  sqlx::query(
+    r#"INSERT INTO local_members_fts (rowid, username, name, email, notes, tags, projects)
+       SELECT m.user_id, m.username, m.name, COALESCE(m.email, ''), COALESCE(m.notes, ''),
+         COALESCE((SELECT group_concat(t.name, ' ') FROM local_member_tags mt INNER JOIN local_tags t ON t.id = mt.tag_id
+                   WHERE mt.user_id = m.user_id), ''),
+         COALESCE((SELECT group_concat(COALESCE(s.project_path, '') || ' ' || COALESCE(s.project_name, ''), ' ')
+                   FROM local_member_sources s WHERE s.user_id = m.user_id), '')
+       FROM local_members m
+       WHERE m.user_id IN (SELECT value FROM json_each(?1))"#,
+  )
+  .bind(&ids)
+  .execute(&mut *conn)
+  .await?;
+
+  tracing::debug!(count = user_ids.len(), "[db] reindex_local_members_fts");
+  Ok(())
+}
+
+/// 把输入转换为 FTS5 查询：每个词作为短语并按前缀匹配，词之间为“且”
+fn fts_match_query(text: &str) -> Option<String> {
+  let terms: Vec<String> = text
+    .split_whitespace()
+    .filter(|t| t.chars().any(char::is_alphanumeric))
+    .map(|t| format!("\"{}\"*", t.replace('"', "\"\"")))
+    .collect();
+  (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// 分页检索本地成员，返回 (列表, 总条数)。
+/// 分组筛选由调用方按有效成员（嵌套分组与动态规则）解析：group_member_ids 为 query.group_id 的成员，
+/// grouped_ids 为属于任一本地分组的用户，query.ungrouped 时排除
 pub async fn list_local_members(
   pool: &SqlitePool,
-  query: Option<String>,
+  query: &LocalMemberQuery,
+  group_member_ids: Option<&[u64]>,
+  grouped_ids: Option<&[u64]>,
   page: u32,
   per_page: u32,
 ) -> Result<(Vec<LocalMember>, u64)> {
   let per_page = per_page.clamp(1, 100);
   let offset = (page.saturating_sub(1)) * per_page;
+  let text = query.text.as_deref().and_then(fts_match_query);
+  let tags = (!query.tags.is_empty()).then(|| serde_json::to_string(&query.tags)).transpose()?;
+  let group_member_ids = group_member_ids.map(serde_json::to_string).transpose()?;
+  let excluded_ids = grouped_ids
+    .filter(|_| query.ungrouped)
+    .map(serde_json::to_string)
+    .transpose()?;
+  let state = query.state.as_deref().map(str::trim).filter(|s| !s.is_empty());
 
-  let (total, rows) = if let Some(q) = query {
-    let like = format!("%{}%", q);
-    let total: (i64,) = sqlx::query_as(
-      r#"SELECT COUNT(*) FROM local_members WHERE username LIKE ?1 OR name LIKE ?1"#,
-    )
-    .bind(&like)
+  // 相关度：bm25 越小越相关，用户名与昵称权重最高
+  let fts_join = if text.is_some() {
+    r#"INNER JOIN (
+         SELECT rowid AS user_id, bm25(local_members_fts, 10.0, 8.0, 5.0, 1.0, 3.0, 1.0) AS score
+         FROM local_members_fts WHERE local_members_fts MATCH ?1
+       ) f ON f.user_id = m.user_id"#
+  } else {
+    // 无检索词时仍引用 ?1，保持后续参数编号一致
+    r#"INNER JOIN (SELECT ?1 AS score) f"#
+  };
+  let filters = r#"WHERE (?2 IS NULL OR m.user_id IN (
+           SELECT mt.user_id FROM local_member_tags mt INNER JOIN local_tags t ON t.id = mt.tag_id
+           WHERE t.name IN (SELECT value FROM json_each(?2))))
+         AND (?3 IS NULL OR m.user_id IN (SELECT value FROM json_each(?3)))
+         AND (?4 IS NULL OR m.user_id NOT IN (SELECT value FROM json_each(?4)))
+         AND (?5 IS NULL OR (?5 = 'missing' AND m.missing = 1) OR m.state = ?5)"#;
+  let order = match query.sort {
+    LocalMemberSort::Relevance if text.is_some() => "f.score ASC, m.username ASC",
+    LocalMemberSort::Relevance | LocalMemberSort::Updated => "m.updated_at DESC",
+    LocalMemberSort::Name => "m.name COLLATE NOCASE ASC, m.username ASC",
+    LocalMemberSort::Username => "m.username COLLATE NOCASE ASC",
+    LocalMemberSort::GroupCount => {
+      "(SELECT COUNT(*) FROM local_group_members gm WHERE gm.user_id = m.user_id) DESC, m.username ASC"
+    }
+  };
+
+  let total: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM local_members m {} {}", fts_join, filters))
+    .bind(text.as_deref())
+    .bind(tags.as_deref())
+    .bind(group_member_ids.as_deref())
+    .bind(excluded_ids.as_deref())
+    .bind(state)
     .fetch_one(pool)
     .await?;
 
-    let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, Option<i64>, Option<String>)>(
-      r#"SELECT user_id, username, name, avatar_url, updated_at, project_id, project_name
-         FROM local_members
-         WHERE username LIKE ?1 OR name LIKE ?1
-         ORDER BY updated_at DESC
-         LIMIT ?2 OFFSET ?3
-      "#,
-    )
-    .bind(&like)
+  let sql = format!(
+    r#"SELECT m.user_id, m.username, m.name, m.avatar_url, m.updated_at, m.project_id, m.project_name,
+         m.state, m.last_synced_at, m.missing, m.notes, m.email
+       FROM local_members m {} {}
+       ORDER BY {}
+       LIMIT ?6 OFFSET ?7"#,
+    fts_join, filters, order
+  );
+  let rows = sqlx::query_as::<_, LocalMemberRow>(&sql)
+    .bind(text.as_deref())
+    .bind(tags.as_deref())
+    .bind(group_member_ids.as_deref())
+    .bind(excluded_ids.as_deref())
+    .bind(state)
     .bind(per_page as i64)
     .bind(offset as i64)
     .fetch_all(pool)
     .await?;
 
-    (total.0 as u64, rows)
-  } else {
-    let total: (i64,) = sqlx::query_as(r#"SELECT COUNT(*) FROM local_members"#)
-      .fetch_one(pool)
-      .await?;
+  tracing::debug!(count = rows.len(), total = total.0, "[db] list_local_members result");
 
-    let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, Option<i64>, Option<String>)>(
-      r#"SELECT user_id, username, name, avatar_url, updated_at, project_id, project_name
-         FROM local_members
-         ORDER BY updated_at DESC
-         LIMIT ?1 OFFSET ?2
-      "#,
-    )
-    .bind(per_page as i64)
-    .bind(offset as i64)
-    .fetch_all(pool)
+  let mut items: Vec<LocalMember> = rows.into_iter().map(local_member_from_row).collect();
+  attach_tags(pool, items.iter_mut().collect()).await?;
+
+  Ok((items, total.0 as u64))
+}
+
+pub async fn set_local_member_notes(pool: &SqlitePool, user_id: u64, notes: Option<String>) -> Result<()> {
+  let mut tx = pool.begin().await?;
+  sqlx::query(r#"UPDATE local_members SET notes = ?1 WHERE user_id = ?2"#)
+    .bind(notes.as_deref().map(str::trim).filter(|n| !n.is_empty()))
+    .bind(user_id as i64)
+    .execute(&mut *tx)
     .await?;
+  reindex_local_members_fts(&mut tx, &[user_id]).await?;
+  tx.commit().await?;
+  tracing::info!(user_id = user_id, "[db] set_local_member_notes");
+  Ok(())
+}
 
-    (total.0 as u64, rows)
-  };
+pub async fn list_local_tags(pool: &SqlitePool) -> Result<Vec<LocalTag>> {
+  let rows = sqlx::query_as::<_, (i64, String, String, i64)>(
+    r#"SELECT t.id, t.name, t.created_at,
+         (SELECT COUNT(*) FROM local_member_tags mt WHERE mt.tag_id = t.id) as members_count
+       FROM local_tags t
+       ORDER BY t.name ASC"#,
+  )
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|r| LocalTag {
+        id: r.0,
+        name: r.1,
+        created_at: r.2,
+        members_count: r.3,
+      })
+      .collect(),
+  )
+}
 
-  tracing::debug!(count = rows.len(), total = total, "[db] list_local_members result");
+/// 给成员打标签，标签不存在时自动创建
+pub async fn add_tags_to_members(pool: &SqlitePool, user_ids: Vec<u64>, tags: Vec<String>) -> Result<()> {
+  tracing::info!(user_count = user_ids.len(), tags = ?tags, "[db] add_tags_to_members");
 
-  let items: Vec<LocalMember> = rows
-    .into_iter()
-    .map(|r| LocalMember {
-      user_id: r.0 as u64,
-      username: r.1,
-      name: r.2,
-      avatar_url: r.3,
-      updated_at: r.4,
-      project_id: r.5.map(|x| x as u64),
-      project_name: r.6,
-    })
-    .collect();
+  let now = Utc::now().to_rfc3339();
+  let mut tx = pool.begin().await?;
+  for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+    sqlx::query(r#"INSERT OR IGNORE INTO local_tags (name, created_at) VALUES (?1, ?2)"#)
+      .bind(tag)
+      .bind(&now)
+      .execute(&mut *tx)
+      .await?;
+    let (tag_id,): (i64,) = sqlx::query_as(r#"SELECT id FROM local_tags WHERE name = ?1"#)
+      .bind(tag)
+      .fetch_one(&mut *tx)
+      .await?;
+    for uid in &user_ids {
+      sqlx::query(r#"INSERT OR IGNORE INTO local_member_tags (user_id, tag_id, created_at) VALUES (?1, ?2, ?3)"#)
+        .bind(*uid as i64)
+        .bind(tag_id)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+    }
+  }
+  reindex_local_members_fts(&mut tx, &user_ids).await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+pub async fn remove_tags_from_members(pool: &SqlitePool, user_ids: Vec<u64>, tags: Vec<String>) -> Result<()> {
+  tracing::info!(user_count = user_ids.len(), tags = ?tags, "[db] remove_tags_from_members");
+
+  let mut tx = pool.begin().await?;
+  for tag in &tags {
+    for uid in &user_ids {
+      sqlx::query(
+        r#"DELETE FROM local_member_tags
+           WHERE user_id = ?1 AND tag_id = (SELECT id FROM local_tags WHERE name = ?2)"#,
+      )
+      .bind(*uid as i64)
+      .bind(tag.trim())
+      .execute(&mut *tx)
+      .await?;
+    }
+  }
+  reindex_local_members_fts(&mut tx, &user_ids).await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+pub async fn delete_local_tag(pool: &SqlitePool, tag_id: i64) -> Result<()> {
+  let mut tx = pool.begin().await?;
+  let tagged: Vec<(i64,)> = sqlx::query_as(r#"SELECT user_id FROM local_member_tags WHERE tag_id = ?1"#)
+    .bind(tag_id)
+    .fetch_all(&mut *tx)
+    .await?;
+  // local_member_tags 的 tag_id 有 ON DELETE CASCADE
+  sqlx::query(r#"DELETE FROM local_tags WHERE id = ?1"#)
+    .bind(tag_id)
+    .execute(&mut *tx)
+    .await?;
+  reindex_local_members_fts(&mut tx, &tagged.into_iter().map(|r| r.0 as u64).collect::<Vec<_>>()).await?;
+  tx.commit().await?;
+  tracing::info!(tag_id = tag_id, "[db] delete_local_tag");
+  Ok(())
+}
 
-  Ok((items, total))
+/// 带有任一标签的成员 ID，用作批量操作的动态选择器
+pub async fn list_member_ids_by_tags(pool: &SqlitePool, tags: &[String]) -> Result<Vec<u64>> {
+  let rows = sqlx::query_as::<_, (i64,)>(
+    r#"SELECT DISTINCT mt.user_id
+       FROM local_member_tags mt
+       INNER JOIN local_tags t ON t.id = mt.tag_id
+       WHERE t.name IN (SELECT value FROM json_each(?1))
+       ORDER BY mt.user_id ASC"#,
+  )
+  .bind(serde_json::to_string(tags)?)
+  .fetch_all(pool)
+  .await?;
+  Ok(rows.into_iter().map(|r| r.0 as u64).collect())
 }
 
 pub async fn delete_local_members(pool: &SqlitePool, user_ids: Vec<u64>) -> Result<()> {
@@ -165,6 +544,7 @@ pub async fn delete_local_members(pool: &SqlitePool, user_ids: Vec<u64>) -> Resu
       .execute(&mut *tx)
       .await?;
   }
+  reindex_local_members_fts(&mut tx, &user_ids).await?;
   tx.commit().await?;
   tracing::info!(count = user_ids.len(), "[db] delete_local_members");
   Ok(())
@@ -189,9 +569,102 @@ pub async fn create_local_group(pool: &SqlitePool, name: String) -> Result<Local
     name,
     created_at: now,
     members_count: 0,
+    includes_count: 0,
+    rule: None,
+    rule_error: None,
+  })
+}
+
+/// 创建动态分组
+pub async fn create_smart_group(pool: &SqlitePool, name: String, rule: GroupRule) -> Result<LocalGroup> {
+  tracing::info!(name = %name, rule = ?rule, "[db] create_smart_group");
+
+  let now = Utc::now().to_rfc3339();
+  let res = sqlx::query(r#"INSERT INTO local_groups (name, created_at, rule) VALUES (?1, ?2, ?3)"#)
+    .bind(&name)
+    .bind(&now)
+    .bind(serde_json::to_string(&rule)?)
+    .execute(pool)
+    .await?;
+
+  Ok(LocalGroup {
+    id: res.last_insert_rowid(),
+    name,
+    created_at: now,
+    members_count: 0,
+    includes_count: 0,
+    rule: Some(rule),
+    rule_error: None,
   })
 }
 
+/// 设置分组规则；rule 为空时转为静态分组
+pub async fn set_group_rule(pool: &SqlitePool, group_id: i64, rule: Option<&GroupRule>) -> Result<()> {
+  let json = rule.map(serde_json::to_string).transpose()?;
+  sqlx::query(r#"UPDATE local_groups SET rule = ?1 WHERE id = ?2"#)
+    .bind(json)
+    .bind(group_id)
+    .execute(pool)
+    .await?;
+  tracing::info!(group_id = group_id, rule = ?rule, "[db] set_group_rule");
+  Ok(())
+}
+
+/// 该分组（effective=true 时含所有子孙分组）中带规则的分组：(id, name, rule)
+pub async fn list_rule_groups(pool: &SqlitePool, group_id: i64, effective: bool) -> Result<Vec<(i64, String, GroupRule)>> {
+  let rows = sqlx::query_as::<_, (i64, String, String)>(
+    r#"WITH RECURSIVE tree(id) AS (
+         SELECT ?1
+         UNION
+         SELECT i.child_id FROM local_group_includes i INNER JOIN tree t ON i.parent_id = t.id WHERE ?2
+       )
+       SELECT g.id, g.name, g.rule
+       FROM tree
+       INNER JOIN local_groups g ON g.id = tree.id
+       WHERE g.rule IS NOT NULL"#,
+  )
+  .bind(group_id)
+  .bind(effective)
+  .fetch_all(pool)
+  .await?;
+
+  let mut groups = Vec::new();
+  for (id, name, json) in rows {
+    let rule = serde_json::from_str(&json).with_context(|| format!("parse rule of group {}", id))?;
+    groups.push((id, name, rule));
+  }
+  Ok(groups)
+}
+
+/// 按规则中的本地条件（标签、用户名、来源项目）筛选本地成员；GitLab 分组条件由调用方再过滤
+pub async fn list_members_by_rule(pool: &SqlitePool, rule: &GroupRule) -> Result<Vec<LocalMember>> {
+  let tags = (!rule.tags.is_empty()).then(|| serde_json::to_string(&rule.tags)).transpose()?;
+  let pattern = rule.username_pattern.as_deref().map(str::trim).filter(|p| !p.is_empty());
+  let source = rule.source_project.as_deref().map(str::trim).filter(|p| !p.is_empty());
+
+  let rows = sqlx::query_as::<_, LocalMemberRow>(
+    r#"SELECT user_id, username, name, avatar_url, updated_at, project_id, project_name, state, last_synced_at, missing, notes, email
+       FROM local_members
+       WHERE (?1 IS NULL OR user_id IN (
+           SELECT mt.user_id FROM local_member_tags mt INNER JOIN local_tags t ON t.id = mt.tag_id
+           WHERE t.name IN (SELECT value FROM json_each(?1))))
+         AND (?2 IS NULL OR username GLOB ?2)
+         AND (?3 IS NULL OR user_id IN (
+           SELECT s.user_id FROM local_member_sources s
+           WHERE CAST(s.project_id AS TEXT) = ?3 OR s.project_path = ?3))
+       ORDER BY username ASC"#,
+  )
+  .bind(tags)
+  .bind(pattern)
+  .bind(source)
+  .fetch_all(pool)
+  .await?;
+
+  let mut members: Vec<LocalMember> = rows.into_iter().map(local_member_from_row).collect();
+  attach_tags(pool, members.iter_mut().collect()).await?;
+  Ok(members)
+}
+
 pub async fn update_local_group(pool: &SqlitePool, id: i64, name: String) -> Result<()> {
   sqlx::query(r#"UPDATE local_groups SET name = ?1 WHERE id = ?2"#)
     .bind(&name)
@@ -215,9 +688,11 @@ pub async fn delete_local_group(pool: &SqlitePool, id: i64) -> Result<()> {
 pub async fn list_local_groups(pool: &SqlitePool) -> Result<Vec<LocalGroup>> {
   tracing::debug!("[db] list_local_groups");
   
-  let rows = sqlx::query_as::<_, (i64, String, String, i64)>(
+  let rows = sqlx::query_as::<_, LocalGroupRow>(
     r#"
-    SELECT g.id, g.name, g.created_at, COUNT(gm.user_id) as members_count
+    SELECT g.id, g.name, g.created_at, COUNT(gm.user_id) as members_count,
+      (SELECT COUNT(*) FROM local_group_includes i WHERE i.parent_id = g.id) as includes_count,
+      g.rule
     FROM local_groups g
     LEFT JOIN local_group_members gm ON gm.group_id = g.id
     GROUP BY g.id
@@ -232,31 +707,35 @@ pub async fn list_local_groups(pool: &SqlitePool) -> Result<Vec<LocalGroup>> {
   Ok(
     rows
       .into_iter()
-      .map(|r| LocalGroup {
-        id: r.0,
-        name: r.1,
-        created_at: r.2,
-        members_count: r.3,
-      })
+      .map(local_group_from_row)
       .collect(),
   )
 }
 
-pub async fn add_members_to_group(pool: &SqlitePool, group_id: i64, user_ids: Vec<u64>) -> Result<()> {
+/// 加入分组；access_level 为空时保留成员原有的分组权限
+pub async fn add_members_to_group(
+  pool: &SqlitePool,
+  group_id: i64,
+  user_ids: Vec<u64>,
+  access_level: Option<AccessLevel>,
+) -> Result<()> {
   let count = user_ids.len();
-  tracing::info!(group_id = group_id, count = count, "[db] add_members_to_group");
+  tracing::info!(group_id = group_id, count = count, access_level = ?access_level, "[db] add_members_to_group");
   
   let mut tx = pool.begin().await?;
   let now = Utc::now().to_rfc3339();
 
   for uid in user_ids {
     sqlx::query(
-      r#"INSERT OR IGNORE INTO local_group_members (group_id, user_id, created_at)
-         VALUES (?1, ?2, ?3)"#,
+      r#"INSERT INTO local_group_members (group_id, user_id, created_at, access_level)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(group_id, user_id) DO UPDATE SET
+           access_level = COALESCE(excluded.access_level, local_group_members.access_level)"#,
     )
     .bind(group_id)
     .bind(uid as i64)
     .bind(&now)
+    .bind(access_level.map(AccessLevel::value))
     .execute(&mut *tx)
     .await?;
   }
@@ -287,47 +766,338 @@ pub async fn remove_members_from_group(pool: &SqlitePool, group_id: i64, user_id
   Ok(())
 }
 
-pub async fn list_group_members(pool: &SqlitePool, group_id: i64) -> Result<Vec<LocalMember>> {
-  tracing::debug!(group_id = group_id, "[db] list_group_members");
-  
-  let rows = sqlx::query_as::<_, (i64, String, String, Option<String>, String, Option<i64>, Option<String>)>(
-    r#"SELECT m.user_id, m.username, m.name, m.avatar_url, m.updated_at, m.project_id, m.project_name
-       FROM local_members m
-       INNER JOIN local_group_members gm ON gm.user_id = m.user_id
-       WHERE gm.group_id = ?1
-       ORDER BY m.username ASC"#,
+pub async fn set_group_members_access_level(
+  pool: &SqlitePool,
+  group_id: i64,
+  user_ids: Vec<u64>,
+  access_level: Option<AccessLevel>,
+) -> Result<()> {
+  let count = user_ids.len();
+  tracing::info!(group_id = group_id, count = count, access_level = ?access_level, "[db] set_group_members_access_level");
+
+  let mut tx = pool.begin().await?;
+  for uid in user_ids {
+    sqlx::query(r#"UPDATE local_group_members SET access_level = ?1 WHERE group_id = ?2 AND user_id = ?3"#)
+      .bind(access_level.map(AccessLevel::value))
+      .bind(group_id)
+      .bind(uid as i64)
+      .execute(&mut *tx)
+      .await?;
+  }
+  tx.commit().await?;
+  Ok(())
+}
+
+/// from_id 是否（直接或间接）包含 target_id，用于嵌套前的环检测
+async fn group_contains(conn: &mut sqlx::SqliteConnection, from_id: i64, target_id: i64) -> Result<bool> {
+  let row: Option<(i64,)> = sqlx::query_as(
+    r#"WITH RECURSIVE tree(id) AS (
+         SELECT ?1
+         UNION
+         SELECT i.child_id FROM local_group_includes i INNER JOIN tree t ON i.parent_id = t.id
+       )
+       SELECT id FROM tree WHERE id = ?2"#,
+  )
+  .bind(from_id)
+  .bind(target_id)
+  .fetch_optional(&mut *conn)
+  .await?;
+  Ok(row.is_some())
+}
+
+/// 把子分组嵌套进父分组；任一子分组会形成环时整体回滚
+pub async fn include_groups(pool: &SqlitePool, parent_id: i64, child_ids: Vec<i64>) -> Result<()> {
+  let count = child_ids.len();
+  tracing::info!(parent_id = parent_id, count = count, "[db] include_groups");
+
+  let mut tx = pool.begin().await?;
+  let now = Utc::now().to_rfc3339();
+
+  for child_id in child_ids {
+    if group_contains(&mut tx, child_id, parent_id).await? {
+      anyhow::bail!("including group {} into group {} would create a cycle", child_id, parent_id);
+    }
+    sqlx::query(
+      r#"INSERT OR IGNORE INTO local_group_includes (parent_id, child_id, created_at)
+         VALUES (?1, ?2, ?3)"#,
+    )
+    .bind(parent_id)
+    .bind(child_id)
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+  }
+
+  tx.commit().await?;
+  tracing::info!(parent_id = parent_id, count = count, "[db] include_groups completed");
+  Ok(())
+}
+
+pub async fn exclude_groups(pool: &SqlitePool, parent_id: i64, child_ids: Vec<i64>) -> Result<()> {
+  let count = child_ids.len();
+  tracing::info!(parent_id = parent_id, count = count, "[db] exclude_groups");
+
+  let mut tx = pool.begin().await?;
+  for child_id in child_ids {
+    sqlx::query(r#"DELETE FROM local_group_includes WHERE parent_id = ?1 AND child_id = ?2"#)
+      .bind(parent_id)
+      .bind(child_id)
+      .execute(&mut *tx)
+      .await?;
+  }
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 列出直接嵌套在该分组下的子分组
+pub async fn list_group_includes(pool: &SqlitePool, group_id: i64) -> Result<Vec<LocalGroup>> {
+  let rows = sqlx::query_as::<_, LocalGroupRow>(
+    r#"
+    SELECT g.id, g.name, g.created_at,
+      (SELECT COUNT(*) FROM local_group_members gm WHERE gm.group_id = g.id) as members_count,
+      (SELECT COUNT(*) FROM local_group_includes c WHERE c.parent_id = g.id) as includes_count,
+      g.rule
+    FROM local_group_includes i
+    INNER JOIN local_groups g ON g.id = i.child_id
+    WHERE i.parent_id = ?1
+    ORDER BY g.name ASC
+    "#,
   )
   .bind(group_id)
   .fetch_all(pool)
   .await?;
 
-  tracing::debug!(group_id = group_id, count = rows.len(), "[db] list_group_members result");
-  
   Ok(
     rows
       .into_iter()
-      .map(|r| LocalMember {
-        user_id: r.0 as u64,
-        username: r.1,
-        name: r.2,
-        avatar_url: r.3,
-        updated_at: r.4,
-        project_id: r.5.map(|x| x as u64),
-        project_name: r.6,
-      })
+      .map(local_group_from_row)
       .collect(),
   )
 }
 
-/// 从 config 表读取 GitLab 配置，key = "gitlab"，value 为 JSON：{ "baseUrl": "...", "token": "..." }
-pub async fn get_gitlab_config(pool: &SqlitePool) -> Result<Option<(String, String)>> {
-  let row = sqlx::query_as::<_, (String,)>(
-    r#"SELECT value FROM config WHERE key = 'gitlab'"#,
-  )
-  .fetch_optional(pool)
-  .await?;
+/// 列出分组成员。effective=false 只返回直接成员；
+/// effective=true 递归展开子分组，按 user_id 去重，权限取各来源中的最高值
+pub async fn list_group_members(pool: &SqlitePool, group_id: i64, effective: bool) -> Result<Vec<GroupMember>> {
+  tracing::debug!(group_id = group_id, effective = effective, "[db] list_group_members");
 
-  let Some((json,)) = row else {
+  if !effective {
+    let rows = sqlx::query_as::<
+      _,
+      (
+        i64,
+        String,
+        String,
+        Option<String>,
+        String,
+        Option<i64>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        i64,
+        Option<String>,
+        Option<String>,
+        Option<i64>,
+      ),
+    >(
+      r#"SELECT m.user_id, m.username, m.name, m.avatar_url, m.updated_at, m.project_id, m.project_name,
+           m.state, m.last_synced_at, m.missing, m.notes, m.email, gm.access_level
+         FROM local_members m
+         INNER JOIN local_group_members gm ON gm.user_id = m.user_id
+         WHERE gm.group_id = ?1
+         ORDER BY m.username ASC"#,
+    )
+    .bind(group_id)
+    .fetch_all(pool)
+    .await?;
+
+    tracing::debug!(group_id = group_id, count = rows.len(), "[db] list_group_members result");
+
+    let mut members: Vec<GroupMember> = rows
+      .into_iter()
+      .map(|r| GroupMember {
+        member: local_member_from_row((r.0, r.1, r.2, r.3, r.4, r.5, r.6, r.7, r.8, r.9, r.10, r.11)),
+        access_level: r.12.map(AccessLevel::from_value_lossy),
+        direct: true,
+        via_groups: Vec::new(),
+      })
+      .collect();
+    attach_tags(pool, members.iter_mut().map(|m| &mut m.member).collect()).await?;
+    return Ok(members);
+  }
+
+  let rows = sqlx::query_as::<
+    _,
+    (
+      i64,
+      String,
+      Option<i64>,
+      i64,
+      String,
+      String,
+      Option<String>,
+      String,
+      Option<i64>,
+      Option<String>,
+      Option<String>,
+      Option<String>,
+      i64,
+      Option<String>,
+      Option<String>,
+    ),
+  >(
+    r#"WITH RECURSIVE tree(id) AS (
+         SELECT ?1
+         UNION
+         SELECT i.child_id FROM local_group_includes i INNER JOIN tree t ON i.parent_id = t.id
+       )
+       SELECT g.id, g.name, gm.access_level,
+         m.user_id, m.username, m.name, m.avatar_url, m.updated_at, m.project_id, m.project_name,
+         m.state, m.last_synced_at, m.missing, m.notes, m.email
+       FROM tree
+       INNER JOIN local_groups g ON g.id = tree.id
+       INNER JOIN local_group_members gm ON gm.group_id = g.id
+       INNER JOIN local_members m ON m.user_id = gm.user_id
+       ORDER BY m.username ASC, g.name ASC"#,
+  )
+  .bind(group_id)
+  .fetch_all(pool)
+  .await?;
+
+  // 同一用户可能经由多个分组出现：合并为一条，权限取最高
+  let mut order: Vec<u64> = Vec::new();
+  let mut resolved: BTreeMap<u64, GroupMember> = BTreeMap::new();
+  for r in rows {
+    let (source_id, source_name, level) = (r.0, r.1, r.2);
+    let user_id = r.3 as u64;
+    let entry = resolved.entry(user_id).or_insert_with(|| {
+      order.push(user_id);
+      GroupMember {
+        member: local_member_from_row((r.3, r.4, r.5, r.6, r.7, r.8, r.9, r.10, r.11, r.12, r.13, r.14)),
+        access_level: None,
+        direct: false,
+        via_groups: Vec::new(),
+      }
+    });
+    entry.access_level = entry.access_level.max(level.map(AccessLevel::from_value_lossy));
+    if source_id == group_id {
+      entry.direct = true;
+    } else {
+      entry.via_groups.push(source_name);
+    }
+  }
+
+  tracing::debug!(group_id = group_id, count = order.len(), "[db] list_group_members effective result");
+
+  let mut members: Vec<GroupMember> = order.into_iter().filter_map(|uid| resolved.remove(&uid)).collect();
+  attach_tags(pool, members.iter_mut().map(|m| &mut m.member).collect()).await?;
+  Ok(members)
+}
+
+/// 列出该用户直接所在的本地分组
+pub async fn list_member_groups(pool: &SqlitePool, user_id: u64) -> Result<Vec<LocalGroup>> {
+  let rows = sqlx::query_as::<_, LocalGroupRow>(
+    r#"
+    SELECT g.id, g.name, g.created_at,
+      (SELECT COUNT(*) FROM local_group_members c WHERE c.group_id = g.id) as members_count,
+      (SELECT COUNT(*) FROM local_group_includes i WHERE i.parent_id = g.id) as includes_count,
+      g.rule
+    FROM local_group_members gm
+    INNER JOIN local_groups g ON g.id = gm.group_id
+    WHERE gm.user_id = ?1
+    ORDER BY g.name ASC
+    "#,
+  )
+  .bind(user_id as i64)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(local_group_from_row)
+      .collect(),
+  )
+}
+
+/// 把用户从所有本地分组中移除（保留本地成员库记录），返回移除的关联条数
+pub async fn remove_member_from_all_groups(pool: &SqlitePool, user_id: u64) -> Result<u64> {
+  let res = sqlx::query(r#"DELETE FROM local_group_members WHERE user_id = ?1"#)
+    .bind(user_id as i64)
+    .execute(pool)
+    .await?;
+  tracing::info!(user_id = user_id, count = res.rows_affected(), "[db] remove_member_from_all_groups");
+  Ok(res.rows_affected())
+}
+
+/// 记录一条操作历史，返回历史 ID
+pub async fn record_history(pool: &SqlitePool, action: &str, target: &str, detail: &serde_json::Value) -> Result<i64> {
+  let now = Utc::now().to_rfc3339();
+  let res = sqlx::query(
+    r#"INSERT INTO operation_history (action, target, detail, created_at) VALUES (?1, ?2, ?3, ?4)"#,
+  )
+  .bind(action)
+  .bind(target)
+  .bind(detail.to_string())
+  .bind(&now)
+  .execute(pool)
+  .await?;
+  tracing::info!(action = %action, target = %target, "[db] record_history");
+  Ok(res.last_insert_rowid())
+}
+
+/// 分页列出操作历史（按时间倒序），可按 action 过滤，返回 (列表, 总条数)
+pub async fn list_history(
+  pool: &SqlitePool,
+  action: Option<String>,
+  page: u32,
+  per_page: u32,
+) -> Result<(Vec<HistoryEntry>, u64)> {
+  let per_page = per_page.clamp(1, 100);
+  let offset = (page.saturating_sub(1)) * per_page;
+
+  let total: (i64,) = sqlx::query_as(
+    r#"SELECT COUNT(*) FROM operation_history WHERE ?1 IS NULL OR action = ?1"#,
+  )
+  .bind(action.as_deref())
+  .fetch_one(pool)
+  .await?;
+
+  let rows = sqlx::query_as::<_, (i64, String, String, String, String)>(
+    r#"SELECT id, action, target, detail, created_at
+       FROM operation_history
+       WHERE ?1 IS NULL OR action = ?1
+       ORDER BY id DESC
+       LIMIT ?2 OFFSET ?3"#,
+  )
+  .bind(action.as_deref())
+  .bind(per_page as i64)
+  .bind(offset as i64)
+  .fetch_all(pool)
+  .await?;
+
+  let items = rows
+    .into_iter()
+    .map(|r| HistoryEntry {
+      id: r.0,
+      action: r.1,
+      target: r.2,
+      detail: serde_json::from_str(&r.3).unwrap_or(serde_json::Value::String(r.3)),
+      created_at: r.4,
+    })
+    .collect();
+
+  Ok((items, total.0 as u64))
+}
+
+/// 从 config 表读取 GitLab 配置，key = "gitlab"，value 为 JSON：{ "baseUrl": "...", "token": "..." }
+pub async fn get_gitlab_config(pool: &SqlitePool) -> Result<Option<(String, String)>> {
+  let row = sqlx::query_as::<_, (String,)>(
+    r#"SELECT value FROM config WHERE key = 'gitlab'"#,
+  )
+  .fetch_optional(pool)
+  .await?;
+
+  let Some((json,)) = row else {
     return Ok(None);
   };
 
@@ -352,3 +1122,650 @@ pub async fn set_gitlab_config(pool: &SqlitePool, base_url: &str, token: &str) -
   .await?;
   Ok(())
 }
+
+/// 读取 config 表中以 JSON 保存的配置项
+pub async fn get_config_json<T: serde::de::DeserializeOwned>(pool: &SqlitePool, key: &str) -> Result<Option<T>> {
+  let row = sqlx::query_as::<_, (String,)>(r#"SELECT value FROM config WHERE key = ?1"#)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+
+  let Some((json,)) = row else {
+    return Ok(None);
+  };
+  let value = serde_json::from_str(&json).with_context(|| format!("parse config json: {}", key))?;
+  Ok(Some(value))
+}
+
+/// 以 JSON 保存配置项到 config 表
+pub async fn set_config_json<T: serde::Serialize>(pool: &SqlitePool, key: &str, value: &T) -> Result<()> {
+  let json = serde_json::to_string(value)?;
+  sqlx::query(
+    r#"INSERT INTO config (key, value) VALUES (?1, ?2)
+       ON CONFLICT(key) DO UPDATE SET value = excluded.value"#,
+  )
+  .bind(key)
+  .bind(&json)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn add_tracked_project(pool: &SqlitePool, project: &str, name: Option<String>) -> Result<()> {
+  let now = Utc::now().to_rfc3339();
+  sqlx::query(
+    r#"INSERT INTO tracked_projects (project, name, created_at) VALUES (?1, ?2, ?3)
+       ON CONFLICT(project) DO UPDATE SET name = COALESCE(excluded.name, tracked_projects.name)"#,
+  )
+  .bind(project)
+  .bind(name)
+  .bind(&now)
+  .execute(pool)
+  .await?;
+  tracing::info!(project = %project, "[db] add_tracked_project");
+  Ok(())
+}
+
+pub async fn remove_tracked_project(pool: &SqlitePool, project: &str) -> Result<()> {
+  sqlx::query(r#"DELETE FROM tracked_projects WHERE project = ?1"#)
+    .bind(project)
+    .execute(pool)
+    .await?;
+  tracing::info!(project = %project, "[db] remove_tracked_project");
+  Ok(())
+}
+
+pub async fn list_tracked_projects(pool: &SqlitePool) -> Result<Vec<TrackedProject>> {
+  let rows = sqlx::query_as::<_, (String, Option<String>, String)>(
+    r#"SELECT project, name, created_at FROM tracked_projects ORDER BY created_at ASC"#,
+  )
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|r| TrackedProject {
+        project: r.0,
+        name: r.1,
+        created_at: r.2,
+      })
+      .collect(),
+  )
+}
+
+/// 保存一份项目成员快照，返回快照 ID
+pub async fn create_snapshot(pool: &SqlitePool, project: &str, reason: &str, members: &[SnapshotMember]) -> Result<i64> {
+  let now = Utc::now().to_rfc3339();
+  let mut tx = pool.begin().await?;
+
+  let res = sqlx::query(
+    r#"INSERT INTO project_snapshots (project, reason, member_count, created_at) VALUES (?1, ?2, ?3, ?4)"#,
+  )
+  .bind(project)
+  .bind(reason)
+  .bind(members.len() as i64)
+  .bind(&now)
+  .execute(&mut *tx)
+  .await?;
+  let snapshot_id = res.last_insert_rowid();
+
+  for m in members {
+    sqlx::query(
+      r#"INSERT OR REPLACE INTO project_snapshot_members
+         (snapshot_id, user_id, username, name, access_level, expires_at, source, member_role_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"#,
+    )
+    .bind(snapshot_id)
+    .bind(m.user_id as i64)
+    .bind(&m.username)
+    .bind(&m.name)
+    .bind(m.access_level.value())
+    .bind(&m.expires_at)
+    .bind(m.source.as_str())
+    .bind(m.member_role_id.map(|id| id as i64))
+    .execute(&mut *tx)
+    .await?;
+  }
+
+  tx.commit().await?;
+  tracing::info!(project = %project, reason = %reason, snapshot_id = snapshot_id, count = members.len(), "[db] create_snapshot");
+  Ok(snapshot_id)
+}
+
+/// 列出项目的快照（按时间倒序）。快照以项目 ID 为 key；alias 为调用方传入的原始标识，用于查出旧版本按路径保存的快照
+pub async fn list_project_snapshots(pool: &SqlitePool, project_id: &str, alias: &str) -> Result<Vec<ProjectSnapshot>> {
+  let rows = sqlx::query_as::<_, (i64, String, String, i64, String)>(
+    r#"SELECT id, project, reason, member_count, created_at
+       FROM project_snapshots
+       WHERE project = ?1 OR project = ?2
+       ORDER BY created_at DESC, id DESC"#,
+  )
+  .bind(project_id)
+  .bind(alias)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|r| ProjectSnapshot {
+        id: r.0,
+        project: r.1,
+        reason: r.2,
+        member_count: r.3,
+        created_at: r.4,
+      })
+      .collect(),
+  )
+}
+
+/// 读取快照及其成员，不存在时返回 None
+pub async fn get_snapshot(pool: &SqlitePool, snapshot_id: i64) -> Result<Option<(ProjectSnapshot, Vec<SnapshotMember>)>> {
+  let row = sqlx::query_as::<_, (i64, String, String, i64, String)>(
+    r#"SELECT id, project, reason, member_count, created_at FROM project_snapshots WHERE id = ?1"#,
+  )
+  .bind(snapshot_id)
+  .fetch_optional(pool)
+  .await?;
+
+  let Some(r) = row else {
+    return Ok(None);
+  };
+  let snapshot = ProjectSnapshot {
+    id: r.0,
+    project: r.1,
+    reason: r.2,
+    member_count: r.3,
+    created_at: r.4,
+  };
+
+  let rows = sqlx::query_as::<_, (i64, String, String, i64, Option<String>, String, Option<i64>)>(
+    r#"SELECT user_id, username, name, access_level, expires_at, source, member_role_id
+       FROM project_snapshot_members
+       WHERE snapshot_id = ?1
+       ORDER BY username ASC"#,
+  )
+  .bind(snapshot_id)
+  .fetch_all(pool)
+  .await?;
+
+  let members = rows
+    .into_iter()
+    .map(|r| SnapshotMember {
+      user_id: r.0 as u64,
+      username: r.1,
+      name: r.2,
+      access_level: AccessLevel::from_value_lossy(r.3),
+      expires_at: r.4,
+      source: SnapshotMemberSource::parse(&r.5),
+      member_role_id: r.6.map(|id| id as u64),
+    })
+    .collect();
+
+  Ok(Some((snapshot, members)))
+}
+
+/// 创建批量任务（状态 running）及其条目，返回任务 ID；条目随执行逐个更新状态
+pub async fn create_batch_job(
+  pool: &SqlitePool,
+  kind: BatchJobKind,
+  project: &str,
+  snapshot_id: Option<i64>,
+  undo_of: Option<i64>,
+  items: &[BatchJobItem],
+) -> Result<i64> {
+  let now = Utc::now().to_rfc3339();
+  let mut tx = pool.begin().await?;
+
+  let res = sqlx::query(
+    r#"INSERT INTO batch_jobs (kind, project, status, snapshot_id, undo_of, created_at)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+  )
+  .bind(kind.as_str())
+  .bind(project)
+  .bind(BatchJobStatus::Running.as_str())
+  .bind(snapshot_id)
+  .bind(undo_of)
+  .bind(&now)
+  .execute(&mut *tx)
+  .await?;
+  let job_id = res.last_insert_rowid();
+
+  for item in items {
+    sqlx::query(
+      r#"INSERT OR REPLACE INTO batch_job_items
+         (job_id, user_id, access_level, expires_at, prior_access_level, prior_expires_at, status, message,
+          member_role_id, prior_member_role_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"#,
+    )
+    .bind(job_id)
+    .bind(item.user_id as i64)
+    .bind(item.access_level.map(AccessLevel::value))
+    .bind(&item.expires_at)
+    .bind(item.prior_access_level.map(AccessLevel::value))
+    .bind(&item.prior_expires_at)
+    .bind(item.status.as_str())
+    .bind(&item.message)
+    .bind(item.member_role_id.map(|id| id as i64))
+    .bind(item.prior_member_role_id.map(|id| id as i64))
+    .execute(&mut *tx)
+    .await?;
+  }
+
+  tx.commit().await?;
+  tracing::info!(job_id = job_id, kind = kind.as_str(), project = %project, count = items.len(), "[db] create_batch_job");
+  Ok(job_id)
+}
+
+type BatchJobRow = (i64, String, String, String, Option<i64>, Option<i64>, String, Option<String>);
+
+fn batch_job_from_row(r: BatchJobRow) -> BatchJob {
+  BatchJob {
+    id: r.0,
+    kind: BatchJobKind::parse(&r.1),
+    project: r.2,
+    status: BatchJobStatus::parse(&r.3),
+    snapshot_id: r.4,
+    undo_of: r.5,
+    created_at: r.6,
+    finished_at: r.7,
+  }
+}
+
+/// 读取批量任务及其条目，不存在时返回 None
+pub async fn get_batch_job(pool: &SqlitePool, job_id: i64) -> Result<Option<(BatchJob, Vec<BatchJobItem>)>> {
+  let row = sqlx::query_as::<_, BatchJobRow>(
+    r#"SELECT id, kind, project, status, snapshot_id, undo_of, created_at, finished_at
+       FROM batch_jobs WHERE id = ?1"#,
+  )
+  .bind(job_id)
+  .fetch_optional(pool)
+  .await?;
+
+  let Some(r) = row else {
+    return Ok(None);
+  };
+
+  let rows = sqlx::query_as::<
+    _,
+    (i64, Option<i64>, Option<String>, Option<i64>, Option<String>, String, Option<String>, Option<i64>, Option<i64>),
+  >(
+    r#"SELECT user_id, access_level, expires_at, prior_access_level, prior_expires_at, status, message,
+         member_role_id, prior_member_role_id
+       FROM batch_job_items
+       WHERE job_id = ?1
+       ORDER BY user_id ASC"#,
+  )
+  .bind(job_id)
+  .fetch_all(pool)
+  .await?;
+
+  let items = rows
+    .into_iter()
+    .map(|r| BatchJobItem {
+      user_id: r.0 as u64,
+      access_level: r.1.map(AccessLevel::from_value_lossy),
+      expires_at: r.2,
+      prior_access_level: r.3.map(AccessLevel::from_value_lossy),
+      prior_expires_at: r.4,
+      member_role_id: r.7.map(|id| id as u64),
+      prior_member_role_id: r.8.map(|id| id as u64),
+      status: BatchItemStatus::parse(&r.5),
+      message: r.6,
+    })
+    .collect();
+
+  Ok(Some((batch_job_from_row(r), items)))
+}
+
+pub async fn update_batch_job_item(
+  pool: &SqlitePool,
+  job_id: i64,
+  user_id: u64,
+  status: BatchItemStatus,
+  message: Option<&str>,
+) -> Result<()> {
+  sqlx::query(r#"UPDATE batch_job_items SET status = ?1, message = ?2 WHERE job_id = ?3 AND user_id = ?4"#)
+    .bind(status.as_str())
+    .bind(message)
+    .bind(job_id)
+    .bind(user_id as i64)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
+
+/// 把失败条目重置为 pending，返回重置条数
+pub async fn reset_failed_batch_items(pool: &SqlitePool, job_id: i64) -> Result<u64> {
+  let res = sqlx::query(r#"UPDATE batch_job_items SET status = ?1, message = NULL WHERE job_id = ?2 AND status = ?3"#)
+    .bind(BatchItemStatus::Pending.as_str())
+    .bind(job_id)
+    .bind(BatchItemStatus::Failed.as_str())
+    .execute(pool)
+    .await?;
+  tracing::info!(job_id = job_id, count = res.rows_affected(), "[db] reset_failed_batch_items");
+  Ok(res.rows_affected())
+}
+
+pub async fn finish_batch_job(pool: &SqlitePool, job_id: i64) -> Result<()> {
+  let now = Utc::now().to_rfc3339();
+  sqlx::query(r#"UPDATE batch_jobs SET status = ?1, finished_at = ?2 WHERE id = ?3"#)
+    .bind(BatchJobStatus::Completed.as_str())
+    .bind(&now)
+    .bind(job_id)
+    .execute(pool)
+    .await?;
+  tracing::info!(job_id = job_id, "[db] finish_batch_job");
+  Ok(())
+}
+
+/// 续跑/重试前原子地把任务置为 running；任务正在执行或已撤销时返回 false
+pub async fn claim_batch_job(pool: &SqlitePool, job_id: i64) -> Result<bool> {
+  let res = sqlx::query(r#"UPDATE batch_jobs SET status = ?1 WHERE id = ?2 AND status NOT IN (?1, ?3)"#)
+    .bind(BatchJobStatus::Running.as_str())
+    .bind(job_id)
+    .bind(BatchJobStatus::Undone.as_str())
+    .execute(pool)
+    .await?;
+  let claimed = res.rows_affected() == 1;
+  tracing::info!(job_id = job_id, claimed = claimed, "[db] claim_batch_job");
+  Ok(claimed)
+}
+
+/// 启动时调用：上次退出时仍在执行的任务标记为 interrupted，返回条数
+pub async fn mark_interrupted_batch_jobs(pool: &SqlitePool) -> Result<u64> {
+  let res = sqlx::query(r#"UPDATE batch_jobs SET status = ?1 WHERE status = ?2"#)
+    .bind(BatchJobStatus::Interrupted.as_str())
+    .bind(BatchJobStatus::Running.as_str())
+    .execute(pool)
+    .await?;
+  Ok(res.rows_affected())
+}
+
+/// 分页列出批量任务（按时间倒序），可按项目/状态过滤，返回 (列表, 总条数)
+pub async fn list_batch_jobs(
+  pool: &SqlitePool,
+  project: Option<String>,
+  status: Option<BatchJobStatus>,
+  page: u32,
+  per_page: u32,
+) -> Result<(Vec<BatchJobSummary>, u64)> {
+  let per_page = per_page.clamp(1, 100);
+  let offset = (page.saturating_sub(1)) * per_page;
+  let status = status.map(|s| s.as_str());
+
+  let total: (i64,) = sqlx::query_as(
+    r#"SELECT COUNT(*) FROM batch_jobs WHERE (?1 IS NULL OR project = ?1) AND (?2 IS NULL OR status = ?2)"#,
+  )
+  .bind(project.as_deref())
+  .bind(status)
+  .fetch_one(pool)
+  .await?;
+
+  let rows = sqlx::query_as::<_, (i64, String, String, String, Option<i64>, Option<i64>, String, Option<String>, i64, i64, i64, i64, i64)>(
+    r#"SELECT j.id, j.kind, j.project, j.status, j.snapshot_id, j.undo_of, j.created_at, j.finished_at,
+         COUNT(i.user_id),
+         COALESCE(SUM(i.status = 'pending'), 0),
+         COALESCE(SUM(i.status = 'applied'), 0),
+         COALESCE(SUM(i.status = 'skipped'), 0),
+         COALESCE(SUM(i.status = 'failed'), 0)
+       FROM batch_jobs j
+       LEFT JOIN batch_job_items i ON i.job_id = j.id
+       WHERE (?1 IS NULL OR j.project = ?1) AND (?2 IS NULL OR j.status = ?2)
+       GROUP BY j.id
+       ORDER BY j.id DESC
+       LIMIT ?3 OFFSET ?4"#,
+  )
+  .bind(project.as_deref())
+  .bind(status)
+  .bind(per_page as i64)
+  .bind(offset as i64)
+  .fetch_all(pool)
+  .await?;
+
+  let items = rows
+    .into_iter()
+    .map(|r| BatchJobSummary {
+      job: batch_job_from_row((r.0, r.1, r.2, r.3, r.4, r.5, r.6, r.7)),
+      total: r.8,
+      pending: r.9,
+      applied: r.10,
+      skipped: r.11,
+      failed: r.12,
+    })
+    .collect();
+
+  Ok((items, total.0 as u64))
+}
+
+pub async fn set_batch_job_status(pool: &SqlitePool, job_id: i64, status: BatchJobStatus) -> Result<()> {
+  sqlx::query(r#"UPDATE batch_jobs SET status = ?1 WHERE id = ?2"#)
+    .bind(status.as_str())
+    .bind(job_id)
+    .execute(pool)
+    .await?;
+  tracing::info!(job_id = job_id, status = status.as_str(), "[db] set_batch_job_status");
+  Ok(())
+}
+
+type ChangeRequestRow = (
+  i64,
+  String,
+  String,
+  String,
+  String,
+  String,
+  String,
+  Option<String>,
+  Option<String>,
+  Option<String>,
+  Option<String>,
+);
+
+fn change_request_from_row(r: ChangeRequestRow) -> Result<ChangeRequest> {
+  Ok(ChangeRequest {
+    id: r.0,
+    uid: r.1,
+    status: ChangeRequestStatus::parse(&r.2),
+    created_at: r.3,
+    created_by: r.4,
+    reasons: serde_json::from_str(&r.5)?,
+    operations: serde_json::from_str(&r.6)?,
+    approved_by: r.7,
+    approved_at: r.8,
+    executed_at: r.9,
+    result: r.10.map(|json| serde_json::from_str(&json)).transpose()?,
+  })
+}
+
+const CHANGE_REQUEST_COLUMNS: &str =
+  "id, uid, status, created_at, created_by, reasons, operations, approved_by, approved_at, executed_at, result";
+
+/// 保存待审批的变更请求（本地发起或导入），uid 已存在时报错
+pub async fn create_change_request(
+  pool: &SqlitePool,
+  uid: &str,
+  created_at: &str,
+  created_by: &str,
+  reasons: &[String],
+  operations: &[ChangeOperation],
+  signature: Option<&str>,
+) -> Result<i64> {
+  let res = sqlx::query(
+    r#"INSERT INTO change_requests (uid, status, created_at, created_by, reasons, operations, signature)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+  )
+  .bind(uid)
+  .bind(ChangeRequestStatus::Pending.as_str())
+  .bind(created_at)
+  .bind(created_by)
+  .bind(serde_json::to_string(reasons)?)
+  .bind(serde_json::to_string(operations)?)
+  .bind(signature)
+  .execute(pool)
+  .await
+  .with_context(|| format!("create change request {}", uid))?;
+  tracing::info!(uid = %uid, created_by = %created_by, "[db] create_change_request");
+  Ok(res.last_insert_rowid())
+}
+
+pub async fn get_change_request(pool: &SqlitePool, id: i64) -> Result<Option<ChangeRequest>> {
+  let row = sqlx::query_as::<_, ChangeRequestRow>(&format!(
+    "SELECT {} FROM change_requests WHERE id = ?1",
+    CHANGE_REQUEST_COLUMNS
+  ))
+  .bind(id)
+  .fetch_optional(pool)
+  .await?;
+  row.map(change_request_from_row).transpose()
+}
+
+/// 按创建时间倒序列出变更请求，status 为空时返回全部
+pub async fn list_change_requests(pool: &SqlitePool, status: Option<ChangeRequestStatus>) -> Result<Vec<ChangeRequest>> {
+  let rows = sqlx::query_as::<_, ChangeRequestRow>(&format!(
+    "SELECT {} FROM change_requests WHERE (?1 IS NULL OR status = ?1) ORDER BY created_at DESC, id DESC",
+    CHANGE_REQUEST_COLUMNS
+  ))
+  .bind(status.map(|s| s.as_str()))
+  .fetch_all(pool)
+  .await?;
+  rows.into_iter().map(change_request_from_row).collect()
+}
+
+/// 审批或驳回待审批的变更请求；状态已变化时返回 false
+pub async fn review_change_request(pool: &SqlitePool, id: i64, status: ChangeRequestStatus, reviewer: &str) -> Result<bool> {
+  let res = sqlx::query(
+    r#"UPDATE change_requests SET status = ?1, approved_by = ?2, approved_at = ?3
+       WHERE id = ?4 AND status = ?5"#,
+  )
+  .bind(status.as_str())
+  .bind(reviewer)
+  .bind(Utc::now().to_rfc3339())
+  .bind(id)
+  .bind(ChangeRequestStatus::Pending.as_str())
+  .execute(pool)
+  .await?;
+  tracing::info!(id = id, status = status.as_str(), reviewer = %reviewer, "[db] review_change_request");
+  Ok(res.rows_affected() > 0)
+}
+
+/// 把已审批的变更请求标记为已执行；不是 approved 状态时返回 false
+pub async fn mark_change_request_executed(pool: &SqlitePool, id: i64) -> Result<bool> {
+  let res = sqlx::query(r#"UPDATE change_requests SET status = ?1, executed_at = ?2 WHERE id = ?3 AND status = ?4"#)
+    .bind(ChangeRequestStatus::Executed.as_str())
+    .bind(Utc::now().to_rfc3339())
+    .bind(id)
+    .bind(ChangeRequestStatus::Approved.as_str())
+    .execute(pool)
+    .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+pub async fn set_change_request_result(pool: &SqlitePool, id: i64, result: &[ProjectBatchResult]) -> Result<()> {
+  sqlx::query(r#"UPDATE change_requests SET result = ?1 WHERE id = ?2"#)
+    .bind(serde_json::to_string(result)?)
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
+
+type PolicyRuleRow = (i64, String, String, Option<i64>, bool, Option<String>, String, String);
+
+fn policy_rule_from_row(r: PolicyRuleRow) -> Option<PolicyRule> {
+  let Some(kind) = PolicyRuleKind::parse(&r.1) else {
+    tracing::warn!(id = r.0, kind = %r.1, "[db] unknown policy rule kind, ignored");
+    return None;
+  };
+  Some(PolicyRule {
+    id: r.0,
+    kind,
+    pattern: r.2,
+    value: r.3,
+    enabled: r.4,
+    description: r.5,
+    created_at: r.6,
+    updated_at: r.7,
+  })
+}
+
+const POLICY_RULE_COLUMNS: &str = "id, kind, pattern, value, enabled, description, created_at, updated_at";
+
+pub async fn list_policy_rules(pool: &SqlitePool) -> Result<Vec<PolicyRule>> {
+  let rows = sqlx::query_as::<_, PolicyRuleRow>(&format!(
+    "SELECT {} FROM policy_rules ORDER BY kind ASC, id ASC",
+    POLICY_RULE_COLUMNS
+  ))
+  .fetch_all(pool)
+  .await?;
+  Ok(rows.into_iter().filter_map(policy_rule_from_row).collect())
+}
+
+pub async fn create_policy_rule(pool: &SqlitePool, input: &PolicyRuleInput) -> Result<PolicyRule> {
+  let now = Utc::now().to_rfc3339();
+  let res = sqlx::query(
+    r#"INSERT INTO policy_rules (kind, pattern, value, enabled, description, created_at, updated_at)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)"#,
+  )
+  .bind(input.kind.as_str())
+  .bind(input.pattern.trim())
+  .bind(input.value)
+  .bind(input.enabled)
+  .bind(&input.description)
+  .bind(&now)
+  .execute(pool)
+  .await?;
+  let id = res.last_insert_rowid();
+  tracing::info!(id = id, kind = input.kind.as_str(), pattern = %input.pattern, "[db] create_policy_rule");
+
+  Ok(PolicyRule {
+    id,
+    kind: input.kind,
+    pattern: input.pattern.trim().to_string(),
+    value: input.value,
+    enabled: input.enabled,
+    description: input.description.clone(),
+    created_at: now.clone(),
+    updated_at: now,
+  })
+}
+
+/// 修改策略规则；规则不存在时返回 false
+pub async fn update_policy_rule(pool: &SqlitePool, id: i64, input: &PolicyRuleInput) -> Result<bool> {
+  let res = sqlx::query(
+    r#"UPDATE policy_rules SET kind = ?1, pattern = ?2, value = ?3, enabled = ?4, description = ?5, updated_at = ?6
+       WHERE id = ?7"#,
+  )
+  .bind(input.kind.as_str())
+  .bind(input.pattern.trim())
+  .bind(input.value)
+  .bind(input.enabled)
+  .bind(&input.description)
+  .bind(Utc::now().to_rfc3339())
+  .bind(id)
+  .execute(pool)
+  .await?;
+  tracing::info!(id = id, kind = input.kind.as_str(), pattern = %input.pattern, "[db] update_policy_rule");
+  Ok(res.rows_affected() > 0)
+}
+
+pub async fn delete_policy_rule(pool: &SqlitePool, id: i64) -> Result<()> {
+  sqlx::query(r#"DELETE FROM policy_rules WHERE id = ?1"#)
+    .bind(id)
+    .execute(pool)
+    .await?;
+  tracing::info!(id = id, "[db] delete_policy_rule");
+  Ok(())
+}
+
+/// 按 user_id 查本地成员的用户名，不在本地的用户不返回
+pub async fn get_local_usernames(pool: &SqlitePool, user_ids: &[u64]) -> Result<HashMap<u64, String>> {
+  let rows = sqlx::query_as::<_, (i64, String)>(
+    r#"SELECT user_id, username FROM local_members WHERE user_id IN (SELECT value FROM json_each(?1))"#,
+  )
+  .bind(serde_json::to_string(user_ids)?)
+  .fetch_all(pool)
+  .await?;
+  Ok(rows.into_iter().map(|(id, username)| (id as u64, username)).collect())
+}
@@ -0,0 +1,125 @@
+use crate::db;
+use crate::models::{ExpiryInput, ExpirySettings};
+use anyhow::{anyhow, bail, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate, TimeDelta};
+use sqlx::SqlitePool;
+
+/// config 表中的 key
+pub const SETTINGS_KEY: &str = "expiry_settings";
+
+/// max_days 的上限（约 100 年）
+pub const MAX_DAYS: i64 = 36500;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+impl TryFrom<String> for ExpiryInput {
+  type Error = String;
+
+  fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+    parse(&s).map_err(|e| e.to_string())
+  }
+}
+
+impl From<ExpiryInput> for String {
+  fn from(input: ExpiryInput) -> Self {
+    match input {
+      ExpiryInput::Date(d) => d.format(DATE_FORMAT).to_string(),
+      ExpiryInput::Days(n) => format!("+{}d", n),
+      ExpiryInput::Weeks(n) => format!("+{}w", n),
+      ExpiryInput::Months(n) => format!("+{}m", n),
+      ExpiryInput::Years(n) => format!("+{}y", n),
+      ExpiryInput::EndOfMonth => "end-of-month".to_string(),
+      ExpiryInput::EndOfQuarter => "end-of-quarter".to_string(),
+      ExpiryInput::EndOfYear => "end-of-year".to_string(),
+    }
+  }
+}
+
+/// 解析过期时间输入（只校验格式，不校验范围）
+pub fn parse(input: &str) -> Result<ExpiryInput> {
+  let s = input.trim().to_ascii_lowercase();
+  let invalid = || {
+    anyhow!(
+      "invalid expiry {:?}: expected YYYY-MM-DD, +30d / +2w / +3m / +1y, end-of-month, end-of-quarter or end-of-year",
+      input
+    )
+  };
+
+  match s.as_str() {
+    "end-of-month" => return Ok(ExpiryInput::EndOfMonth),
+    "end-of-quarter" => return Ok(ExpiryInput::EndOfQuarter),
+    "end-of-year" => return Ok(ExpiryInput::EndOfYear),
+    _ => {}
+  }
+
+  if let Some(relative) = s.strip_prefix('+') {
+    let unit = relative.chars().last().ok_or_else(invalid)?;
+    let n: u32 = relative[..relative.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+    if n == 0 {
+      return Err(invalid());
+    }
+    return match unit {
+      'd' => Ok(ExpiryInput::Days(n)),
+      'w' => Ok(ExpiryInput::Weeks(n)),
+      'm' => Ok(ExpiryInput::Months(n)),
+      'y' => Ok(ExpiryInput::Years(n)),
+      _ => Err(invalid()),
+    };
+  }
+
+  NaiveDate::parse_from_str(&s, DATE_FORMAT)
+    .map(ExpiryInput::Date)
+    .map_err(|_| invalid())
+}
+
+/// 相对 today 解析为具体日期。end-of-* 取明天所在的月 / 季度 / 年的最后一天，保证晚于今天
+pub fn resolve(input: ExpiryInput, today: NaiveDate) -> Result<NaiveDate> {
+  let overflow = || anyhow!("expiry {} is out of range", String::from(input));
+  let tomorrow = today.succ_opt().ok_or_else(overflow)?;
+  match input {
+    ExpiryInput::Date(d) => Ok(d),
+    ExpiryInput::Days(n) => today.checked_add_signed(Duration::days(n as i64)).ok_or_else(overflow),
+    ExpiryInput::Weeks(n) => today.checked_add_signed(Duration::weeks(n as i64)).ok_or_else(overflow),
+    ExpiryInput::Months(n) => today.checked_add_months(Months::new(n)).ok_or_else(overflow),
+    ExpiryInput::Years(n) => n
+      .checked_mul(12)
+      .and_then(|m| today.checked_add_months(Months::new(m)))
+      .ok_or_else(overflow),
+    ExpiryInput::EndOfMonth => end_of_month(tomorrow.year(), tomorrow.month()).ok_or_else(overflow),
+    ExpiryInput::EndOfQuarter => end_of_month(tomorrow.year(), (tomorrow.month() - 1) / 3 * 3 + 3).ok_or_else(overflow),
+    ExpiryInput::EndOfYear => end_of_month(tomorrow.year(), 12).ok_or_else(overflow),
+  }
+}
+
+fn end_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+  NaiveDate::from_ymd_opt(year, month, 1)?
+    .checked_add_months(Months::new(1))?
+    .pred_opt()
+}
+
+/// 过期时间必须晚于今天，且不超过设置的最长期限
+pub fn validate(date: NaiveDate, today: NaiveDate, settings: &ExpirySettings) -> Result<()> {
+  if date <= today {
+    bail!("expiry date {} must be in the future", date.format(DATE_FORMAT));
+  }
+  if let Some(max_days) = settings.max_days {
+    // 旧配置可能超出 MAX_DAYS，超出日期范围时视为不限制
+    let limit = TimeDelta::try_days(max_days).and_then(|d| today.checked_add_signed(d));
+    if limit.is_some_and(|limit| date > limit) {
+      bail!("expiry date {} is more than {} days ahead", date.format(DATE_FORMAT), max_days);
+    }
+  }
+  Ok(())
+}
+
+/// 命令入口使用：按本地日期解析并校验，返回传给 GitLab 的 YYYY-MM-DD
+pub async fn resolve_expiry(pool: &SqlitePool, input: Option<ExpiryInput>) -> Result<Option<String>> {
+  let Some(input) = input else {
+    return Ok(None);
+  };
+  let settings: ExpirySettings = db::get_config_json(pool, SETTINGS_KEY).await?.unwrap_or_default();
+  let today = chrono::Local::now().date_naive();
+  let date = resolve(input, today)?;
+  validate(date, today, &settings)?;
+  Ok(Some(date.format(DATE_FORMAT).to_string()))
+}
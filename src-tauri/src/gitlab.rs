@@ -1,4 +1,8 @@
-use crate::models::{ProjectMember, ProjectSummary};
+use crate::models::{
+    AccessLevel, AccessRequest, CurrentUser, GitLabUser, GroupSummary, MemberRole,
+    MembershipSourceType, ProjectMember, ProjectOrderBy, ProjectSearchOptions, ProjectSummary,
+    SharedGroup, SortDirection, UserMembership,
+};
 use anyhow::{anyhow, Context, Result};
 use reqwest::StatusCode;
 use serde::Deserialize;
@@ -10,6 +14,13 @@ pub struct GitLabConfig {
     pub token: String,
 }
 
+/// 加人 / 移除的结果：Unchanged 表示成员已存在（409）或本就不是成员（404），GitLab 未做修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberOutcome {
+    Changed,
+    Unchanged,
+}
+
 fn normalize_base_url(base_url: &str) -> String {
     base_url.trim_end_matches('/').to_string()
 }
@@ -58,17 +69,54 @@ struct ApiMember {
     access_level: i64,
     created_at: Option<String>,
     expires_at: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    /// 自定义角色（仅支持自定义角色的实例返回）
+    #[serde(default)]
+    member_role: Option<ApiMemberRole>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiMemberRole {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    base_access_level: i64,
+    #[serde(default)]
+    group_id: Option<u64>,
 }
 
-/// 分页搜索项目。返回 (项目列表, 总条数)。总条数来自响应头 X-Total，若缺失则用本页数量估算。
+fn project_member(m: ApiMember) -> ProjectMember {
+    ProjectMember {
+        id: m.id,
+        username: m.username,
+        name: m.name,
+        avatar_url: m.avatar_url,
+        access_level: AccessLevel::from_value_lossy(m.access_level),
+        created_at: m.created_at,
+        expires_at: m.expires_at,
+        state: m.state,
+        member_role_id: m.member_role.as_ref().map(|r| r.id),
+        member_role_name: m.member_role.map(|r| r.name),
+    }
+}
+
+/// 分页搜索项目，options.group 不为空时在该分组内搜索。返回 (项目列表, 总条数)。总条数来自响应头 X-Total，若缺失则用本页数量估算。
 pub async fn search_projects(
     cfg: &GitLabConfig,
     keyword: &str,
+    options: &ProjectSearchOptions,
     page: u32,
     per_page: u32,
 ) -> Result<(Vec<ProjectSummary>, u64)> {
     let keyword = keyword.trim();
-    let url = api_url(&cfg.base_url, "/api/v4/projects");
+    let group = options.group.as_deref().map(str::trim).filter(|g| !g.is_empty());
+    let url = match group {
+        Some(group) => api_url(&cfg.base_url, &format!("/api/v4/groups/{}/projects", encode_project(group))),
+        None => api_url(&cfg.base_url, "/api/v4/projects"),
+    };
     let http = client();
 
     let token_preview = if cfg.token.len() > 8 {
@@ -81,22 +129,47 @@ pub async fn search_projects(
         token = %token_preview,
         url = %url,
         keyword = %keyword,
+        options = ?options,
         page = page,
         per_page = per_page,
-        "[gitlab] GET /api/v4/projects"
+        "[gitlab] GET projects"
     );
 
+    let mut query: Vec<(&str, String)> = vec![
+        ("simple", "true".to_string()),
+        ("per_page", per_page.to_string()),
+        ("page", page.to_string()),
+        ("order_by", options.order_by.unwrap_or(ProjectOrderBy::LastActivityAt).as_str().to_string()),
+        ("sort", options.sort.unwrap_or(SortDirection::Desc).as_str().to_string()),
+    ];
+    if !keyword.is_empty() {
+        query.push(("search", keyword.to_string()));
+    }
+    if group.is_some() {
+        query.push(("include_subgroups", options.include_subgroups.unwrap_or(true).to_string()));
+    } else if options.membership {
+        query.push(("membership", "true".to_string()));
+    }
+    if options.owned {
+        query.push(("owned", "true".to_string()));
+    }
+    if let Some(archived) = options.archived {
+        query.push(("archived", archived.to_string()));
+    }
+    if let Some(visibility) = options.visibility {
+        query.push(("visibility", visibility.as_str().to_string()));
+    }
+    if let Some(level) = options.min_access_level {
+        query.push(("min_access_level", level.value().to_string()));
+    }
+    if let Some(topic) = options.topic.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        query.push(("topic", topic.to_string()));
+    }
+
     let resp = http
         .get(&url)
         .header("PRIVATE-TOKEN", &cfg.token)
-        .query(&[
-            ("search", keyword),
-            ("simple", "true"),
-            ("per_page", per_page.to_string().as_str()),
-            ("page", page.to_string().as_str()),
-            ("order_by", "last_activity_at"),
-            ("sort", "desc"),
-        ])
+        .query(&query)
         .send()
         .await
         .context("GitLab request failed")?;
@@ -120,29 +193,7 @@ pub async fn search_projects(
     let projects: Vec<ApiProject> = serde_json::from_str(&text).context("Parse JSON")?;
     tracing::debug!(count = projects.len(), "[gitlab] parsed projects");
 
-    let items: Vec<ProjectSummary> = projects
-        .into_iter()
-        .map(|p| {
-            let namespace = p
-                .namespace
-                .and_then(|n| n.full_path.or(n.name))
-                .unwrap_or_else(|| {
-                    p.path_with_namespace
-                        .rsplit_once('/')
-                        .map(|x| x.0.to_string())
-                        .unwrap_or_else(|| p.path_with_namespace.clone())
-                });
-
-            ProjectSummary {
-                id: p.id,
-                name: p.name,
-                namespace,
-                path_with_namespace: p.path_with_namespace,
-                description: p.description,
-                last_activity_at: p.last_activity_at,
-            }
-        })
-        .collect();
+    let items: Vec<ProjectSummary> = projects.into_iter().map(project_summary).collect();
 
     // 若接口未返回 X-Total，用「本页满页则可能还有下一页」的启发式
     let total_resolved = if total > 0 {
@@ -156,21 +207,74 @@ pub async fn search_projects(
     Ok((items, total_resolved))
 }
 
+fn project_summary(p: ApiProject) -> ProjectSummary {
+    let namespace = p
+        .namespace
+        .and_then(|n| n.full_path.or(n.name))
+        .unwrap_or_else(|| {
+            p.path_with_namespace
+                .rsplit_once('/')
+                .map(|x| x.0.to_string())
+                .unwrap_or_else(|| p.path_with_namespace.clone())
+        });
+
+    ProjectSummary {
+        id: p.id,
+        name: p.name,
+        namespace,
+        path_with_namespace: p.path_with_namespace,
+        description: p.description,
+        last_activity_at: p.last_activity_at,
+    }
+}
+
+/// 分页列出某个 GitLab 分组（namespace）下的项目，包含子分组。返回 (项目列表, 总条数)
+pub async fn list_group_projects(
+    cfg: &GitLabConfig,
+    group: &str,
+    page: u32,
+    per_page: u32,
+) -> Result<(Vec<ProjectSummary>, u64)> {
+    let options = ProjectSearchOptions {
+        group: Some(group.to_string()),
+        ..Default::default()
+    };
+    search_projects(cfg, "", &options, page, per_page).await
+}
+
 /// 分页获取项目成员。返回 (成员列表, 总条数)。总条数来自响应头 X-Total。
+/// inherited=true 时包含从上级分组继承的成员（/members/all），否则只返回直接成员（/members）
 pub async fn list_project_members(
     cfg: &GitLabConfig,
     project: &str,
+    inherited: bool,
     page: u32,
     per_page: u32,
 ) -> Result<(Vec<ProjectMember>, u64)> {
-    let project = encode_project(project.trim());
+    list_source_members(cfg, MembershipSourceType::Project, project, inherited, page, per_page).await
+}
+
+async fn list_source_members(
+    cfg: &GitLabConfig,
+    source_type: MembershipSourceType,
+    source: &str,
+    inherited: bool,
+    page: u32,
+    per_page: u32,
+) -> Result<(Vec<ProjectMember>, u64)> {
+    let source = encode_project(source.trim());
     let http = client();
     let url = api_url(
         &cfg.base_url,
-        &format!("/api/v4/projects/{}/members/all", project),
+        &format!(
+            "/api/v4/{}/{}/{}",
+            source_segment(source_type),
+            source,
+            if inherited { "members/all" } else { "members" }
+        ),
     );
 
-    tracing::info!(project = %project, page = page, per_page = per_page, "[gitlab] GET project members");
+    tracing::info!(source = %source, source_type = ?source_type, inherited = inherited, page = page, per_page = per_page, "[gitlab] GET members");
 
     let resp = http
         .get(&url)
@@ -200,18 +304,7 @@ pub async fn list_project_members(
     let members: Vec<ApiMember> = resp.json().await.context("Parse JSON")?;
     tracing::debug!(page = page, count = members.len(), total = total, "[gitlab] parsed members");
 
-    let items: Vec<ProjectMember> = members
-        .into_iter()
-        .map(|m| ProjectMember {
-            id: m.id,
-            username: m.username,
-            name: m.name,
-            avatar_url: m.avatar_url,
-            access_level: m.access_level,
-            created_at: m.created_at,
-            expires_at: m.expires_at,
-        })
-        .collect();
+    let items: Vec<ProjectMember> = members.into_iter().map(project_member).collect();
 
     let total_resolved = if total > 0 {
         total
@@ -224,13 +317,62 @@ pub async fn list_project_members(
     Ok((items, total_resolved))
 }
 
+/// 翻页时最多拉取的页数（每页 100）
+const MAX_ALL_PAGES: u32 = 20;
+
+/// 翻页拉取项目的全部成员（inherited=true 时包含继承成员）。
+/// 成员超过 MAX_ALL_PAGES 页时返回错误，不会返回截断的列表
+pub async fn list_all_project_members(
+    cfg: &GitLabConfig,
+    project: &str,
+    inherited: bool,
+) -> Result<Vec<ProjectMember>> {
+    list_all_source_members(cfg, MembershipSourceType::Project, project, inherited).await
+}
+
+/// 翻页拉取 GitLab 分组的全部成员（inherited=true 时包含继承成员）
+pub async fn list_all_group_members(
+    cfg: &GitLabConfig,
+    group: &str,
+    inherited: bool,
+) -> Result<Vec<ProjectMember>> {
+    list_all_source_members(cfg, MembershipSourceType::Group, group, inherited).await
+}
+
+async fn list_all_source_members(
+    cfg: &GitLabConfig,
+    source_type: MembershipSourceType,
+    source: &str,
+    inherited: bool,
+) -> Result<Vec<ProjectMember>> {
+    let mut members = Vec::new();
+    for page in 1..=MAX_ALL_PAGES {
+        let (items, total) = list_source_members(cfg, source_type, source, inherited, page, 100).await?;
+        let done = items.len() < 100 || (page as u64) * 100 >= total;
+        members.extend(items);
+        if done {
+            return Ok(members);
+        }
+    }
+    tracing::warn!(source = %source, limit = MAX_ALL_PAGES * 100, "[gitlab] member list exceeds page limit");
+    Err(anyhow!(
+        "{} has more than {} members, refusing to work on a truncated list",
+        source,
+        MAX_ALL_PAGES * 100
+    ))
+}
+
 pub async fn add_member(
     cfg: &GitLabConfig,
     project: &str,
     user_id: u64,
-    access_level: i64,
+    access_level: AccessLevel,
     expires_at: Option<String>,
-) -> Result<()> {
+    member_role_id: Option<u64>,
+) -> Result<MemberOutcome> {
+    if access_level == AccessLevel::NoAccess {
+        return Err(anyhow!("cannot add a member with access level {}", access_level));
+    }
     let project = encode_project(project.trim());
     let url = api_url(
         &cfg.base_url,
@@ -241,20 +383,24 @@ pub async fn add_member(
     tracing::info!(
       url = %url,
       user_id = user_id,
-      access_level = access_level,
+      access_level = access_level.value(),
       expires_at = ?expires_at,
+      member_role_id = ?member_role_id,
       "[gitlab] POST add member"
     );
 
     let mut params: Vec<(&str, String)> = vec![
         ("user_id", user_id.to_string()),
-        ("access_level", access_level.to_string()),
+        ("access_level", access_level.value().to_string()),
     ];
     if let Some(expires_at) = expires_at {
         if !expires_at.trim().is_empty() {
             params.push(("expires_at", expires_at));
         }
     }
+    if let Some(role_id) = member_role_id {
+        params.push(("member_role_id", role_id.to_string()));
+    }
 
     let resp = http
         .post(&url)
@@ -269,16 +415,16 @@ pub async fn add_member(
 
     if status.is_success() {
         tracing::info!(user_id = user_id, "[gitlab] add_member success");
-        return Ok(());
+        return Ok(MemberOutcome::Changed);
     }
 
-    // GitLab 在成员已存在时返回 409，需视作成功
+    // GitLab 在成员已存在时返回 409，不算失败，但需与新增区分（撤销时不能移除原有成员）
     if status == StatusCode::CONFLICT {
         tracing::info!(
             user_id = user_id,
-            "[gitlab] member already exists, treating as success"
+            "[gitlab] member already exists, unchanged"
         );
-        return Ok(());
+        return Ok(MemberOutcome::Unchanged);
     }
 
     let text = resp.text().await.unwrap_or_default();
@@ -287,7 +433,7 @@ pub async fn add_member(
     Err(anyhow!("GitLab API error {status}: {text}"))
 }
 
-pub async fn remove_member(cfg: &GitLabConfig, project: &str, user_id: u64) -> Result<()> {
+pub async fn remove_member(cfg: &GitLabConfig, project: &str, user_id: u64) -> Result<MemberOutcome> {
     let project = encode_project(project.trim());
     let url = api_url(
         &cfg.base_url,
@@ -313,19 +459,902 @@ pub async fn remove_member(cfg: &GitLabConfig, project: &str, user_id: u64) -> R
 
     if status.is_success() {
         tracing::info!(user_id = user_id, "[gitlab] remove_member success");
-        return Ok(());
+        return Ok(MemberOutcome::Changed);
     }
 
     if status == StatusCode::NOT_FOUND {
-        // Not a member -> treat as success.
+        // Not a member -> nothing to remove.
         tracing::info!(
             user_id = user_id,
-            "[gitlab] user not found, treating as success"
+            "[gitlab] user not found, unchanged"
         );
-        return Ok(());
+        return Ok(MemberOutcome::Unchanged);
     }
 
     let text = resp.text().await.unwrap_or_default();
     tracing::warn!(status = %status, body = %text, "[gitlab] remove_member failed");
     Err(anyhow!("GitLab API error {status}: {text}"))
 }
+
+#[derive(Debug, Deserialize)]
+struct ApiCurrentUser {
+    id: u64,
+    username: String,
+    #[serde(default)]
+    is_admin: bool,
+}
+
+/// 当前 token 对应的用户（GET /user），is_admin 仅管理员 token 为 true
+pub async fn current_user(cfg: &GitLabConfig) -> Result<CurrentUser> {
+    let url = api_url(&cfg.base_url, "/api/v4/user");
+    let http = client();
+
+    tracing::info!(url = %url, "[gitlab] GET current user");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::error!(status = %status, body = %text, "[gitlab] API error");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let u: ApiCurrentUser = resp.json().await.context("Parse JSON")?;
+    Ok(CurrentUser {
+        id: u.id,
+        username: u.username,
+        is_admin: u.is_admin,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiMembership {
+    source_id: u64,
+    source_name: String,
+    source_type: String,
+    access_level: i64,
+}
+
+/// 用户的直接成员关系（GET /users/:id/memberships，需要管理员 token）。返回 (列表, 总条数)
+pub async fn list_user_memberships(
+    cfg: &GitLabConfig,
+    user_id: u64,
+    page: u32,
+    per_page: u32,
+) -> Result<(Vec<UserMembership>, u64)> {
+    let url = api_url(&cfg.base_url, &format!("/api/v4/users/{}/memberships", user_id));
+    let http = client();
+
+    tracing::info!(user_id = user_id, page = page, per_page = per_page, "[gitlab] GET user memberships");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .query(&[
+            ("per_page", per_page.to_string().as_str()),
+            ("page", page.to_string().as_str()),
+        ])
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    let total: u64 = resp
+        .headers()
+        .get("x-total")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::error!(status = %status, body = %text, "[gitlab] API error");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let items: Vec<ApiMembership> = resp.json().await.context("Parse JSON")?;
+    tracing::debug!(user_id = user_id, count = items.len(), total = total, "[gitlab] parsed memberships");
+
+    let items: Vec<UserMembership> = items
+        .into_iter()
+        .map(|m| UserMembership {
+            // GitLab 用 "Namespace" 表示分组
+            source_type: if m.source_type == "Project" {
+                MembershipSourceType::Project
+            } else {
+                MembershipSourceType::Group
+            },
+            source_id: m.source_id,
+            source_name: m.source_name,
+            access_level: AccessLevel::from_value_lossy(m.access_level),
+        })
+        .collect();
+
+    let total_resolved = if total > 0 {
+        total
+    } else if items.len() as u32 >= per_page {
+        ((page - 1) * per_page) as u64 + items.len() as u64 + 1
+    } else {
+        ((page - 1) * per_page) as u64 + items.len() as u64
+    };
+
+    Ok((items, total_resolved))
+}
+
+/// 当前 token 至少具备 Maintainer 权限（可管理成员）的项目。返回 (项目列表, 总条数)
+pub async fn list_manageable_projects(
+    cfg: &GitLabConfig,
+    page: u32,
+    per_page: u32,
+) -> Result<(Vec<ProjectSummary>, u64)> {
+    let url = api_url(&cfg.base_url, "/api/v4/projects");
+    let http = client();
+
+    tracing::info!(page = page, per_page = per_page, "[gitlab] GET manageable projects");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .query(&[
+            ("min_access_level", "40"),
+            ("simple", "true"),
+            ("per_page", per_page.to_string().as_str()),
+            ("page", page.to_string().as_str()),
+        ])
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    let total: u64 = resp
+        .headers()
+        .get("x-total")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::error!(status = %status, body = %text, "[gitlab] API error");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let projects: Vec<ApiProject> = resp.json().await.context("Parse JSON")?;
+    let items: Vec<ProjectSummary> = projects.into_iter().map(project_summary).collect();
+
+    let total_resolved = if total > 0 {
+        total
+    } else if items.len() as u32 >= per_page {
+        ((page - 1) * per_page) as u64 + items.len() as u64 + 1
+    } else {
+        ((page - 1) * per_page) as u64 + items.len() as u64
+    };
+
+    Ok((items, total_resolved))
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiGroup {
+    id: u64,
+    name: String,
+    full_path: String,
+}
+
+/// 当前 token 至少具备 Maintainer 权限的 GitLab 分组。返回 (分组列表, 总条数)
+pub async fn list_manageable_groups(
+    cfg: &GitLabConfig,
+    page: u32,
+    per_page: u32,
+) -> Result<(Vec<GroupSummary>, u64)> {
+    let url = api_url(&cfg.base_url, "/api/v4/groups");
+    let http = client();
+
+    tracing::info!(page = page, per_page = per_page, "[gitlab] GET manageable groups");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .query(&[
+            ("min_access_level", "40"),
+            ("per_page", per_page.to_string().as_str()),
+            ("page", page.to_string().as_str()),
+        ])
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    let total: u64 = resp
+        .headers()
+        .get("x-total")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::error!(status = %status, body = %text, "[gitlab] API error");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let groups: Vec<ApiGroup> = resp.json().await.context("Parse JSON")?;
+    let items: Vec<GroupSummary> = groups
+        .into_iter()
+        .map(|g| GroupSummary {
+            id: g.id,
+            name: g.name,
+            full_path: g.full_path,
+        })
+        .collect();
+
+    let total_resolved = if total > 0 {
+        total
+    } else if items.len() as u32 >= per_page {
+        ((page - 1) * per_page) as u64 + items.len() as u64 + 1
+    } else {
+        ((page - 1) * per_page) as u64 + items.len() as u64
+    };
+
+    Ok((items, total_resolved))
+}
+
+/// 按分组 ID 或 full_path 获取 GitLab 分组。不存在时返回 None
+pub async fn get_group(cfg: &GitLabConfig, group: &str) -> Result<Option<GroupSummary>> {
+    let url = api_url(&cfg.base_url, &format!("/api/v4/groups/{}", encode_project(group.trim())));
+    let http = client();
+
+    tracing::debug!(url = %url, "[gitlab] GET group");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .query(&[("with_projects", "false")])
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::warn!(status = %status, body = %text, "[gitlab] get_group failed");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let g: ApiGroup = resp.json().await.context("Parse JSON")?;
+    Ok(Some(GroupSummary {
+        id: g.id,
+        name: g.name,
+        full_path: g.full_path,
+    }))
+}
+
+fn source_segment(source_type: MembershipSourceType) -> &'static str {
+    match source_type {
+        MembershipSourceType::Project => "projects",
+        MembershipSourceType::Group => "groups",
+    }
+}
+
+/// 查询用户在项目/分组中的直接成员关系，不是直接成员时返回 None
+pub async fn get_direct_member(
+    cfg: &GitLabConfig,
+    source_type: MembershipSourceType,
+    source: &str,
+    user_id: u64,
+) -> Result<Option<ProjectMember>> {
+    let source = encode_project(source.trim());
+    let url = api_url(
+        &cfg.base_url,
+        &format!("/api/v4/{}/{}/members/{}", source_segment(source_type), source, user_id),
+    );
+    let http = client();
+
+    tracing::debug!(url = %url, user_id = user_id, "[gitlab] GET direct member");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::warn!(status = %status, body = %text, "[gitlab] get_direct_member failed");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let m: ApiMember = resp.json().await.context("Parse JSON")?;
+    Ok(Some(project_member(m)))
+}
+
+pub async fn remove_group_member(cfg: &GitLabConfig, group: &str, user_id: u64) -> Result<MemberOutcome> {
+    let group = encode_project(group.trim());
+    let url = api_url(
+        &cfg.base_url,
+        &format!("/api/v4/groups/{}/members/{}", group, user_id),
+    );
+    let http = client();
+
+    tracing::info!(
+      url = %url,
+      user_id = user_id,
+      "[gitlab] DELETE remove group member"
+    );
+
+    let resp = http
+        .delete(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    tracing::info!(status = %status, "[gitlab] remove_group_member response");
+
+    if status.is_success() {
+        return Ok(MemberOutcome::Changed);
+    }
+    if status == StatusCode::NOT_FOUND {
+        // Not a member -> nothing to remove.
+        return Ok(MemberOutcome::Unchanged);
+    }
+
+    let text = resp.text().await.unwrap_or_default();
+    tracing::warn!(status = %status, body = %text, "[gitlab] remove_group_member failed");
+    Err(anyhow!("GitLab API error {status}: {text}"))
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiAccessRequest {
+    id: u64,
+    username: String,
+    name: String,
+    avatar_url: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    requested_at: Option<String>,
+}
+
+/// 列出项目/分组待处理的访问申请（需 Maintainer 以上权限），翻页拉取全部；
+/// 超过 MAX_ALL_PAGES 页时返回错误，不会返回截断的列表
+pub async fn list_access_requests(
+    cfg: &GitLabConfig,
+    source_type: MembershipSourceType,
+    source: &str,
+) -> Result<Vec<AccessRequest>> {
+    let source = source.trim();
+    let url = api_url(
+        &cfg.base_url,
+        &format!("/api/v4/{}/{}/access_requests", source_segment(source_type), encode_project(source)),
+    );
+    let http = client();
+
+    tracing::info!(url = %url, source_type = ?source_type, "[gitlab] GET access requests");
+
+    let mut requests = Vec::new();
+    for page in 1..=MAX_ALL_PAGES {
+        let resp = http
+            .get(&url)
+            .header("PRIVATE-TOKEN", &cfg.token)
+            .query(&[("per_page", "100"), ("page", page.to_string().as_str())])
+            .send()
+            .await
+            .context("GitLab request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            tracing::warn!(status = %status, body = %text, "[gitlab] list_access_requests failed");
+            return Err(anyhow!("GitLab API error {status}: {text}"));
+        }
+
+        let items: Vec<ApiAccessRequest> = resp.json().await.context("Parse JSON")?;
+        let done = items.len() < 100;
+        requests.extend(items.into_iter().map(|r| AccessRequest {
+            id: r.id,
+            username: r.username,
+            name: r.name,
+            avatar_url: r.avatar_url,
+            state: r.state,
+            requested_at: r.requested_at,
+        }));
+        if done {
+            tracing::debug!(count = requests.len(), "[gitlab] parsed access requests");
+            return Ok(requests);
+        }
+    }
+
+    tracing::warn!(source = %source, limit = MAX_ALL_PAGES * 100, "[gitlab] access request list exceeds page limit");
+    Err(anyhow!(
+        "{} has more than {} access requests, refusing to return a truncated list",
+        source,
+        MAX_ALL_PAGES * 100
+    ))
+}
+
+/// 批准访问申请并授予指定权限；申请不存在（已被处理或撤回）时返回错误
+pub async fn approve_access_request(
+    cfg: &GitLabConfig,
+    source_type: MembershipSourceType,
+    source: &str,
+    user_id: u64,
+    access_level: AccessLevel,
+) -> Result<()> {
+    if access_level == AccessLevel::NoAccess {
+        return Err(anyhow!("cannot approve with access level {}", access_level));
+    }
+    let source = encode_project(source.trim());
+    let url = api_url(
+        &cfg.base_url,
+        &format!(
+            "/api/v4/{}/{}/access_requests/{}/approve",
+            source_segment(source_type),
+            source,
+            user_id
+        ),
+    );
+    let http = client();
+
+    tracing::info!(
+      url = %url,
+      user_id = user_id,
+      access_level = access_level.value(),
+      "[gitlab] PUT approve access request"
+    );
+
+    let resp = http
+        .put(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .form(&[("access_level", access_level.value().to_string())])
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    tracing::info!(status = %status, "[gitlab] approve_access_request response");
+
+    if status.is_success() {
+        return Ok(());
+    }
+    if status == StatusCode::NOT_FOUND {
+        return Err(anyhow!("no pending access request for user {}", user_id));
+    }
+
+    let text = resp.text().await.unwrap_or_default();
+    tracing::warn!(status = %status, body = %text, "[gitlab] approve_access_request failed");
+    Err(anyhow!("GitLab API error {status}: {text}"))
+}
+
+/// 拒绝访问申请；申请已不存在时视为未修改
+pub async fn deny_access_request(
+    cfg: &GitLabConfig,
+    source_type: MembershipSourceType,
+    source: &str,
+    user_id: u64,
+) -> Result<MemberOutcome> {
+    let source = encode_project(source.trim());
+    let url = api_url(
+        &cfg.base_url,
+        &format!(
+            "/api/v4/{}/{}/access_requests/{}",
+            source_segment(source_type),
+            source,
+            user_id
+        ),
+    );
+    let http = client();
+
+    tracing::info!(url = %url, user_id = user_id, "[gitlab] DELETE deny access request");
+
+    let resp = http
+        .delete(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    tracing::info!(status = %status, "[gitlab] deny_access_request response");
+
+    if status.is_success() {
+        return Ok(MemberOutcome::Changed);
+    }
+    if status == StatusCode::NOT_FOUND {
+        tracing::info!(user_id = user_id, "[gitlab] access request not found, unchanged");
+        return Ok(MemberOutcome::Unchanged);
+    }
+
+    let text = resp.text().await.unwrap_or_default();
+    tracing::warn!(status = %status, body = %text, "[gitlab] deny_access_request failed");
+    Err(anyhow!("GitLab API error {status}: {text}"))
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiUser {
+    id: u64,
+    username: String,
+    name: String,
+    avatar_url: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    last_activity_on: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    public_email: Option<String>,
+}
+
+/// 获取单个用户（GET /users/:id）。用户不存在时返回 None；
+/// last_activity_on / email 仅管理员 token 可见
+pub async fn get_user(cfg: &GitLabConfig, user_id: u64) -> Result<Option<GitLabUser>> {
+    let url = api_url(&cfg.base_url, &format!("/api/v4/users/{}", user_id));
+    let http = client();
+
+    tracing::debug!(url = %url, "[gitlab] GET user");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::warn!(status = %status, body = %text, "[gitlab] get_user failed");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let u: ApiUser = resp.json().await.context("Parse JSON")?;
+    Ok(Some(GitLabUser {
+        id: u.id,
+        username: u.username,
+        name: u.name,
+        avatar_url: u.avatar_url,
+        state: u.state,
+        last_activity_on: u.last_activity_on,
+        email: u.email.or(u.public_email).filter(|e| !e.is_empty()),
+    }))
+}
+
+/// 按项目 ID 或 path_with_namespace 获取项目。不存在时返回 None
+pub async fn get_project(cfg: &GitLabConfig, project: &str) -> Result<Option<ProjectSummary>> {
+    let url = api_url(&cfg.base_url, &format!("/api/v4/projects/{}", encode_project(project)));
+    let http = client();
+
+    tracing::debug!(url = %url, "[gitlab] GET project");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::warn!(status = %status, body = %text, "[gitlab] get_project failed");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let p: ApiProject = resp.json().await.context("Parse JSON")?;
+    Ok(Some(project_summary(p)))
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiProjectShares {
+    #[serde(default)]
+    shared_with_groups: Vec<ApiSharedGroup>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiSharedGroup {
+    group_id: u64,
+    group_name: String,
+    group_full_path: String,
+    group_access_level: i64,
+    #[serde(default)]
+    expires_at: Option<String>,
+}
+
+/// 项目共享给的 GitLab 分组（GET /projects/:id 的 shared_with_groups）
+pub async fn list_shared_groups(cfg: &GitLabConfig, project: &str) -> Result<Vec<SharedGroup>> {
+    let url = api_url(&cfg.base_url, &format!("/api/v4/projects/{}", encode_project(project.trim())));
+    let http = client();
+
+    tracing::info!(url = %url, "[gitlab] GET project shared groups");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::warn!(status = %status, body = %text, "[gitlab] list_shared_groups failed");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let p: ApiProjectShares = resp.json().await.context("Parse JSON")?;
+    Ok(p
+        .shared_with_groups
+        .into_iter()
+        .map(|g| SharedGroup {
+            group_id: g.group_id,
+            group_name: g.group_name,
+            group_full_path: g.group_full_path,
+            group_access_level: AccessLevel::from_value_lossy(g.group_access_level),
+            expires_at: g.expires_at,
+        })
+        .collect())
+}
+
+/// 把项目共享给 GitLab 分组（POST /projects/:id/share）。已共享时（409）读取现有共享：
+/// 权限与过期时间一致时视为未修改，不一致时返回冲突并给出当前值（GitLab 不支持直接修改共享，需先取消共享）
+pub async fn share_project_with_group(
+    cfg: &GitLabConfig,
+    project: &str,
+    group_id: u64,
+    group_access: AccessLevel,
+    expires_at: Option<String>,
+) -> Result<MemberOutcome> {
+    if group_access == AccessLevel::NoAccess {
+        return Err(anyhow!("cannot share with access level {}", group_access));
+    }
+    let url = api_url(
+        &cfg.base_url,
+        &format!("/api/v4/projects/{}/share", encode_project(project.trim())),
+    );
+    let http = client();
+
+    tracing::info!(
+      url = %url,
+      group_id = group_id,
+      group_access = group_access.value(),
+      expires_at = ?expires_at,
+      "[gitlab] POST share project"
+    );
+
+    let mut params: Vec<(&str, String)> = vec![
+        ("group_id", group_id.to_string()),
+        ("group_access", group_access.value().to_string()),
+    ];
+    let expires_at = expires_at.filter(|e| !e.trim().is_empty());
+    if let Some(expires_at) = &expires_at {
+        params.push(("expires_at", expires_at.clone()));
+    }
+
+    let resp = http
+        .post(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .form(&params)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    tracing::info!(status = %status, "[gitlab] share_project response");
+
+    if status.is_success() {
+        return Ok(MemberOutcome::Changed);
+    }
+    if status == StatusCode::CONFLICT {
+        let existing = list_shared_groups(cfg, project)
+            .await?
+            .into_iter()
+            .find(|g| g.group_id == group_id)
+            .ok_or_else(|| {
+                anyhow!("GitLab reported project already shared with group {}, but the share was not found", group_id)
+            })?;
+        let existing_expiry = existing.expires_at.as_deref().map(str::trim).filter(|e| !e.is_empty());
+        if existing.group_access_level == group_access && existing_expiry == expires_at.as_deref().map(str::trim) {
+            tracing::info!(group_id = group_id, "[gitlab] project already shared with group, unchanged");
+            return Ok(MemberOutcome::Unchanged);
+        }
+        tracing::warn!(
+          group_id = group_id,
+          current_access = existing.group_access_level.value(),
+          current_expires_at = ?existing.expires_at,
+          "[gitlab] project already shared with group with different settings"
+        );
+        return Err(anyhow!(
+            "conflict: project is already shared with group {} as {} (expires {}), unshare it first to change the share",
+            existing.group_full_path,
+            existing.group_access_level,
+            existing_expiry.unwrap_or("never")
+        ));
+    }
+
+    let text = resp.text().await.unwrap_or_default();
+    tracing::warn!(status = %status, body = %text, "[gitlab] share_project failed");
+    Err(anyhow!("GitLab API error {status}: {text}"))
+}
+
+/// 取消项目与 GitLab 分组的共享（DELETE /projects/:id/share/:group_id）；未共享时（404）视为未修改
+pub async fn unshare_project_with_group(cfg: &GitLabConfig, project: &str, group_id: u64) -> Result<MemberOutcome> {
+    let url = api_url(
+        &cfg.base_url,
+        &format!("/api/v4/projects/{}/share/{}", encode_project(project.trim()), group_id),
+    );
+    let http = client();
+
+    tracing::info!(url = %url, group_id = group_id, "[gitlab] DELETE unshare project");
+
+    let resp = http
+        .delete(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    tracing::info!(status = %status, "[gitlab] unshare_project response");
+
+    if status.is_success() {
+        return Ok(MemberOutcome::Changed);
+    }
+    if status == StatusCode::NOT_FOUND {
+        tracing::info!(group_id = group_id, "[gitlab] project not shared with group, unchanged");
+        return Ok(MemberOutcome::Unchanged);
+    }
+
+    let text = resp.text().await.unwrap_or_default();
+    tracing::warn!(status = %status, body = %text, "[gitlab] unshare_project failed");
+    Err(anyhow!("GitLab API error {status}: {text}"))
+}
+
+/// 按用户名精确查找用户（GET /users?username=）。不存在时返回 None
+pub async fn find_user_by_username(cfg: &GitLabConfig, username: &str) -> Result<Option<GitLabUser>> {
+    let url = api_url(&cfg.base_url, "/api/v4/users");
+    let http = client();
+
+    tracing::debug!(username = %username, "[gitlab] GET users by username");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .query(&[("username", username.trim())])
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::warn!(status = %status, body = %text, "[gitlab] find_user_by_username failed");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let users: Vec<ApiUser> = resp.json().await.context("Parse JSON")?;
+    Ok(users.into_iter().next().map(|u| GitLabUser {
+        id: u.id,
+        username: u.username,
+        name: u.name,
+        avatar_url: u.avatar_url,
+        state: u.state,
+        last_activity_on: u.last_activity_on,
+        email: u.email.or(u.public_email).filter(|e| !e.is_empty()),
+    }))
+}
+
+/// 修改项目成员的权限与过期时间（PUT /projects/:id/members/:user_id）
+pub async fn update_member(
+    cfg: &GitLabConfig,
+    project: &str,
+    user_id: u64,
+    access_level: AccessLevel,
+    expires_at: Option<String>,
+    member_role_id: Option<u64>,
+) -> Result<()> {
+    let project = encode_project(project.trim());
+    let url = api_url(
+        &cfg.base_url,
+        &format!("/api/v4/projects/{}/members/{}", project, user_id),
+    );
+    let http = client();
+
+    tracing::info!(
+      url = %url,
+      user_id = user_id,
+      access_level = access_level.value(),
+      expires_at = ?expires_at,
+      member_role_id = ?member_role_id,
+      "[gitlab] PUT update member"
+    );
+
+    let mut params: Vec<(&str, String)> = vec![("access_level", access_level.value().to_string())];
+    // 传空字符串表示清除过期时间
+    params.push(("expires_at", expires_at.unwrap_or_default()));
+    if let Some(role_id) = member_role_id {
+        params.push(("member_role_id", role_id.to_string()));
+    }
+
+    let resp = http
+        .put(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .form(&params)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    tracing::info!(status = %status, "[gitlab] update_member response");
+
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let text = resp.text().await.unwrap_or_default();
+    tracing::warn!(status = %status, body = %text, "[gitlab] update_member failed");
+    Err(anyhow!("GitLab API error {status}: {text}"))
+}
+
+/// 列出自定义成员角色：指定 group 时为该顶级分组的角色（GitLab.com），否则为实例级角色（自建实例，需管理员）。
+/// 实例不支持自定义角色时（404）返回空列表
+pub async fn list_member_roles(cfg: &GitLabConfig, group: Option<&str>) -> Result<Vec<MemberRole>> {
+    let path = match group.map(str::trim).filter(|g| !g.is_empty()) {
+        Some(group) => format!("/api/v4/groups/{}/member_roles", encode_project(group)),
+        None => "/api/v4/member_roles".to_string(),
+    };
+    let url = api_url(&cfg.base_url, &path);
+    let http = client();
+
+    tracing::info!(url = %url, "[gitlab] GET member roles");
+
+    let resp = http
+        .get(&url)
+        .header("PRIVATE-TOKEN", &cfg.token)
+        .send()
+        .await
+        .context("GitLab request failed")?;
+
+    let status = resp.status();
+    if status == StatusCode::NOT_FOUND {
+        tracing::info!("[gitlab] member roles not supported by this instance");
+        return Ok(Vec::new());
+    }
+    if !status.is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        tracing::warn!(status = %status, body = %text, "[gitlab] list_member_roles failed");
+        return Err(anyhow!("GitLab API error {status}: {text}"));
+    }
+
+    let roles: Vec<ApiMemberRole> = resp.json().await.context("Parse JSON")?;
+    Ok(roles
+        .into_iter()
+        .map(|r| MemberRole {
+            id: r.id,
+            name: r.name,
+            description: r.description,
+            base_access_level: AccessLevel::from_value_lossy(r.base_access_level),
+            group_id: r.group_id,
+        })
+        .collect())
+}
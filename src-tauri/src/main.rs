@@ -1,15 +1,36 @@
+mod approval;
+mod backup;
+mod bundle;
 mod db;
+mod expiry;
 mod gitlab;
+mod manifest;
 mod models;
+mod policy;
+mod renewal;
+mod report;
+mod smart_group;
+mod snapshot;
 
 use tauri::Manager;
 
-use crate::gitlab::GitLabConfig;
+use crate::gitlab::{GitLabConfig, MemberOutcome};
 use crate::models::{
-  BatchItemError, BatchResult, LocalGroup, LocalMember, LocalMemberUpsert, ProjectMember,
-  ProjectSummary,
+  AccessLevel, AccessRequest, AccessReviewOptions, AccessReviewReport, ApprovalPolicy, BackupInfo,
+  BatchAddItem, BatchItemError, BatchItemStatus, BatchJobDetail, BatchJobItem, BatchJobKind,
+  BatchJobStatus, BatchJobSummary, BatchResult, BundleFormat, BundleImportResult, ChangeOperation,
+  ChangeRequest, ChangeRequestStatus, CopyMembersOptions, CopyMembersPreview, CopyPlanItem,
+  ExpiryInput, ExpiryScanResult, ExpirySettings, GroupApplyResult, GroupMember, GroupRule,
+  GroupShareItem, HistoryEntry, LocalGroup, LocalMember, LocalMemberQuery, LocalMemberUpsert,
+  LocalTag, ManifestApplyResult, ManifestPlan, ManifestValidation, MemberRole, MemberSource,
+  MembershipComparison, MembershipError, MembershipSourceType, OffboardPreview, OffboardResult,
+  PolicyRule, PolicyRuleInput, ProjectBatchResult, ProjectMember, ProjectSearchOptions,
+  ProjectSnapshot, ProjectSummary, ProjectTargets, RefreshLocalMembersResult, RenewalSettings,
+  SharedGroup, SkippedSource, SnapshotDiff, SnapshotMember, SnapshotMemberSource, TrackedProject,
+  UserMembership,
 };
 use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use tauri::State;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
@@ -77,6 +98,14 @@ fn require_cfg(state: &AppState) -> Result<GitLabConfig, String> {
     .ok_or_else(|| "GitLab config not set. Please go to 设置页保存 Base URL 和 Token".to_string())
 }
 
+/// 按审批策略检查变更；需要审批时创建待审批的变更请求并中止本次操作
+async fn require_approval(pool: &SqlitePool, cfg: &GitLabConfig, operations: Vec<ChangeOperation>) -> Result<(), String> {
+  match approval::gate(pool, cfg, operations).await.map_err(|e| e.to_string())? {
+    Some(id) => Err(format!("approval required: change request #{} created", id)),
+    None => Ok(()),
+  }
+}
+
 #[tauri::command]
 async fn get_gitlab_config(state: State<'_, AppState>) -> Result<Option<(String, String)>, String> {
   let cfg = db::get_gitlab_config(&state.db)
@@ -118,19 +147,56 @@ async fn set_gitlab_config(state: State<'_, AppState>, base_url: String, token:
   Ok(())
 }
 
+/// 在线备份本地数据库到 path；exclude_secrets=true 时备份中不包含 GitLab token
+#[tauri::command]
+async fn backup_database(state: State<'_, AppState>, path: String, exclude_secrets: Option<bool>) -> Result<BackupInfo, String> {
+  let exclude_secrets = exclude_secrets.unwrap_or(false);
+  tracing::info!(path = %path, exclude_secrets = exclude_secrets, "backup_database called");
+
+  let result = backup::backup_to(&state.db, std::path::Path::new(&path), exclude_secrets)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(info) => tracing::info!(schema_version = info.schema_version, size_bytes = info.size_bytes, "backup_database success"),
+    Err(e) => tracing::error!(error = %e, "backup_database failed"),
+  }
+  result
+}
+
+/// 校验备份文件并暂存，重启应用后替换当前数据库（替换前自动备份当前数据库）。
+/// 操作历史在启动应用恢复后写入新数据库
+#[tauri::command]
+async fn restore_database(app: tauri::AppHandle, path: String) -> Result<BackupInfo, String> {
+  tracing::info!(path = %path, "restore_database called");
+
+  let db_path = db::db_path(&app).map_err(|e| e.to_string())?;
+  let result = backup::stage_restore(&db_path, std::path::Path::new(&path))
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(info) => tracing::info!(schema_version = info.schema_version, "restore_database staged, restart required"),
+    Err(e) => tracing::error!(error = %e, "restore_database failed"),
+  }
+  result
+}
+
 #[tauri::command]
 async fn search_projects(
   state: State<'_, AppState>,
   keyword: String,
   page: Option<u32>,
   per_page: Option<u32>,
+  options: Option<ProjectSearchOptions>,
 ) -> Result<(Vec<ProjectSummary>, u64), String> {
   let page = page.unwrap_or(1);
   let per_page = per_page.unwrap_or(20).clamp(1, 100);
-  tracing::info!(keyword = %keyword, page = page, per_page = per_page, "search_projects called");
+  let options = options.unwrap_or_default();
+  tracing::info!(keyword = %keyword, page = page, per_page = per_page, options = ?options, "search_projects called");
   
   let cfg = require_cfg(&state)?;
-  let result = gitlab::search_projects(&cfg, keyword.trim(), page, per_page)
+  let result = gitlab::search_projects(&cfg, keyword.trim(), &options, page, per_page)
     .await
     .map_err(|e| e.to_string());
   
@@ -153,7 +219,7 @@ async fn list_project_members(
   tracing::info!(project = %project, page = page, per_page = per_page, "list_project_members called");
   
   let cfg = require_cfg(&state)?;
-  let result = gitlab::list_project_members(&cfg, project.trim(), page, per_page)
+  let result = gitlab::list_project_members(&cfg, project.trim(), true, page, per_page)
     .await
     .map_err(|e| e.to_string());
   
@@ -164,6 +230,154 @@ async fn list_project_members(
   result
 }
 
+/// 项目共享给的 GitLab 分组（分组成员经由共享获得项目权限，不出现在直接成员中）
+#[tauri::command]
+async fn list_project_shared_groups(state: State<'_, AppState>, project: String) -> Result<Vec<SharedGroup>, String> {
+  tracing::info!(project = %project, "list_project_shared_groups called");
+
+  let cfg = require_cfg(&state)?;
+  let result = gitlab::list_shared_groups(&cfg, &project)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(groups) => tracing::info!(count = groups.len(), "list_project_shared_groups success"),
+    Err(e) => tracing::error!(error = %e, "list_project_shared_groups failed"),
+  }
+  result
+}
+
+/// 把项目共享给 GitLab 分组；按该权限的加人经过审批策略，共享前按策略护栏检查，已共享时不做修改
+#[tauri::command]
+async fn share_project_with_group(
+  state: State<'_, AppState>,
+  project: String,
+  group_id: u64,
+  group_access: AccessLevel,
+  expires_at: Option<ExpiryInput>,
+) -> Result<(), String> {
+  tracing::info!(
+    project = %project,
+    group_id = group_id,
+    group_access = %group_access,
+    expires_at = ?expires_at,
+    "share_project_with_group called"
+  );
+
+  let cfg = require_cfg(&state)?;
+  let expires_at = expiry::resolve_expiry(&state.db, expires_at)
+    .await
+    .map_err(|e| e.to_string())?;
+  require_approval(
+    &state.db,
+    &cfg,
+    vec![ChangeOperation {
+      kind: BatchJobKind::Add,
+      source_type: MembershipSourceType::Project,
+      project: project.clone(),
+      items: Vec::new(),
+      user_ids: Vec::new(),
+      updates: Vec::new(),
+      shares: vec![GroupShareItem {
+        group_id,
+        group_access,
+        expires_at: expires_at.clone(),
+      }],
+      policy: None,
+    }],
+  )
+  .await?;
+  if let Some(reason) = policy::check_share(
+    &state.db,
+    &cfg,
+    BatchJobKind::Add,
+    &project,
+    group_id,
+    Some(group_access),
+    expires_at.as_deref(),
+  )
+  .await
+  {
+    return Err(format!("blocked by policy: {}", reason));
+  }
+
+  let outcome = gitlab::share_project_with_group(&cfg, &project, group_id, group_access, expires_at.clone())
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let detail = serde_json::json!({
+    "groupId": group_id,
+    "groupAccess": group_access,
+    "expiresAt": expires_at,
+    "changed": outcome == MemberOutcome::Changed,
+  });
+  if let Err(e) = db::record_history(&state.db, "share_project_with_group", &format!("project:{}", project), &detail).await {
+    tracing::error!(error = %e, "share_project_with_group record history failed");
+  }
+
+  tracing::info!(group_id = group_id, outcome = ?outcome, "share_project_with_group success");
+  Ok(())
+}
+
+#[tauri::command]
+async fn unshare_project_with_group(state: State<'_, AppState>, project: String, group_id: u64) -> Result<(), String> {
+  tracing::info!(project = %project, group_id = group_id, "unshare_project_with_group called");
+
+  let cfg = require_cfg(&state)?;
+  // 与移除成员一样受审批策略约束（受保护项目、涉及用户数）
+  require_approval(
+    &state.db,
+    &cfg,
+    vec![ChangeOperation {
+      kind: BatchJobKind::Remove,
+      source_type: MembershipSourceType::Project,
+      project: project.clone(),
+      items: Vec::new(),
+      user_ids: Vec::new(),
+      updates: Vec::new(),
+      shares: vec![GroupShareItem {
+        group_id,
+        group_access: AccessLevel::NoAccess,
+        expires_at: None,
+      }],
+      policy: None,
+    }],
+  )
+  .await?;
+  if let Some(reason) = policy::check_share(&state.db, &cfg, BatchJobKind::Remove, &project, group_id, None, None).await {
+    return Err(format!("blocked by policy: {}", reason));
+  }
+
+  let outcome = gitlab::unshare_project_with_group(&cfg, &project, group_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let detail = serde_json::json!({ "groupId": group_id, "changed": outcome == MemberOutcome::Changed });
+  if let Err(e) = db::record_history(&state.db, "unshare_project_with_group", &format!("project:{}", project), &detail).await {
+    tracing::error!(error = %e, "unshare_project_with_group record history failed");
+  }
+
+  tracing::info!(group_id = group_id, outcome = ?outcome, "unshare_project_with_group success");
+  Ok(())
+}
+
+/// 自定义成员角色（GitLab 16.5+ Ultimate）；group 为空时列出实例级角色
+#[tauri::command]
+async fn list_member_roles(state: State<'_, AppState>, group: Option<String>) -> Result<Vec<MemberRole>, String> {
+  tracing::info!(group = ?group, "list_member_roles called");
+
+  let cfg = require_cfg(&state)?;
+  let result = gitlab::list_member_roles(&cfg, group.as_deref())
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(roles) => tracing::info!(count = roles.len(), "list_member_roles success"),
+    Err(e) => tracing::error!(error = %e, "list_member_roles failed"),
+  }
+  result
+}
+
 #[tauri::command]
 async fn upsert_local_members(state: State<'_, AppState>, members: Vec<LocalMemberUpsert>) -> Result<(), String> {
   tracing::info!(count = members.len(), "upsert_local_members called");
@@ -182,17 +396,45 @@ async fn upsert_local_members(state: State<'_, AppState>, members: Vec<LocalMemb
 #[tauri::command]
 async fn list_local_members(
   state: State<'_, AppState>,
-  query: Option<String>,
+  query: Option<LocalMemberQuery>,
   page: Option<u32>,
   per_page: Option<u32>,
 ) -> Result<(Vec<LocalMember>, u64), String> {
+  let query = query.unwrap_or_default();
   let page = page.unwrap_or(1);
   let per_page = per_page.unwrap_or(50).clamp(1, 100);
   tracing::info!(query = ?query, page = page, per_page = per_page, "list_local_members called");
   
-  let result = db::list_local_members(&state.db, query, page, per_page)
+  // 分组筛选按有效成员：包含嵌套分组与动态分组规则命中的成员
+  let cfg = state.gitlab.lock().ok().and_then(|g| g.clone());
+  let result = async {
+    let group_member_ids = match query.group_id {
+      Some(group_id) => Some(
+        smart_group::resolve_group_members(&state.db, cfg.as_ref(), group_id, true)
+          .await?
+          .into_iter()
+          .map(|m| m.member.user_id)
+          .collect::<Vec<_>>(),
+      ),
+      None => None,
+    };
+    let grouped_ids = if query.ungrouped {
+      Some(smart_group::grouped_user_ids(&state.db, cfg.as_ref()).await?)
+    } else {
+      None
+    };
+    db::list_local_members(
+      &state.db,
+      &query,
+      group_member_ids.as_deref(),
+      grouped_ids.as_deref(),
+      page,
+      per_page,
+    )
     .await
-    .map_err(|e| e.to_string());
+  }
+  .await
+  .map_err(|e| e.to_string());
   
   match &result {
     Ok((members, total)) => tracing::info!(count = members.len(), total = total, "list_local_members success"),
@@ -201,6 +443,113 @@ async fn list_local_members(
   result
 }
 
+#[tauri::command]
+async fn set_local_member_notes(state: State<'_, AppState>, user_id: u64, notes: Option<String>) -> Result<(), String> {
+  tracing::info!(user_id = user_id, "set_local_member_notes called");
+  db::set_local_member_notes(&state.db, user_id, notes)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_local_tags(state: State<'_, AppState>) -> Result<Vec<LocalTag>, String> {
+  tracing::info!("list_local_tags called");
+  db::list_local_tags(&state.db)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 给成员打标签，标签不存在时自动创建
+#[tauri::command]
+async fn add_tags_to_members(state: State<'_, AppState>, user_ids: Vec<u64>, tags: Vec<String>) -> Result<(), String> {
+  tracing::info!(user_count = user_ids.len(), tags = ?tags, "add_tags_to_members called");
+
+  let result = db::add_tags_to_members(&state.db, user_ids, tags)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(_) => tracing::info!("add_tags_to_members success"),
+    Err(e) => tracing::error!(error = %e, "add_tags_to_members failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn remove_tags_from_members(state: State<'_, AppState>, user_ids: Vec<u64>, tags: Vec<String>) -> Result<(), String> {
+  tracing::info!(user_count = user_ids.len(), tags = ?tags, "remove_tags_from_members called");
+  db::remove_tags_from_members(&state.db, user_ids, tags)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn delete_local_tag(state: State<'_, AppState>, tag_id: i64) -> Result<(), String> {
+  tracing::info!(tag_id = tag_id, "delete_local_tag called");
+  db::delete_local_tag(&state.db, tag_id)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 列出本地成员出现过的所有项目
+#[tauri::command]
+async fn list_member_sources(state: State<'_, AppState>, user_id: u64) -> Result<Vec<MemberSource>, String> {
+  tracing::info!(user_id = user_id, "list_member_sources called");
+  db::list_member_sources(&state.db, user_id)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 按 /users/:id 刷新本地成员的用户名、昵称、头像与状态；user_ids 为空时刷新全部。
+/// GitLab 返回 404 的用户标记为已不存在
+#[tauri::command]
+async fn refresh_local_members(
+  state: State<'_, AppState>,
+  user_ids: Option<Vec<u64>>,
+) -> Result<RefreshLocalMembersResult, String> {
+  tracing::info!(selected = ?user_ids.as_ref().map(|x| x.len()), "refresh_local_members called");
+
+  let cfg = require_cfg(&state)?;
+  let user_ids = match user_ids {
+    Some(ids) => ids,
+    None => db::list_local_member_ids(&state.db)
+      .await
+      .map_err(|e| e.to_string())?,
+  };
+
+  let mut result = RefreshLocalMembersResult {
+    refreshed: Vec::new(),
+    missing: Vec::new(),
+    failed: Vec::new(),
+  };
+  for uid in user_ids {
+    let synced = match gitlab::get_user(&cfg, uid).await {
+      Ok(Some(user)) => db::sync_local_member(&state.db, &user).await.map(|_| true),
+      Ok(None) => db::mark_local_member_missing(&state.db, uid).await.map(|_| false),
+      Err(e) => Err(e),
+    };
+    match synced {
+      Ok(true) => result.refreshed.push(uid),
+      Ok(false) => result.missing.push(uid),
+      Err(e) => {
+        tracing::warn!(user_id = uid, error = %e, "refresh local member failed");
+        result.failed.push(BatchItemError {
+          user_id: uid,
+          message: e.to_string(),
+        });
+      }
+    }
+  }
+
+  tracing::info!(
+    refreshed_count = result.refreshed.len(),
+    missing_count = result.missing.len(),
+    failed_count = result.failed.len(),
+    "refresh_local_members completed"
+  );
+  Ok(result)
+}
+
 #[tauri::command]
 async fn delete_local_members(state: State<'_, AppState>, user_ids: Vec<u64>) -> Result<(), String> {
   tracing::info!(count = user_ids.len(), "delete_local_members called");
@@ -229,13 +578,71 @@ async fn create_local_group(state: State<'_, AppState>, name: String) -> Result<
   result
 }
 
+/// 创建按规则动态计算成员的分组
+#[tauri::command]
+async fn create_smart_group(state: State<'_, AppState>, name: String, rule: GroupRule) -> Result<LocalGroup, String> {
+  tracing::info!(name = %name, rule = ?rule, "create_smart_group called");
+  smart_group::validate_rule(&rule).map_err(|e| e.to_string())?;
+
+  let result = db::create_smart_group(&state.db, name, rule)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(group) => tracing::info!(group_id = group.id, "create_smart_group success"),
+    Err(e) => tracing::error!(error = %e, "create_smart_group failed"),
+  }
+  result
+}
+
+/// 修改分组规则；rule 为空时转为静态分组（已有的静态成员保留）
+#[tauri::command]
+async fn set_group_rule(state: State<'_, AppState>, group_id: i64, rule: Option<GroupRule>) -> Result<(), String> {
+  tracing::info!(group_id = group_id, rule = ?rule, "set_group_rule called");
+  if let Some(rule) = &rule {
+    smart_group::validate_rule(rule).map_err(|e| e.to_string())?;
+  }
+
+  let result = db::set_group_rule(&state.db, group_id, rule.as_ref())
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(_) => tracing::info!("set_group_rule success"),
+    Err(e) => tracing::error!(error = %e, "set_group_rule failed"),
+  }
+  result
+}
+
+/// 预览规则当前命中的本地成员
+#[tauri::command]
+async fn preview_group_rule(state: State<'_, AppState>, rule: GroupRule) -> Result<Vec<LocalMember>, String> {
+  tracing::info!(rule = ?rule, "preview_group_rule called");
+
+  let cfg = state.gitlab.lock().ok().and_then(|g| g.clone());
+  let result = smart_group::evaluate_rule(&state.db, cfg.as_ref(), &rule)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(members) => tracing::info!(count = members.len(), "preview_group_rule success"),
+    Err(e) => tracing::error!(error = %e, "preview_group_rule failed"),
+  }
+  result
+}
+
 #[tauri::command]
 async fn list_local_groups(state: State<'_, AppState>) -> Result<Vec<LocalGroup>, String> {
   tracing::info!("list_local_groups called");
   
-  let result = db::list_local_groups(&state.db)
-    .await
-    .map_err(|e| e.to_string());
+  let cfg = state.gitlab.lock().ok().and_then(|g| g.clone());
+  let result = match db::list_local_groups(&state.db).await {
+    Ok(mut groups) => {
+      smart_group::fill_members_count(&state.db, cfg.as_ref(), &mut groups).await;
+      Ok(groups)
+    }
+    Err(e) => Err(e.to_string()),
+  };
   
   match &result {
     Ok(groups) => tracing::info!(count = groups.len(), "list_local_groups success"),
@@ -259,10 +666,15 @@ async fn delete_local_group(state: State<'_, AppState>, id: i64) -> Result<(), S
 }
 
 #[tauri::command]
-async fn add_members_to_group(state: State<'_, AppState>, group_id: i64, user_ids: Vec<u64>) -> Result<(), String> {
-  tracing::info!(group_id = group_id, user_count = user_ids.len(), "add_members_to_group called");
+async fn add_members_to_group(
+  state: State<'_, AppState>,
+  group_id: i64,
+  user_ids: Vec<u64>,
+  access_level: Option<AccessLevel>,
+) -> Result<(), String> {
+  tracing::info!(group_id = group_id, user_count = user_ids.len(), access_level = ?access_level, "add_members_to_group called");
   
-  let result = db::add_members_to_group(&state.db, group_id, user_ids)
+  let result = db::add_members_to_group(&state.db, group_id, user_ids, access_level)
     .await
     .map_err(|e| e.to_string());
   
@@ -289,139 +701,2323 @@ async fn remove_members_from_group(state: State<'_, AppState>, group_id: i64, us
 }
 
 #[tauri::command]
-async fn list_group_members(state: State<'_, AppState>, group_id: i64) -> Result<Vec<LocalMember>, String> {
-  tracing::info!(group_id = group_id, "list_group_members called");
-  
-  let result = db::list_group_members(&state.db, group_id)
+async fn set_group_members_access_level(
+  state: State<'_, AppState>,
+  group_id: i64,
+  user_ids: Vec<u64>,
+  access_level: Option<AccessLevel>,
+) -> Result<(), String> {
+  tracing::info!(group_id = group_id, user_count = user_ids.len(), access_level = ?access_level, "set_group_members_access_level called");
+
+  let result = db::set_group_members_access_level(&state.db, group_id, user_ids, access_level)
     .await
     .map_err(|e| e.to_string());
-  
+
   match &result {
-    Ok(members) => tracing::info!(count = members.len(), "list_group_members success"),
-    Err(e) => tracing::error!(error = %e, "list_group_members failed"),
+    Ok(_) => tracing::info!(group_id = group_id, "set_group_members_access_level success"),
+    Err(e) => tracing::error!(error = %e, "set_group_members_access_level failed"),
   }
   result
 }
 
 #[tauri::command]
-async fn batch_add_members_to_project(
-  state: State<'_, AppState>,
-  project: String,
-  user_ids: Vec<u64>,
-  access_level: i64,
-  expires_at: Option<String>,
-) -> Result<BatchResult, String> {
-  tracing::info!(
-    project = %project,
-    user_count = user_ids.len(),
-    access_level = access_level,
-    expires_at = ?expires_at,
-    "batch_add_members_to_project called"
-  );
-  
-  let cfg = require_cfg(&state)?;
+async fn include_groups(state: State<'_, AppState>, group_id: i64, child_ids: Vec<i64>) -> Result<(), String> {
+  tracing::info!(group_id = group_id, child_count = child_ids.len(), "include_groups called");
 
-  let mut ok = Vec::new();
-  let mut failed = Vec::new();
+  let result = db::include_groups(&state.db, group_id, child_ids)
+    .await
+    .map_err(|e| e.to_string());
 
-  for uid in &user_ids {
-    match gitlab::add_member(&cfg, &project, *uid, access_level, expires_at.clone()).await {
-      Ok(_) => {
-        tracing::debug!(user_id = uid, "add member success");
-        ok.push(*uid);
-      }
-      Err(e) => {
-        tracing::warn!(user_id = uid, error = %e, "add member failed");
-        failed.push(BatchItemError {
-          user_id: *uid,
-          message: e.to_string(),
-        });
-      }
-    }
+  match &result {
+    Ok(_) => tracing::info!(group_id = group_id, "include_groups success"),
+    Err(e) => tracing::error!(error = %e, "include_groups failed"),
   }
-
-  tracing::info!(
-    success_count = ok.len(),
-    failed_count = failed.len(),
-    "batch_add_members_to_project completed"
-  );
-  
-  Ok(BatchResult {
-    success_user_ids: ok,
-    failed,
-  })
+  result
 }
 
 #[tauri::command]
-async fn add_member_to_project(
-  state: State<'_, AppState>,
-  project: String,
-  user_id: u64,
-  access_level: i64,
-  expires_at: Option<String>,
-) -> Result<(), String> {
-  tracing::info!(
-    project = %project,
-    user_id = user_id,
-    access_level = access_level,
-    expires_at = ?expires_at,
-    "add_member_to_project called"
-  );
+async fn exclude_groups(state: State<'_, AppState>, group_id: i64, child_ids: Vec<i64>) -> Result<(), String> {
+  tracing::info!(group_id = group_id, child_count = child_ids.len(), "exclude_groups called");
 
-  let cfg = require_cfg(&state)?;
-  gitlab::add_member(&cfg, &project, user_id, access_level, expires_at)
+  let result = db::exclude_groups(&state.db, group_id, child_ids)
     .await
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| e.to_string());
 
-  tracing::info!(user_id = user_id, "add_member_to_project success");
-  Ok(())
+  match &result {
+    Ok(_) => tracing::info!(group_id = group_id, "exclude_groups success"),
+    Err(e) => tracing::error!(error = %e, "exclude_groups failed"),
+  }
+  result
 }
 
 #[tauri::command]
-async fn batch_remove_members_from_project(
-  state: State<'_, AppState>,
-  project: String,
-  user_ids: Vec<u64>,
-) -> Result<BatchResult, String> {
-  tracing::info!(
-    project = %project,
-    user_count = user_ids.len(),
-    "batch_remove_members_from_project called"
-  );
-  
-  let cfg = require_cfg(&state)?;
-
-  let mut ok = Vec::new();
-  let mut failed = Vec::new();
+async fn list_group_includes(state: State<'_, AppState>, group_id: i64) -> Result<Vec<LocalGroup>, String> {
+  tracing::info!(group_id = group_id, "list_group_includes called");
 
-  for uid in &user_ids {
-    match gitlab::remove_member(&cfg, &project, *uid).await {
-      Ok(_) => {
-        tracing::debug!(user_id = uid, "remove member success");
-        ok.push(*uid);
-      }
-      Err(e) => {
-        tracing::warn!(user_id = uid, error = %e, "remove member failed");
-        failed.push(BatchItemError {
-          user_id: *uid,
-          message: e.to_string(),
-        });
-      }
+  let cfg = state.gitlab.lock().ok().and_then(|g| g.clone());
+  let result = match db::list_group_includes(&state.db, group_id).await {
+    Ok(mut groups) => {
+      smart_group::fill_members_count(&state.db, cfg.as_ref(), &mut groups).await;
+      Ok(groups)
     }
-  }
+    Err(e) => Err(e.to_string()),
+  };
+
+  match &result {
+    Ok(groups) => tracing::info!(count = groups.len(), "list_group_includes success"),
+    Err(e) => tracing::error!(error = %e, "list_group_includes failed"),
+  }
+  result
+}
+
+/// effective=true 时返回展开嵌套分组后的有效成员，否则只返回直接成员
+#[tauri::command]
+async fn list_group_members(
+  state: State<'_, AppState>,
+  group_id: i64,
+  effective: Option<bool>,
+) -> Result<Vec<GroupMember>, String> {
+  let effective = effective.unwrap_or(false);
+  tracing::info!(group_id = group_id, effective = effective, "list_group_members called");
+  
+  // 没有 GitLab 配置时仍可查看静态成员；仅 gitlab_group 规则需要配置
+  let cfg = state.gitlab.lock().ok().and_then(|g| g.clone());
+  let result = smart_group::resolve_group_members(&state.db, cfg.as_ref(), group_id, effective)
+    .await
+    .map_err(|e| e.to_string());
+  
+  match &result {
+    Ok(members) => tracing::info!(count = members.len(), "list_group_members success"),
+    Err(e) => tracing::error!(error = %e, "list_group_members failed"),
+  }
+  result
+}
+
+/// 校验团队清单文件（不访问 GitLab）
+#[tauri::command]
+async fn validate_manifest(path: String) -> Result<ManifestValidation, String> {
+  tracing::info!(path = %path, "validate_manifest called");
+
+  let result = manifest::load(std::path::Path::new(&path))
+    .map(|m| manifest::validate(&m))
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(v) => tracing::info!(valid = v.valid, errors = v.errors.len(), "validate_manifest success"),
+    Err(e) => tracing::error!(error = %e, "validate_manifest failed"),
+  }
+  result
+}
+
+/// 对比团队清单与 GitLab 当前的项目直接成员，返回执行计划
+#[tauri::command]
+async fn plan_manifest(state: State<'_, AppState>, path: String) -> Result<ManifestPlan, String> {
+  tracing::info!(path = %path, "plan_manifest called");
+
+  let cfg = require_cfg(&state)?;
+  let result = async {
+    let m = manifest::load(std::path::Path::new(&path))?;
+    manifest::plan(&state.db, &cfg, &m).await
+  }
+  .await
+  .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(plan) => tracing::info!(changes = plan.changes.len(), errors = plan.errors.len(), "plan_manifest success"),
+    Err(e) => tracing::error!(error = %e, "plan_manifest failed"),
+  }
+  result
+}
+
+/// 执行 plan_manifest 预览过的计划，使清单中的项目与清单保持一致；当前状态与预览不一致时拒绝执行
+#[tauri::command]
+async fn apply_manifest(
+  state: State<'_, AppState>,
+  path: String,
+  plan: ManifestPlan,
+) -> Result<ManifestApplyResult, String> {
+  tracing::info!(path = %path, changes = plan.changes.len(), "apply_manifest called");
+
+  let cfg = require_cfg(&state)?;
+  let result = async {
+    let m = manifest::load(std::path::Path::new(&path))?;
+    manifest::apply(&state.db, &cfg, &m, &plan).await
+  }
+  .await
+  .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(r) => {
+      tracing::info!(
+        changes = r.plan.changes.len(),
+        updated = r.updated.len(),
+        failed = r.failed.len(),
+        "apply_manifest success"
+      );
+      let detail = serde_json::json!({ "path": path, "result": r });
+      if let Err(e) = db::record_history(&state.db, "apply_manifest", &format!("manifest:{}", path), &detail).await {
+        tracing::error!(error = %e, "apply_manifest record history failed");
+      }
+    }
+    Err(e) => tracing::error!(error = %e, "apply_manifest failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn get_approval_policy(state: State<'_, AppState>) -> Result<ApprovalPolicy, String> {
+  tracing::info!("get_approval_policy called");
+  db::get_config_json(&state.db, approval::POLICY_KEY)
+    .await
+    .map(|p| p.unwrap_or_default())
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_approval_policy(state: State<'_, AppState>, policy: ApprovalPolicy) -> Result<(), String> {
+  tracing::info!(
+    enabled = policy.enabled,
+    min_access_level = %policy.min_access_level,
+    max_users = policy.max_users,
+    protected_count = policy.protected_projects.len(),
+    "set_approval_policy called"
+  );
+
+  // 策略启用时，修改（包括停用）策略本身也需要另一位操作员审批
+  let current: ApprovalPolicy = db::get_config_json(&state.db, approval::POLICY_KEY)
+    .await
+    .map_err(|e| e.to_string())?
+    .unwrap_or_default();
+  if current.enabled {
+    let cfg = require_cfg(&state)?;
+    require_approval(
+      &state.db,
+      &cfg,
+      vec![ChangeOperation {
+        kind: BatchJobKind::Update,
+        source_type: MembershipSourceType::Project,
+        project: approval::POLICY_KEY.to_string(),
+        items: Vec::new(),
+        user_ids: Vec::new(),
+        updates: Vec::new(),
+        shares: Vec::new(),
+        policy: Some(policy.clone()),
+      }],
+    )
+    .await?;
+  }
+
+  let result = db::set_config_json(&state.db, approval::POLICY_KEY, &policy)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(_) => {
+      tracing::info!("set_approval_policy success");
+      let detail = serde_json::json!({ "policy": policy });
+      if let Err(e) = db::record_history(&state.db, "set_approval_policy", "approval_policy", &detail).await {
+        tracing::error!(error = %e, "set_approval_policy record history failed");
+      }
+    }
+    Err(e) => tracing::error!(error = %e, "set_approval_policy failed"),
+  }
+  result
+}
+
+/// 设置团队共享的签名密钥，用于导出 / 导入变更请求文件
+#[tauri::command]
+async fn set_approval_secret(state: State<'_, AppState>, secret: String) -> Result<(), String> {
+  tracing::info!("set_approval_secret called");
+  if secret.trim().is_empty() {
+    return Err("approval secret must not be empty".to_string());
+  }
+  db::set_config_json(&state.db, approval::SECRET_KEY, &secret)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+async fn require_approval_secret(pool: &SqlitePool) -> Result<String, String> {
+  db::get_config_json::<String>(pool, approval::SECRET_KEY)
+    .await
+    .map_err(|e| e.to_string())?
+    .filter(|s| !s.is_empty())
+    .ok_or_else(|| "approval secret not set".to_string())
+}
+
+#[tauri::command]
+async fn list_change_requests(
+  state: State<'_, AppState>,
+  status: Option<ChangeRequestStatus>,
+) -> Result<Vec<ChangeRequest>, String> {
+  tracing::info!(status = ?status, "list_change_requests called");
+  db::list_change_requests(&state.db, status)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_change_request(state: State<'_, AppState>, id: i64) -> Result<Option<ChangeRequest>, String> {
+  tracing::info!(id = id, "get_change_request called");
+  db::get_change_request(&state.db, id)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 导出为签名的 JSON 文件内容，交给另一位操作者导入审批
+#[tauri::command]
+async fn export_change_request(state: State<'_, AppState>, id: i64) -> Result<String, String> {
+  tracing::info!(id = id, "export_change_request called");
+
+  let secret = require_approval_secret(&state.db).await?;
+  let result = async {
+    let request = db::get_change_request(&state.db, id)
+      .await?
+      .ok_or_else(|| anyhow::anyhow!("change request {} not found", id))?;
+    approval::export(&request, &secret)
+  }
+  .await
+  .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(content) => tracing::info!(id = id, bytes = content.len(), "export_change_request success"),
+    Err(e) => tracing::error!(error = %e, "export_change_request failed"),
+  }
+  result
+}
+
+/// 导入签名的变更请求文件（签名不符时拒绝），保存为待审批
+#[tauri::command]
+async fn import_change_request(state: State<'_, AppState>, content: String) -> Result<ChangeRequest, String> {
+  tracing::info!(bytes = content.len(), "import_change_request called");
+
+  let secret = require_approval_secret(&state.db).await?;
+  let result = async {
+    let id = approval::import(&state.db, &content, &secret).await?;
+    db::get_change_request(&state.db, id)
+      .await?
+      .ok_or_else(|| anyhow::anyhow!("change request {} not found", id))
+  }
+  .await
+  .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(r) => tracing::info!(id = r.id, uid = %r.uid, "import_change_request success"),
+    Err(e) => tracing::error!(error = %e, "import_change_request failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn approve_change_request(state: State<'_, AppState>, id: i64) -> Result<ChangeRequest, String> {
+  tracing::info!(id = id, "approve_change_request called");
+  review_change_request(&state, id, true).await
+}
+
+#[tauri::command]
+async fn reject_change_request(state: State<'_, AppState>, id: i64) -> Result<ChangeRequest, String> {
+  tracing::info!(id = id, "reject_change_request called");
+  review_change_request(&state, id, false).await
+}
+
+async fn review_change_request(state: &AppState, id: i64, approve: bool) -> Result<ChangeRequest, String> {
+  let action = if approve { "approve_change_request" } else { "reject_change_request" };
+  let cfg = require_cfg(state)?;
+  let result = approval::review(&state.db, &cfg, id, approve)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(r) => {
+      tracing::info!(id = id, reviewer = ?r.approved_by, "{} success", action);
+      let detail = serde_json::json!({ "uid": r.uid, "reviewer": r.approved_by });
+      if let Err(e) = db::record_history(&state.db, action, &format!("change_request:{}", id), &detail).await {
+        tracing::error!(error = %e, "{} record history failed", action);
+      }
+    }
+    Err(e) => tracing::error!(error = %e, "{} failed", action),
+  }
+  result
+}
+
+/// 执行已审批的变更请求
+#[tauri::command]
+async fn execute_change_request(state: State<'_, AppState>, id: i64) -> Result<ChangeRequest, String> {
+  tracing::info!(id = id, "execute_change_request called");
+
+  let cfg = require_cfg(&state)?;
+  let result = approval::execute(&state.db, &cfg, id)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(r) => {
+      tracing::info!(id = id, operations = r.operations.len(), "execute_change_request success");
+      let detail = serde_json::json!({ "uid": r.uid, "result": r.result });
+      if let Err(e) = db::record_history(&state.db, "execute_change_request", &format!("change_request:{}", id), &detail).await {
+        tracing::error!(error = %e, "execute_change_request record history failed");
+      }
+    }
+    Err(e) => tracing::error!(error = %e, "execute_change_request failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn list_policy_rules(state: State<'_, AppState>) -> Result<Vec<PolicyRule>, String> {
+  tracing::info!("list_policy_rules called");
+  db::list_policy_rules(&state.db)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn create_policy_rule(state: State<'_, AppState>, rule: PolicyRuleInput) -> Result<PolicyRule, String> {
+  tracing::info!(kind = rule.kind.as_str(), pattern = %rule.pattern, value = ?rule.value, "create_policy_rule called");
+  policy::validate(&rule).map_err(|e| e.to_string())?;
+
+  let result = db::create_policy_rule(&state.db, &rule)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(r) => {
+      tracing::info!(id = r.id, "create_policy_rule success");
+      let detail = serde_json::json!({ "rule": r });
+      if let Err(e) = db::record_history(&state.db, "create_policy_rule", &format!("policy_rule:{}", r.id), &detail).await {
+        tracing::error!(error = %e, "create_policy_rule record history failed");
+      }
+    }
+    Err(e) => tracing::error!(error = %e, "create_policy_rule failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn update_policy_rule(state: State<'_, AppState>, id: i64, rule: PolicyRuleInput) -> Result<(), String> {
+  tracing::info!(id = id, kind = rule.kind.as_str(), pattern = %rule.pattern, value = ?rule.value, enabled = rule.enabled, "update_policy_rule called");
+  policy::validate(&rule).map_err(|e| e.to_string())?;
+
+  let result = match db::update_policy_rule(&state.db, id, &rule).await {
+    Ok(true) => Ok(()),
+    Ok(false) => Err(format!("policy rule {} not found", id)),
+    Err(e) => Err(e.to_string()),
+  };
+
+  match &result {
+    Ok(_) => {
+      tracing::info!(id = id, "update_policy_rule success");
+      let detail = serde_json::json!({ "rule": rule });
+      if let Err(e) = db::record_history(&state.db, "update_policy_rule", &format!("policy_rule:{}", id), &detail).await {
+        tracing::error!(error = %e, "update_policy_rule record history failed");
+      }
+    }
+    Err(e) => tracing::error!(error = %e, "update_policy_rule failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn delete_policy_rule(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+  tracing::info!(id = id, "delete_policy_rule called");
+
+  let result = db::delete_policy_rule(&state.db, id)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(_) => {
+      tracing::info!(id = id, "delete_policy_rule success");
+      if let Err(e) = db::record_history(&state.db, "delete_policy_rule", &format!("policy_rule:{}", id), &serde_json::json!({})).await {
+        tracing::error!(error = %e, "delete_policy_rule record history failed");
+      }
+    }
+    Err(e) => tracing::error!(error = %e, "delete_policy_rule failed"),
+  }
+  result
+}
+
+/// 导出分组（含嵌套子分组）为共享包，format 默认 JSON
+#[tauri::command]
+async fn export_group_bundle(
+  state: State<'_, AppState>,
+  group_ids: Vec<i64>,
+  format: Option<BundleFormat>,
+) -> Result<String, String> {
+  let format = format.unwrap_or_default();
+  tracing::info!(group_ids = ?group_ids, format = ?format, "export_group_bundle called");
+
+  let instance_url = state.gitlab.lock().ok().and_then(|g| g.as_ref().map(|c| c.base_url.clone()));
+  let result = async {
+    let bundle = bundle::export(&state.db, instance_url.as_deref(), &group_ids).await?;
+    bundle::render(&bundle, format)
+  }
+  .await
+  .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(content) => tracing::info!(bytes = content.len(), "export_group_bundle success"),
+    Err(e) => tracing::error!(error = %e, "export_group_bundle failed"),
+  }
+  result
+}
+
+/// 导入共享包（JSON 或 YAML）：按用户名在当前实例解析成员后合并到本地分组，返回冲突报告
+#[tauri::command]
+async fn import_group_bundle(state: State<'_, AppState>, content: String) -> Result<BundleImportResult, String> {
+  tracing::info!(bytes = content.len(), "import_group_bundle called");
+
+  let cfg = require_cfg(&state)?;
+  let result = async {
+    let bundle = bundle::parse(&content)?;
+    let (users, conflicts) = bundle::resolve_users(&cfg, &bundle).await?;
+    let mut result = bundle::merge(&state.db, &bundle, &users).await?;
+    result.conflicts.splice(0..0, conflicts);
+    anyhow::Ok(result)
+  }
+  .await
+  .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(r) => {
+      tracing::info!(
+        created = r.groups_created.len(),
+        merged = r.groups_merged.len(),
+        members_added = r.members_added,
+        conflicts = r.conflicts.len(),
+        "import_group_bundle success"
+      );
+      let detail = serde_json::to_value(r).unwrap_or_default();
+      if let Err(e) = db::record_history(&state.db, "import_group_bundle", "local_groups", &detail).await {
+        tracing::error!(error = %e, "import_group_bundle record history failed");
+      }
+    }
+    Err(e) => tracing::error!(error = %e, "import_group_bundle failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn batch_add_members_to_project(
+  state: State<'_, AppState>,
+  project: String,
+  user_ids: Vec<u64>,
+  access_level: AccessLevel,
+  expires_at: Option<ExpiryInput>,
+  member_role_id: Option<u64>,
+  tags: Option<Vec<String>>,
+) -> Result<BatchResult, String> {
+  tracing::info!(
+    project = %project,
+    user_count = user_ids.len(),
+    tags = ?tags,
+    access_level = %access_level,
+    expires_at = ?expires_at,
+    member_role_id = ?member_role_id,
+    "batch_add_members_to_project called"
+  );
+  
+  let cfg = require_cfg(&state)?;
+  let expires_at = expiry::resolve_expiry(&state.db, expires_at)
+    .await
+    .map_err(|e| e.to_string())?;
+  let user_ids = resolve_user_selector(&state.db, user_ids, tags).await?;
+
+  let items: Vec<BatchAddItem> = user_ids
+    .iter()
+    .map(|uid| BatchAddItem {
+      user_id: *uid,
+      access_level,
+      expires_at: expires_at.clone(),
+      member_role_id,
+    })
+    .collect();
+  require_approval(
+    &state.db,
+    &cfg,
+    vec![ChangeOperation {
+      kind: BatchJobKind::Add,
+      source_type: MembershipSourceType::Project,
+      project: project.clone(),
+      items: items.clone(),
+      user_ids: Vec::new(),
+      updates: Vec::new(),
+      shares: Vec::new(),
+      policy: None,
+    }],
+  )
+  .await?;
+  let snapshot_id = take_snapshot(&state.db, &cfg, &project, "batch_add_members_to_project").await?;
+  let result = run_batch_add(&state.db, &cfg, &project, snapshot_id, None, &items).await;
+
+  tracing::info!(
+    success_count = result.success_user_ids.len(),
+    skipped_count = result.skipped_user_ids.len(),
+    failed_count = result.failed.len(),
+    "batch_add_members_to_project completed"
+  );
+  
+  Ok(result)
+}
+
+#[tauri::command]
+async fn add_member_to_project(
+  state: State<'_, AppState>,
+  project: String,
+  user_id: u64,
+  access_level: AccessLevel,
+  expires_at: Option<ExpiryInput>,
+  member_role_id: Option<u64>,
+) -> Result<(), String> {
+  tracing::info!(
+    project = %project,
+    user_id = user_id,
+    access_level = %access_level,
+    expires_at = ?expires_at,
+    member_role_id = ?member_role_id,
+    "add_member_to_project called"
+  );
+
+  let cfg = require_cfg(&state)?;
+  let expires_at = expiry::resolve_expiry(&state.db, expires_at)
+    .await
+    .map_err(|e| e.to_string())?;
+  require_approval(
+    &state.db,
+    &cfg,
+    vec![ChangeOperation {
+      kind: BatchJobKind::Add,
+      source_type: MembershipSourceType::Project,
+      project: project.clone(),
+      items: vec![BatchAddItem {
+        user_id,
+        access_level,
+        expires_at: expires_at.clone(),
+        member_role_id,
+      }],
+      user_ids: Vec::new(),
+      updates: Vec::new(),
+      shares: Vec::new(),
+      policy: None,
+    }],
+  )
+  .await?;
+  let item = BatchJobItem {
+    user_id,
+    access_level: Some(access_level),
+    expires_at: expires_at.clone(),
+    prior_access_level: None,
+    prior_expires_at: None,
+    member_role_id,
+    prior_member_role_id: None,
+    status: BatchItemStatus::Pending,
+    message: None,
+  };
+  if let Some(reason) = policy::check_items(&state.db, &cfg, BatchJobKind::Add, &project, &[item]).await.remove(&user_id) {
+    return Err(format!("blocked by policy: {}", reason));
+  }
+  gitlab::add_member(&cfg, &project, user_id, access_level, expires_at, member_role_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  tracing::info!(user_id = user_id, "add_member_to_project success");
+  Ok(())
+}
+
+#[tauri::command]
+async fn batch_remove_members_from_project(
+  state: State<'_, AppState>,
+  project: String,
+  user_ids: Vec<u64>,
+  tags: Option<Vec<String>>,
+) -> Result<BatchResult, String> {
+  tracing::info!(
+    project = %project,
+    user_count = user_ids.len(),
+    tags = ?tags,
+    "batch_remove_members_from_project called"
+  );
+  
+  let cfg = require_cfg(&state)?;
+  let user_ids = resolve_user_selector(&state.db, user_ids, tags).await?;
+  require_approval(
+    &state.db,
+    &cfg,
+    vec![ChangeOperation {
+      kind: BatchJobKind::Remove,
+      source_type: MembershipSourceType::Project,
+      project: project.clone(),
+      items: Vec::new(),
+      user_ids: user_ids.clone(),
+      updates: Vec::new(),
+      shares: Vec::new(),
+      policy: None,
+    }],
+  )
+  .await?;
+
+  let snapshot_id = take_snapshot(&state.db, &cfg, &project, "batch_remove_members_from_project").await?;
+  let result = run_batch_remove(&state.db, &cfg, &project, snapshot_id, None, &user_ids).await;
+
+  tracing::info!(
+    success_count = result.success_user_ids.len(),
+    skipped_count = result.skipped_user_ids.len(),
+    failed_count = result.failed.len(),
+    "batch_remove_members_from_project completed"
+  );
+  
+  Ok(result)
+}
+
+/// 合并显式选择的用户与按标签选中的本地成员（带有任一标签），去重并保持顺序
+async fn resolve_user_selector(pool: &SqlitePool, mut user_ids: Vec<u64>, tags: Option<Vec<String>>) -> Result<Vec<u64>, String> {
+  if let Some(tags) = tags.filter(|t| !t.is_empty()) {
+    let tagged = db::list_member_ids_by_tags(pool, &tags)
+      .await
+      .map_err(|e| e.to_string())?;
+    user_ids.extend(tagged);
+  }
+  let mut seen = HashSet::new();
+  user_ids.retain(|uid| seen.insert(*uid));
+  Ok(user_ids)
+}
+
+/// 批量修改前为项目生成成员快照；快照失败时不执行修改
+async fn take_snapshot(pool: &SqlitePool, cfg: &GitLabConfig, project: &str, reason: &str) -> Result<i64, String> {
+  snapshot::capture(pool, cfg, project, reason).await.map_err(|e| {
+    tracing::error!(project = %project, reason = %reason, error = %e, "take snapshot failed");
+    format!("snapshot before {} failed: {}", reason, e)
+  })
+}
+
+/// 批量加人：先把每个用户写入批量任务（pending），再逐个执行。
+/// 409（已是成员）记为跳过，撤销时不会移除这些用户
+async fn run_batch_add(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  project: &str,
+  snapshot_id: i64,
+  undo_of: Option<i64>,
+  items: &[BatchAddItem],
+) -> BatchResult {
+  run_new_batch_job(pool, cfg, BatchJobKind::Add, project, snapshot_id, undo_of, add_job_items(items)).await
+}
+
+/// 批准项目的访问申请：记为批准任务，续跑 / 重试时重新批准（不会改为直接加人），撤销时移除批准加入的用户
+async fn run_batch_approve(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  project: &str,
+  snapshot_id: i64,
+  items: &[BatchAddItem],
+) -> BatchResult {
+  run_new_batch_job(pool, cfg, BatchJobKind::Approve, project, snapshot_id, None, add_job_items(items)).await
+}
+
+fn add_job_items(items: &[BatchAddItem]) -> Vec<BatchJobItem> {
+  items
+    .iter()
+    .map(|item| BatchJobItem {
+      user_id: item.user_id,
+      access_level: Some(item.access_level),
+      expires_at: item.expires_at.clone(),
+      prior_access_level: None,
+      prior_expires_at: None,
+      member_role_id: item.member_role_id,
+      prior_member_role_id: None,
+      status: BatchItemStatus::Pending,
+      message: None,
+    })
+    .collect()
+}
+
+/// 批量修改已有成员的权限与过期时间，按加人的规则做策略检查。
+/// 修改前的权限与过期时间取自执行前的快照，撤销时按此改回
+async fn run_batch_update(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  project: &str,
+  snapshot_id: i64,
+  undo_of: Option<i64>,
+  items: &[BatchAddItem],
+) -> BatchResult {
+  let prior = snapshot_members(pool, snapshot_id).await;
+  let job_items: Vec<BatchJobItem> = add_job_items(items)
+    .into_iter()
+    .map(|item| {
+      let before = prior.get(&item.user_id);
+      BatchJobItem {
+        prior_access_level: before.map(|m| m.access_level),
+        prior_expires_at: before.and_then(|m| m.expires_at.clone()),
+        prior_member_role_id: before.and_then(|m| m.member_role_id),
+        ..item
+      }
+    })
+    .collect();
+  run_new_batch_job(pool, cfg, BatchJobKind::Update, project, snapshot_id, undo_of, job_items).await
+}
+
+/// 快照中的成员（按 user_id）；读取失败时为空，撤销时这些用户记为无法恢复
+async fn snapshot_members(pool: &SqlitePool, snapshot_id: i64) -> HashMap<u64, SnapshotMember> {
+  match db::get_snapshot(pool, snapshot_id).await {
+    Ok(Some((_, members))) => members.into_iter().map(|m| (m.user_id, m)).collect(),
+    Ok(None) => HashMap::new(),
+    Err(e) => {
+      tracing::error!(snapshot_id = snapshot_id, error = %e, "load snapshot for batch job failed");
+      HashMap::new()
+    }
+  }
+}
+
+/// 把批量任务条目转成审批用的操作（续跑 / 重试前检查）
+fn job_operation(kind: BatchJobKind, project: &str, items: &[BatchJobItem]) -> ChangeOperation {
+  let grants = || -> Vec<BatchAddItem> {
+    items
+      .iter()
+      .filter_map(|i| {
+        i.access_level.map(|level| BatchAddItem {
+          user_id: i.user_id,
+          access_level: level,
+          expires_at: i.expires_at.clone(),
+          member_role_id: i.member_role_id,
+        })
+      })
+      .collect()
+  };
+  // 修改任务按加人计算，记在 updates 中
+  let (kind, items, user_ids, updates) = match kind {
+    BatchJobKind::Add => (BatchJobKind::Add, grants(), Vec::new(), Vec::new()),
+    BatchJobKind::Remove => (BatchJobKind::Remove, Vec::new(), items.iter().map(|i| i.user_id).collect(), Vec::new()),
+    BatchJobKind::Update => (BatchJobKind::Add, Vec::new(), Vec::new(), grants()),
+    BatchJobKind::Approve => (BatchJobKind::Approve, grants(), Vec::new(), Vec::new()),
+  };
+  ChangeOperation {
+    kind,
+    source_type: MembershipSourceType::Project,
+    project: project.to_string(),
+    items,
+    user_ids,
+    updates,
+    shares: Vec::new(),
+    policy: None,
+  }
+}
+
+/// 批量移除：移除前的权限与过期时间取自执行前的快照，撤销时按此重新加入
+async fn run_batch_remove(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  project: &str,
+  snapshot_id: i64,
+  undo_of: Option<i64>,
+  user_ids: &[u64],
+) -> BatchResult {
+  let prior = snapshot_members(pool, snapshot_id).await;
+
+  let job_items: Vec<BatchJobItem> = user_ids
+    .iter()
+    .map(|uid| {
+      let before = prior.get(uid);
+      BatchJobItem {
+        user_id: *uid,
+        access_level: None,
+        expires_at: None,
+        prior_access_level: before.map(|m| m.access_level),
+        prior_expires_at: before.and_then(|m| m.expires_at.clone()),
+        member_role_id: None,
+        prior_member_role_id: before.and_then(|m| m.member_role_id),
+        status: BatchItemStatus::Pending,
+        message: None,
+      }
+    })
+    .collect();
+  run_new_batch_job(pool, cfg, BatchJobKind::Remove, project, snapshot_id, undo_of, job_items).await
+}
+
+/// 保存批量任务后执行；任务保存失败时仍继续执行（只是无法续跑/撤销）
+async fn run_new_batch_job(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  kind: BatchJobKind,
+  project: &str,
+  snapshot_id: i64,
+  undo_of: Option<i64>,
+  items: Vec<BatchJobItem>,
+) -> BatchResult {
+  let job_id = match db::create_batch_job(pool, kind, project, Some(snapshot_id), undo_of, &items).await {
+    Ok(id) => Some(id),
+    Err(e) => {
+      tracing::error!(project = %project, error = %e, "create batch job failed");
+      None
+    }
+  };
+
+  let mut result = execute_batch_items(pool, cfg, kind, project, job_id, items).await;
+  result.snapshot_id = Some(snapshot_id);
+  result
+}
+
+/// 逐个执行批量任务条目，失败不中断；每个条目执行后立即写回状态，便于中断后续跑。
+/// 执行前按策略规则检查，被拦截的条目记为失败（续跑 / 重试时会重新检查）
+async fn execute_batch_items(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  kind: BatchJobKind,
+  project: &str,
+  job_id: Option<i64>,
+  items: Vec<BatchJobItem>,
+) -> BatchResult {
+  let mut ok = Vec::new();
+  let mut skipped = Vec::new();
+  let mut failed = Vec::new();
+
+  // 修改权限 / 过期时间按加人的规则检查
+  let policy_kind = match kind {
+    BatchJobKind::Remove => BatchJobKind::Remove,
+    BatchJobKind::Add | BatchJobKind::Update | BatchJobKind::Approve => BatchJobKind::Add,
+  };
+  let blocked = policy::check_items(pool, cfg, policy_kind, project, &items).await;
+
+  for item in items {
+    let outcome = match kind {
+      _ if blocked.contains_key(&item.user_id) => Err(anyhow::anyhow!("blocked by policy: {}", blocked[&item.user_id])),
+      BatchJobKind::Add => match item.access_level {
+        Some(level) => gitlab::add_member(cfg, project, item.user_id, level, item.expires_at.clone(), item.member_role_id).await,
+        None => Err(anyhow::anyhow!("no access level specified for this member")),
+      },
+      BatchJobKind::Remove => gitlab::remove_member(cfg, project, item.user_id).await,
+      BatchJobKind::Update => match item.access_level {
+        Some(level) => {
+          gitlab::update_member(cfg, project, item.user_id, level, item.expires_at.clone(), item.member_role_id)
+            .await
+            .map(|()| MemberOutcome::Changed)
+        }
+        None => Err(anyhow::anyhow!("no access level specified for this member")),
+      },
+      BatchJobKind::Approve => match item.access_level {
+        Some(level) => {
+          gitlab::approve_access_request(cfg, MembershipSourceType::Project, project, item.user_id, level)
+            .await
+            .map(|()| MemberOutcome::Changed)
+        }
+        None => Err(anyhow::anyhow!("no access level specified for this member")),
+      },
+    };
+
+    let (status, message) = match outcome {
+      Ok(MemberOutcome::Changed) => {
+        tracing::debug!(user_id = item.user_id, kind = kind.as_str(), "batch item applied");
+        ok.push(item.user_id);
+        (BatchItemStatus::Applied, None)
+      }
+      Ok(MemberOutcome::Unchanged) => {
+        tracing::debug!(user_id = item.user_id, kind = kind.as_str(), "batch item skipped, unchanged");
+        skipped.push(item.user_id);
+        (BatchItemStatus::Skipped, None)
+      }
+      Err(e) => {
+        tracing::warn!(user_id = item.user_id, kind = kind.as_str(), error = %e, "batch item failed");
+        failed.push(BatchItemError {
+          user_id: item.user_id,
+          message: e.to_string(),
+        });
+        (BatchItemStatus::Failed, Some(e.to_string()))
+      }
+    };
+
+    if let Some(job_id) = job_id {
+      if let Err(e) = db::update_batch_job_item(pool, job_id, item.user_id, status, message.as_deref()).await {
+        tracing::error!(job_id = job_id, user_id = item.user_id, error = %e, "update batch job item failed");
+      }
+    }
+  }
+
+  if let Some(job_id) = job_id {
+    if let Err(e) = db::finish_batch_job(pool, job_id).await {
+      tracing::error!(job_id = job_id, error = %e, "finish batch job failed");
+    }
+  }
+
+  BatchResult {
+    success_user_ids: ok,
+    skipped_user_ids: skipped,
+    failed,
+    snapshot_id: None,
+    job_id,
+  }
+}
+
+/// 单次展开目标项目时最多翻的页数（每页 100），避免关键字过宽时无限翻页
+const MAX_TARGET_PAGES: u32 = 20;
+
+/// 把 ProjectTargets 展开为去重后的项目标识列表（项目 ID 或 path_with_namespace）。
+/// 关键字只匹配当前用户是成员的项目；任一来源超过 MAX_TARGET_PAGES 页时报错而不是截断，
+/// 避免只对部分项目执行批量操作
+async fn resolve_target_projects(cfg: &GitLabConfig, targets: &ProjectTargets) -> Result<Vec<String>, String> {
+  let mut projects: Vec<String> = Vec::new();
+  let mut push = |p: String| {
+    if !p.is_empty() && !projects.contains(&p) {
+      projects.push(p);
+    }
+  };
+
+  for p in &targets.projects {
+    push(p.trim().to_string());
+  }
+
+  if let Some(keyword) = targets.keyword.as_deref().map(str::trim).filter(|k| !k.is_empty()) {
+    let options = ProjectSearchOptions {
+      membership: true,
+      ..Default::default()
+    };
+    let mut done = false;
+    for page in 1..=MAX_TARGET_PAGES {
+      let (items, total) = gitlab::search_projects(cfg, keyword, &options, page, 100)
+        .await
+        .map_err(|e| e.to_string())?;
+      done = items.len() < 100 || (page as u64) * 100 >= total;
+      for p in items {
+        push(p.id.to_string());
+      }
+      if done {
+        break;
+      }
+    }
+    if !done {
+      return Err(format!(
+        "keyword '{}' matches more than {} projects, please narrow it down",
+        keyword,
+        MAX_TARGET_PAGES * 100
+      ));
+    }
+  }
+
+  if let Some(namespace) = targets.namespace.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+    let mut done = false;
+    for page in 1..=MAX_TARGET_PAGES {
+      let (items, total) = gitlab::list_group_projects(cfg, namespace, page, 100)
+        .await
+        .map_err(|e| e.to_string())?;
+      done = items.len() < 100 || (page as u64) * 100 >= total;
+      for p in items {
+        push(p.id.to_string());
+      }
+      if done {
+        break;
+      }
+    }
+    if !done {
+      return Err(format!(
+        "namespace '{}' has more than {} projects, please list target projects explicitly",
+        namespace,
+        MAX_TARGET_PAGES * 100
+      ));
+    }
+  }
+
+  Ok(projects)
+}
+
+/// 把一个本地分组（展开嵌套后）批量加入多个项目。
+/// access_level 为空时使用成员在分组中的权限；成员也未设置权限时该用户记为失败
+#[tauri::command]
+async fn batch_apply_group_to_projects(
+  state: State<'_, AppState>,
+  group_id: i64,
+  targets: ProjectTargets,
+  access_level: Option<AccessLevel>,
+  expires_at: Option<ExpiryInput>,
+  member_role_id: Option<u64>,
+) -> Result<GroupApplyResult, String> {
+  tracing::info!(
+    group_id = group_id,
+    targets = ?targets,
+    access_level = ?access_level,
+    expires_at = ?expires_at,
+    member_role_id = ?member_role_id,
+    "batch_apply_group_to_projects called"
+  );
+
+  let cfg = require_cfg(&state)?;
+  let expires_at = expiry::resolve_expiry(&state.db, expires_at)
+    .await
+    .map_err(|e| e.to_string())?;
+  let members = smart_group::resolve_group_members(&state.db, Some(&cfg), group_id, true)
+    .await
+    .map_err(|e| e.to_string())?;
+  let projects = resolve_target_projects(&cfg, &targets).await?;
+  if projects.is_empty() {
+    return Err("no target projects matched".to_string());
+  }
+
+  let mut items = Vec::new();
+  let mut missing_level = Vec::new();
+  for m in &members {
+    match access_level.or(m.access_level) {
+      Some(level) => items.push(BatchAddItem {
+        user_id: m.member.user_id,
+        access_level: level,
+        expires_at: expires_at.clone(),
+        member_role_id,
+      }),
+      None => missing_level.push(m.member.user_id),
+    }
+  }
+
+  let operations = projects
+    .iter()
+    .map(|project| ChangeOperation {
+      kind: BatchJobKind::Add,
+      source_type: MembershipSourceType::Project,
+      project: project.clone(),
+      items: items.clone(),
+      user_ids: Vec::new(),
+      updates: Vec::new(),
+      shares: Vec::new(),
+      policy: None,
+    })
+    .collect();
+  require_approval(&state.db, &cfg, operations).await?;
+
+  let mut snapshots = Vec::new();
+  for project in &projects {
+    snapshots.push(take_snapshot(&state.db, &cfg, project, "batch_apply_group_to_projects").await?);
+  }
+
+  let mut results = Vec::new();
+  for (project, snapshot_id) in projects.into_iter().zip(snapshots) {
+    let mut result = run_batch_add(&state.db, &cfg, &project, snapshot_id, None, &items).await;
+    result.failed.extend(missing_level.iter().map(|uid| BatchItemError {
+      user_id: *uid,
+      message: "no access level specified for this member".to_string(),
+    }));
+    tracing::info!(
+      project = %project,
+      success_count = result.success_user_ids.len(),
+      skipped_count = result.skipped_user_ids.len(),
+      failed_count = result.failed.len(),
+      "batch_apply_group_to_projects project done"
+    );
+    results.push(ProjectBatchResult { project, result });
+  }
+
+  Ok(GroupApplyResult {
+    group_id,
+    user_ids: members.iter().map(|m| m.member.user_id).collect(),
+    projects: results,
+  })
+}
+
+/// 把一个本地分组（展开嵌套后）的成员从多个项目中移除
+#[tauri::command]
+async fn batch_remove_group_from_projects(
+  state: State<'_, AppState>,
+  group_id: i64,
+  targets: ProjectTargets,
+) -> Result<GroupApplyResult, String> {
+  tracing::info!(group_id = group_id, targets = ?targets, "batch_remove_group_from_projects called");
+
+  let cfg = require_cfg(&state)?;
+  let members = smart_group::resolve_group_members(&state.db, Some(&cfg), group_id, true)
+    .await
+    .map_err(|e| e.to_string())?;
+  let projects = resolve_target_projects(&cfg, &targets).await?;
+  if projects.is_empty() {
+    return Err("no target projects matched".to_string());
+  }
+
+  let user_ids: Vec<u64> = members.iter().map(|m| m.member.user_id).collect();
+  let operations = projects
+    .iter()
+    .map(|project| ChangeOperation {
+      kind: BatchJobKind::Remove,
+      source_type: MembershipSourceType::Project,
+      project: project.clone(),
+      items: Vec::new(),
+      user_ids: user_ids.clone(),
+      updates: Vec::new(),
+      shares: Vec::new(),
+      policy: None,
+    })
+    .collect();
+  require_approval(&state.db, &cfg, operations).await?;
+
+  let mut snapshots = Vec::new();
+  for project in &projects {
+    snapshots.push(take_snapshot(&state.db, &cfg, project, "batch_remove_group_from_projects").await?);
+  }
+
+  let mut results = Vec::new();
+  for (project, snapshot_id) in projects.into_iter().zip(snapshots) {
+    let result = run_batch_remove(&state.db, &cfg, &project, snapshot_id, None, &user_ids).await;
+    tracing::info!(
+      project = %project,
+      success_count = result.success_user_ids.len(),
+      skipped_count = result.skipped_user_ids.len(),
+      failed_count = result.failed.len(),
+      "batch_remove_group_from_projects project done"
+    );
+    results.push(ProjectBatchResult { project, result });
+  }
+
+  Ok(GroupApplyResult {
+    group_id,
+    user_ids,
+    projects: results,
+  })
+}
+
+/// 逐个批准 GitLab 分组的访问申请，执行前按策略规则检查；分组没有快照与批量任务
+/// （项目的批准走 run_batch_approve）
+async fn approve_request_items(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  source_type: MembershipSourceType,
+  source: &str,
+  items: Vec<BatchJobItem>,
+) -> BatchResult {
+  let blocked = match source_type {
+    MembershipSourceType::Project => policy::check_items(pool, cfg, BatchJobKind::Add, source, &items).await,
+    MembershipSourceType::Group => policy::check_group_items(pool, cfg, BatchJobKind::Add, source, &items).await,
+  };
+
+  let mut ok = Vec::new();
+  let mut failed = Vec::new();
+  for item in items {
+    let outcome = match (blocked.get(&item.user_id), item.access_level) {
+      (Some(reason), _) => Err(anyhow::anyhow!("blocked by policy: {}", reason)),
+      (None, Some(level)) => gitlab::approve_access_request(cfg, source_type, source, item.user_id, level).await,
+      (None, None) => Err(anyhow::anyhow!("no access level specified for this member")),
+    };
+    match outcome {
+      Ok(()) => ok.push(item.user_id),
+      Err(e) => {
+        tracing::warn!(user_id = item.user_id, error = %e, "approve access request failed");
+        failed.push(BatchItemError {
+          user_id: item.user_id,
+          message: e.to_string(),
+        });
+      }
+    }
+  }
+
+  BatchResult {
+    success_user_ids: ok,
+    failed,
+    skipped_user_ids: Vec::new(),
+    snapshot_id: None,
+    job_id: None,
+  }
+}
+
+fn source_target(source_type: MembershipSourceType, source: &str) -> String {
+  match source_type {
+    MembershipSourceType::Project => format!("project:{}", source),
+    MembershipSourceType::Group => format!("group:{}", source),
+  }
+}
+
+#[tauri::command]
+async fn list_access_requests(
+  state: State<'_, AppState>,
+  source_type: MembershipSourceType,
+  source: String,
+) -> Result<Vec<AccessRequest>, String> {
+  tracing::info!(source_type = ?source_type, source = %source, "list_access_requests called");
+
+  let cfg = require_cfg(&state)?;
+  let result = gitlab::list_access_requests(&cfg, source_type, &source)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(requests) => tracing::info!(count = requests.len(), "list_access_requests success"),
+    Err(e) => tracing::error!(error = %e, "list_access_requests failed"),
+  }
+  result
+}
+
+/// 批准访问申请：权限优先取 access_level，为空时取本地分组 group_id 中该成员的默认权限（含嵌套与动态成员），
+/// 两者都没有的用户记为失败。与加人一样经过审批策略与策略规则；项目的批准记为批量任务，可撤销
+#[tauri::command]
+async fn approve_access_requests(
+  state: State<'_, AppState>,
+  source_type: MembershipSourceType,
+  source: String,
+  user_ids: Vec<u64>,
+  access_level: Option<AccessLevel>,
+  group_id: Option<i64>,
+) -> Result<BatchResult, String> {
+  tracing::info!(
+    source_type = ?source_type,
+    source = %source,
+    user_count = user_ids.len(),
+    access_level = ?access_level,
+    group_id = ?group_id,
+    "approve_access_requests called"
+  );
+
+  if access_level.is_none() && group_id.is_none() {
+    return Err("access_level or group_id is required".to_string());
+  }
+  let cfg = require_cfg(&state)?;
+  let defaults: HashMap<u64, AccessLevel> = match group_id {
+    Some(group_id) => smart_group::resolve_group_members(&state.db, Some(&cfg), group_id, true)
+      .await
+      .map_err(|e| e.to_string())?
+      .into_iter()
+      .filter_map(|m| m.access_level.map(|level| (m.member.user_id, level)))
+      .collect(),
+    None => HashMap::new(),
+  };
+
+  let mut failed = Vec::new();
+  let mut items = Vec::new();
+  for uid in resolve_user_selector(&state.db, user_ids, None).await? {
+    match access_level.or_else(|| defaults.get(&uid).copied()) {
+      Some(level) => items.push(BatchJobItem {
+        user_id: uid,
+        access_level: Some(level),
+        expires_at: None,
+        prior_access_level: None,
+        prior_expires_at: None,
+        member_role_id: None,
+        prior_member_role_id: None,
+        status: BatchItemStatus::Pending,
+        message: None,
+      }),
+      None => failed.push(BatchItemError {
+        user_id: uid,
+        message: "no access level: user has no default level in the selected group".to_string(),
+      }),
+    }
+  }
+
+  let mut operation = job_operation(BatchJobKind::Approve, source.trim(), &items);
+  operation.source_type = source_type;
+  require_approval(&state.db, &cfg, vec![operation.clone()]).await?;
+
+  // 项目的批准先生成快照并记为批准任务，可续跑、重试与撤销；GitLab 分组没有快照与批量任务
+  let mut result = match source_type {
+    MembershipSourceType::Project => {
+      let snapshot_id = take_snapshot(&state.db, &cfg, source.trim(), "approve_access_requests").await?;
+      run_batch_approve(&state.db, &cfg, source.trim(), snapshot_id, &operation.items).await
+    }
+    MembershipSourceType::Group => approve_request_items(&state.db, &cfg, source_type, source.trim(), items).await,
+  };
+  failed.append(&mut result.failed);
+  result.failed = failed;
+
+  let detail = serde_json::json!({ "accessLevel": access_level, "groupId": group_id, "result": result });
+  if let Err(e) = db::record_history(&state.db, "approve_access_requests", &source_target(source_type, &source), &detail).await {
+    tracing::error!(error = %e, "approve_access_requests record history failed");
+  }
+
+  tracing::info!(
+    success_count = result.success_user_ids.len(),
+    failed_count = result.failed.len(),
+    "approve_access_requests completed"
+  );
+  Ok(result)
+}
+
+/// 批量拒绝访问申请；申请已被处理或撤回的用户记为跳过
+#[tauri::command]
+async fn deny_access_requests(
+  state: State<'_, AppState>,
+  source_type: MembershipSourceType,
+  source: String,
+  user_ids: Vec<u64>,
+) -> Result<BatchResult, String> {
+  tracing::info!(source_type = ?source_type, source = %source, user_count = user_ids.len(), "deny_access_requests called");
+
+  let cfg = require_cfg(&state)?;
+  let mut result = BatchResult {
+    success_user_ids: Vec::new(),
+    failed: Vec::new(),
+    skipped_user_ids: Vec::new(),
+    snapshot_id: None,
+    job_id: None,
+  };
+  for uid in resolve_user_selector(&state.db, user_ids, None).await? {
+    match gitlab::deny_access_request(&cfg, source_type, &source, uid).await {
+      Ok(MemberOutcome::Changed) => result.success_user_ids.push(uid),
+      Ok(MemberOutcome::Unchanged) => result.skipped_user_ids.push(uid),
+      Err(e) => result.failed.push(BatchItemError {
+        user_id: uid,
+        message: e.to_string(),
+      }),
+    }
+  }
+
+  let detail = serde_json::json!({ "result": result });
+  if let Err(e) = db::record_history(&state.db, "deny_access_requests", &source_target(source_type, &source), &detail).await {
+    tracing::error!(error = %e, "deny_access_requests record history failed");
+  }
+
+  tracing::info!(
+    success_count = result.success_user_ids.len(),
+    skipped_count = result.skipped_user_ids.len(),
+    failed_count = result.failed.len(),
+    "deny_access_requests completed"
+  );
+  Ok(result)
+}
+
+async fn build_copy_plan(
+  cfg: &GitLabConfig,
+  source: &str,
+  target: &str,
+  options: &CopyMembersOptions,
+  expires_override: Option<&str>,
+) -> Result<Vec<CopyPlanItem>, String> {
+  let source_members = gitlab::list_all_project_members(cfg, source, !options.direct_only)
+    .await
+    .map_err(|e| e.to_string())?;
+  let target_direct = gitlab::list_all_project_members(cfg, target, false)
+    .await
+    .map_err(|e| e.to_string())?;
+  let target_all = gitlab::list_all_project_members(cfg, target, true)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(
+    source_members
+      .into_iter()
+      .filter(|m| options.min_access_level.is_none_or(|min| m.access_level >= min))
+      .map(|m| {
+        let access_level = options.access_level.unwrap_or(m.access_level);
+        // 直接成员一律跳过；仅通过继承可见的成员只有继承权限已不低于计划权限时才跳过
+        let existing_access_level = match target_direct.iter().find(|t| t.id == m.id) {
+          Some(t) => Some(t.access_level),
+          None => target_all
+            .iter()
+            .find(|t| t.id == m.id && t.access_level >= access_level)
+            .map(|t| t.access_level),
+        };
+        CopyPlanItem {
+          user_id: m.id,
+          existing_access_level,
+          access_level,
+          expires_at: expires_override.map(str::to_string).or(m.expires_at),
+          username: m.username,
+          name: m.name,
+        }
+      })
+      .collect(),
+  )
+}
+
+/// 预览从 source 复制成员到 target 的计划（不做任何修改）
+#[tauri::command]
+async fn preview_copy_project_members(
+  state: State<'_, AppState>,
+  source: String,
+  target: String,
+  options: Option<CopyMembersOptions>,
+) -> Result<CopyMembersPreview, String> {
+  let options = options.unwrap_or_default();
+  tracing::info!(source = %source, target = %target, options = ?options, "preview_copy_project_members called");
+
+  let cfg = require_cfg(&state)?;
+  let expires_at = expiry::resolve_expiry(&state.db, options.expires_at)
+    .await
+    .map_err(|e| e.to_string())?;
+  let items = build_copy_plan(&cfg, source.trim(), target.trim(), &options, expires_at.as_deref()).await?;
+
+  tracing::info!(count = items.len(), "preview_copy_project_members success");
+  Ok(CopyMembersPreview { source, target, items })
+}
+
+/// 按复制计划把成员加入 target；目标已有的成员跳过。user_ids 不为空时只执行预览中勾选的用户
+#[tauri::command]
+async fn copy_project_members(
+  state: State<'_, AppState>,
+  source: String,
+  target: String,
+  options: Option<CopyMembersOptions>,
+  user_ids: Option<Vec<u64>>,
+) -> Result<BatchResult, String> {
+  let options = options.unwrap_or_default();
+  tracing::info!(
+    source = %source,
+    target = %target,
+    options = ?options,
+    selected = ?user_ids.as_ref().map(|x| x.len()),
+    "copy_project_members called"
+  );
+
+  let cfg = require_cfg(&state)?;
+  let expires_at = expiry::resolve_expiry(&state.db, options.expires_at)
+    .await
+    .map_err(|e| e.to_string())?;
+  let plan = build_copy_plan(&cfg, source.trim(), target.trim(), &options, expires_at.as_deref()).await?;
+  let items: Vec<BatchAddItem> = plan
+    .into_iter()
+    .filter(|p| p.existing_access_level.is_none())
+    .filter(|p| user_ids.as_ref().is_none_or(|ids| ids.contains(&p.user_id)))
+    .map(|p| BatchAddItem {
+      user_id: p.user_id,
+      access_level: p.access_level,
+      expires_at: p.expires_at,
+      member_role_id: None,
+    })
+    .collect();
+
+  require_approval(
+    &state.db,
+    &cfg,
+    vec![ChangeOperation {
+      kind: BatchJobKind::Add,
+      source_type: MembershipSourceType::Project,
+      project: target.trim().to_string(),
+      items: items.clone(),
+      user_ids: Vec::new(),
+      updates: Vec::new(),
+      shares: Vec::new(),
+      policy: None,
+    }],
+  )
+  .await?;
+  let snapshot_id = take_snapshot(&state.db, &cfg, target.trim(), "copy_project_members").await?;
+  let result = run_batch_add(&state.db, &cfg, target.trim(), snapshot_id, None, &items).await;
+
+  let detail = serde_json::json!({ "source": source, "options": options, "result": result });
+  if let Err(e) = db::record_history(&state.db, "copy_project_members", &format!("project:{}", target), &detail).await {
+    tracing::error!(error = %e, "copy_project_members record history failed");
+  }
+
+  tracing::info!(
+    success_count = result.success_user_ids.len(),
+    skipped_count = result.skipped_user_ids.len(),
+    failed_count = result.failed.len(),
+    "copy_project_members completed"
+  );
+  Ok(result)
+}
+
+/// 对比多个项目的成员（含继承成员），返回 用户 × 项目 矩阵并标记缺失 / 权限不一致 / 过期时间不一致
+#[tauri::command]
+async fn compare_project_members(state: State<'_, AppState>, projects: Vec<String>) -> Result<MembershipComparison, String> {
+  tracing::info!(projects = ?projects, "compare_project_members called");
+
+  if projects.len() < 2 {
+    return Err("at least two projects are required".to_string());
+  }
+
+  let cfg = require_cfg(&state)?;
+  let mut members = Vec::new();
+  for project in &projects {
+    members.push(
+      gitlab::list_all_project_members(&cfg, project.trim(), true)
+        .await
+        .map_err(|e| e.to_string())?,
+    );
+  }
+
+  let comparison = report::compare_members(projects, members);
+  tracing::info!(rows = comparison.rows.len(), "compare_project_members success");
+  Ok(comparison)
+}
+
+/// 把对比结果导出为 CSV 文本，由前端负责保存
+#[tauri::command]
+fn export_comparison_csv(comparison: MembershipComparison) -> String {
+  report::comparison_to_csv(&comparison)
+}
+
+/// 权限审计报表：按项目列出即将过期、高权限、长期不活跃（仅管理员 token）以及被封禁/停用的成员
+#[tauri::command]
+async fn access_review_report(
+  state: State<'_, AppState>,
+  projects: Vec<String>,
+  options: Option<AccessReviewOptions>,
+) -> Result<AccessReviewReport, String> {
+  let options = options.unwrap_or_default();
+  tracing::info!(projects = ?projects, options = ?options, "access_review_report called");
+
+  report::validate_options(&options).map_err(|e| e.to_string())?;
+  let cfg = require_cfg(&state)?;
+  let activity_checked = gitlab::current_user(&cfg)
+    .await
+    .map_err(|e| e.to_string())?
+    .is_admin;
+
+  let today = chrono::Local::now().date_naive();
+  // 同一用户可能出现在多个项目中，活动时间只查一次
+  let mut activity: HashMap<u64, Option<String>> = HashMap::new();
+  let mut reviews = Vec::new();
+
+  for project in projects {
+    let members = gitlab::list_all_project_members(&cfg, project.trim(), true)
+      .await
+      .map_err(|e| e.to_string())?;
+    if activity_checked {
+      for m in &members {
+        if activity.contains_key(&m.id) {
+          continue;
+        }
+        let last = match gitlab::get_user(&cfg, m.id).await {
+          Ok(u) => u.and_then(|u| u.last_activity_on),
+          Err(e) => {
+            tracing::warn!(user_id = m.id, error = %e, "fetch user activity failed");
+            None
+          }
+        };
+        activity.insert(m.id, last);
+      }
+    }
+    let review = report::review_project(
+      project,
+      members,
+      activity_checked.then_some(&activity),
+      &options,
+      today,
+    );
+    reviews.push(review);
+  }
+
+  tracing::info!(project_count = reviews.len(), activity_checked = activity_checked, "access_review_report success");
+
+  Ok(AccessReviewReport {
+    generated_at: chrono::Utc::now().to_rfc3339(),
+    options,
+    activity_checked,
+    projects: reviews,
+  })
+}
+
+/// 导出权限审计报表，format 为 "csv" 或 "html"，返回文本内容
+#[tauri::command]
+fn export_access_review(report: AccessReviewReport, format: String) -> Result<String, String> {
+  match format.as_str() {
+    "csv" => Ok(report::review_to_csv(&report)),
+    "html" => Ok(report::review_to_html(&report)),
+    other => Err(format!("unsupported export format: {}", other)),
+  }
+}
+
+/// collect_user_memberships 的结果
+struct MembershipScan {
+  memberships: Vec<UserMembership>,
+  /// 是否为逐个扫描得到（非管理员接口）
+  scanned: bool,
+  /// 任一列表翻页达到 MAX_TARGET_PAGES 仍未结束
+  truncated: bool,
+  skipped: Vec<SkippedSource>,
+}
+
+/// 收集用户的直接成员关系：管理员 token 走 /users/:id/memberships，
+/// 否则逐个扫描 token 可管理的项目/分组
+async fn collect_user_memberships(cfg: &GitLabConfig, user_id: u64) -> Result<MembershipScan, String> {
+  let me = gitlab::current_user(cfg).await.map_err(|e| e.to_string())?;
+  let mut scan = MembershipScan {
+    memberships: Vec::new(),
+    scanned: !me.is_admin,
+    truncated: false,
+    skipped: Vec::new(),
+  };
+
+  if me.is_admin {
+    let mut done = false;
+    for page in 1..=MAX_TARGET_PAGES {
+      let (items, total) = gitlab::list_user_memberships(cfg, user_id, page, 100)
+        .await
+        .map_err(|e| e.to_string())?;
+      done = items.len() < 100 || (page as u64) * 100 >= total;
+      scan.memberships.extend(items);
+      if done {
+        break;
+      }
+    }
+    scan.truncated = !done;
+    return Ok(scan);
+  }
+
+  let mut done = false;
+  for page in 1..=MAX_TARGET_PAGES {
+    let (projects, total) = gitlab::list_manageable_projects(cfg, page, 100)
+      .await
+      .map_err(|e| e.to_string())?;
+    done = projects.len() < 100 || (page as u64) * 100 >= total;
+    for p in projects {
+      match gitlab::get_direct_member(cfg, MembershipSourceType::Project, &p.id.to_string(), user_id).await {
+        Ok(Some(m)) => scan.memberships.push(UserMembership {
+          source_type: MembershipSourceType::Project,
+          source_id: p.id,
+          source_name: p.path_with_namespace,
+          access_level: m.access_level,
+        }),
+        Ok(None) => {}
+        Err(e) => {
+          tracing::warn!(project_id = p.id, error = %e, "scan project member failed, skipped");
+          scan.skipped.push(SkippedSource {
+            source_type: MembershipSourceType::Project,
+            source_id: p.id,
+            source_name: p.path_with_namespace,
+            message: e.to_string(),
+          });
+        }
+      }
+    }
+    if done {
+      break;
+    }
+  }
+  scan.truncated |= !done;
+
+  let mut done = false;
+  for page in 1..=MAX_TARGET_PAGES {
+    let (groups, total) = gitlab::list_manageable_groups(cfg, page, 100)
+      .await
+      .map_err(|e| e.to_string())?;
+    done = groups.len() < 100 || (page as u64) * 100 >= total;
+    for g in groups {
+      match gitlab::get_direct_member(cfg, MembershipSourceType::Group, &g.id.to_string(), user_id).await {
+        Ok(Some(m)) => scan.memberships.push(UserMembership {
+          source_type: MembershipSourceType::Group,
+          source_id: g.id,
+          source_name: g.full_path,
+          access_level: m.access_level,
+        }),
+        Ok(None) => {}
+        Err(e) => {
+          tracing::warn!(group_id = g.id, error = %e, "scan group member failed, skipped");
+          scan.skipped.push(SkippedSource {
+            source_type: MembershipSourceType::Group,
+            source_id: g.id,
+            source_name: g.full_path,
+            message: e.to_string(),
+          });
+        }
+      }
+    }
+    if done {
+      break;
+    }
+  }
+  scan.truncated |= !done;
+
+  if scan.truncated {
+    tracing::warn!(user_id = user_id, "membership scan reached page limit, result may be incomplete");
+  }
+  Ok(scan)
+}
+
+/// 离职清理预览：列出用户在 GitLab 中的直接成员关系以及所在的本地分组
+#[tauri::command]
+async fn preview_offboard_user(state: State<'_, AppState>, user_id: u64) -> Result<OffboardPreview, String> {
+  tracing::info!(user_id = user_id, "preview_offboard_user called");
+
+  let cfg = require_cfg(&state)?;
+  let scan = collect_user_memberships(&cfg, user_id).await?;
+  let local_groups = db::list_member_groups(&state.db, user_id)
+    .await
+    .map_err(|e| e.to_string())?;
 
   tracing::info!(
-    success_count = ok.len(),
-    failed_count = failed.len(),
-    "batch_remove_members_from_project completed"
+    user_id = user_id,
+    scanned = scan.scanned,
+    truncated = scan.truncated,
+    skipped_count = scan.skipped.len(),
+    membership_count = scan.memberships.len(),
+    local_group_count = local_groups.len(),
+    "preview_offboard_user success"
   );
-  
-  Ok(BatchResult {
-    success_user_ids: ok,
-    failed,
+
+  Ok(OffboardPreview {
+    user_id,
+    scanned: scan.scanned,
+    truncated: scan.truncated,
+    skipped: scan.skipped,
+    memberships: scan.memberships,
+    local_groups,
   })
 }
 
+/// 离职清理：从给定（为空时重新收集）的项目/分组中移除用户，并移出所有本地分组，结果写入操作历史
+#[tauri::command]
+async fn offboard_user(
+  state: State<'_, AppState>,
+  user_id: u64,
+  memberships: Option<Vec<UserMembership>>,
+) -> Result<OffboardResult, String> {
+  tracing::info!(user_id = user_id, preset = memberships.is_some(), "offboard_user called");
+
+  let cfg = require_cfg(&state)?;
+  let memberships = match memberships {
+    Some(m) => m,
+    None => collect_user_memberships(&cfg, user_id).await?.memberships,
+  };
+  // 与移除成员一样受审批策略约束；需要审批时不做任何修改（包括本地分组）
+  require_approval(
+    &state.db,
+    &cfg,
+    memberships
+      .iter()
+      .map(|m| ChangeOperation {
+        kind: BatchJobKind::Remove,
+        source_type: m.source_type,
+        project: m.source_id.to_string(),
+        items: Vec::new(),
+        user_ids: vec![user_id],
+        updates: Vec::new(),
+        shares: Vec::new(),
+        policy: None,
+      })
+      .collect(),
+  )
+  .await?;
+
+  let mut removed = Vec::new();
+  let mut unchanged = Vec::new();
+  let mut failed = Vec::new();
+  for m in memberships {
+    let source = m.source_id.to_string();
+    let item = BatchJobItem {
+      user_id,
+      access_level: None,
+      expires_at: None,
+      prior_access_level: Some(m.access_level),
+      prior_expires_at: None,
+      member_role_id: None,
+      prior_member_role_id: None,
+      status: BatchItemStatus::Pending,
+      message: None,
+    };
+    // 受保护用户等规则对项目与 GitLab 分组成员同样生效
+    let mut blocked = match m.source_type {
+      MembershipSourceType::Project => policy::check_items(&state.db, &cfg, BatchJobKind::Remove, &source, &[item]).await,
+      MembershipSourceType::Group => policy::check_group_items(&state.db, &cfg, BatchJobKind::Remove, &source, &[item]).await,
+    };
+    if let Some(reason) = blocked.remove(&user_id) {
+      failed.push(MembershipError {
+        membership: m,
+        message: format!("blocked by policy: {}", reason),
+      });
+      continue;
+    }
+    if m.source_type == MembershipSourceType::Project {
+      if let Err(e) = take_snapshot(&state.db, &cfg, &source, "offboard_user").await {
+        failed.push(MembershipError { membership: m, message: e });
+        continue;
+      }
+    }
+    let res = match m.source_type {
+      MembershipSourceType::Project => gitlab::remove_member(&cfg, &source, user_id).await,
+      MembershipSourceType::Group => gitlab::remove_group_member(&cfg, &source, user_id).await,
+    };
+    match res {
+      Ok(MemberOutcome::Changed) => removed.push(m),
+      Ok(MemberOutcome::Unchanged) => unchanged.push(m),
+      Err(e) => {
+        tracing::warn!(user_id = user_id, source = %m.source_name, error = %e, "offboard remove failed");
+        failed.push(MembershipError {
+          membership: m,
+          message: e.to_string(),
+        });
+      }
+    }
+  }
+
+  let local_groups_removed = db::remove_member_from_all_groups(&state.db, user_id)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let result = OffboardResult {
+    user_id,
+    removed,
+    unchanged,
+    failed,
+    local_groups_removed,
+  };
+
+  let detail = serde_json::to_value(&result).unwrap_or_default();
+  if let Err(e) = db::record_history(&state.db, "offboard_user", &format!("user:{}", user_id), &detail).await {
+    tracing::error!(error = %e, "offboard_user record history failed");
+  }
+
+  tracing::info!(
+    user_id = user_id,
+    removed_count = result.removed.len(),
+    unchanged_count = result.unchanged.len(),
+    failed_count = result.failed.len(),
+    local_groups_removed = local_groups_removed,
+    "offboard_user completed"
+  );
+  Ok(result)
+}
+
+#[tauri::command]
+async fn list_history(
+  state: State<'_, AppState>,
+  action: Option<String>,
+  page: Option<u32>,
+  per_page: Option<u32>,
+) -> Result<(Vec<HistoryEntry>, u64), String> {
+  let page = page.unwrap_or(1);
+  let per_page = per_page.unwrap_or(50).clamp(1, 100);
+  tracing::info!(action = ?action, page = page, per_page = per_page, "list_history called");
+
+  db::list_history(&state.db, action, page, per_page)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_project_snapshots(state: State<'_, AppState>, project: String) -> Result<Vec<ProjectSnapshot>, String> {
+  tracing::info!(project = %project, "list_project_snapshots called");
+
+  let cfg = require_cfg(&state)?;
+  let project_id = snapshot::resolve_project_id(&cfg, &project)
+    .await
+    .map_err(|e| e.to_string())?;
+  db::list_project_snapshots(&state.db, &project_id, project.trim())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+async fn load_snapshot_diff(state: &AppState, cfg: &GitLabConfig, snapshot_id: i64) -> Result<SnapshotDiff, String> {
+  let (snap, members) = db::get_snapshot(&state.db, snapshot_id)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("snapshot {} not found", snapshot_id))?;
+  let live = snapshot::fetch_live_members(cfg, &snap.project)
+    .await
+    .map_err(|e| e.to_string())?;
+  Ok(snapshot::diff(snap, members, live))
+}
+
+/// 对比快照与项目当前成员
+#[tauri::command]
+async fn diff_snapshot(state: State<'_, AppState>, snapshot_id: i64) -> Result<SnapshotDiff, String> {
+  tracing::info!(snapshot_id = snapshot_id, "diff_snapshot called");
+
+  let cfg = require_cfg(&state)?;
+  let diff = load_snapshot_diff(&state, &cfg, snapshot_id).await?;
+
+  tracing::info!(
+    snapshot_id = snapshot_id,
+    added = diff.added.len(),
+    removed = diff.removed.len(),
+    changed = diff.changed.len(),
+    "diff_snapshot success"
+  );
+  Ok(diff)
+}
+
+/// 按快照恢复：把快照中的直接成员、现已不在项目中（或只剩继承权限）的用户按原权限与过期时间重新加入。
+/// 继承成员由上级分组决定，不在此恢复。user_ids 不为空时只恢复其中的用户
+#[tauri::command]
+async fn restore_snapshot(
+  state: State<'_, AppState>,
+  snapshot_id: i64,
+  user_ids: Option<Vec<u64>>,
+) -> Result<BatchResult, String> {
+  tracing::info!(
+    snapshot_id = snapshot_id,
+    selected = ?user_ids.as_ref().map(|x| x.len()),
+    "restore_snapshot called"
+  );
+
+  let cfg = require_cfg(&state)?;
+  let diff = load_snapshot_diff(&state, &cfg, snapshot_id).await?;
+  let project = diff.snapshot.project.clone();
+  // 直接成员被移除后仍可能通过上级分组可见，diff 中记为 changed，同样需要加回
+  let demoted = diff
+    .changed
+    .into_iter()
+    .filter(|c| c.after.source == SnapshotMemberSource::Inherited)
+    .map(|c| c.before);
+  let items: Vec<BatchAddItem> = diff
+    .removed
+    .into_iter()
+    .chain(demoted)
+    .filter(|m| m.source == SnapshotMemberSource::Direct)
+    .filter(|m| user_ids.as_ref().is_none_or(|ids| ids.contains(&m.user_id)))
+    .map(|m| BatchAddItem {
+      user_id: m.user_id,
+      access_level: m.access_level,
+      expires_at: m.expires_at,
+      member_role_id: m.member_role_id,
+    })
+    .collect();
+
+  require_approval(
+    &state.db,
+    &cfg,
+    vec![ChangeOperation {
+      kind: BatchJobKind::Add,
+      source_type: MembershipSourceType::Project,
+      project: project.clone(),
+      items: items.clone(),
+      user_ids: Vec::new(),
+      updates: Vec::new(),
+      shares: Vec::new(),
+      policy: None,
+    }],
+  )
+  .await?;
+  let pre_restore = take_snapshot(&state.db, &cfg, &project, "restore_snapshot").await?;
+  let result = run_batch_add(&state.db, &cfg, &project, pre_restore, None, &items).await;
+
+  let detail = serde_json::json!({ "snapshotId": snapshot_id, "result": result });
+  if let Err(e) = db::record_history(&state.db, "restore_snapshot", &format!("project:{}", project), &detail).await {
+    tracing::error!(error = %e, "restore_snapshot record history failed");
+  }
+
+  tracing::info!(
+    snapshot_id = snapshot_id,
+    success_count = result.success_user_ids.len(),
+    skipped_count = result.skipped_user_ids.len(),
+    failed_count = result.failed.len(),
+    "restore_snapshot completed"
+  );
+  Ok(result)
+}
+
+/// 撤销一个已完成的批量任务：加人任务移除当时新加入的用户（已是成员的不动），
+/// 移除任务按移除前的权限与过期时间重新加入，修改任务改回修改前的权限与过期时间。撤销本身也记为新的批量任务；
+/// 全部成功后才标记为已撤销，部分失败时可再次撤销
+#[tauri::command]
+async fn undo_batch(state: State<'_, AppState>, job_id: i64) -> Result<BatchResult, String> {
+  tracing::info!(job_id = job_id, "undo_batch called");
+
+  let cfg = require_cfg(&state)?;
+  let (job, items) = db::get_batch_job(&state.db, job_id)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("batch job {} not found", job_id))?;
+  match job.status {
+    BatchJobStatus::Undone => return Err(format!("batch job {} has already been undone", job_id)),
+    BatchJobStatus::Running => return Err(format!("batch job {} is still running", job_id)),
+    _ => {}
+  }
+
+  let mut remove_ids = Vec::new();
+  let mut restore = Vec::new();
+  let mut unknown = Vec::new();
+  for i in items.into_iter().filter(|i| i.status == BatchItemStatus::Applied) {
+    match (job.kind, i.prior_access_level) {
+      (BatchJobKind::Add | BatchJobKind::Approve, _) => remove_ids.push(i.user_id),
+      (BatchJobKind::Remove | BatchJobKind::Update, Some(level)) => restore.push(BatchAddItem {
+        user_id: i.user_id,
+        access_level: level,
+        expires_at: i.prior_expires_at,
+        member_role_id: i.prior_member_role_id,
+      }),
+      (BatchJobKind::Remove | BatchJobKind::Update, None) => unknown.push(i.user_id),
+    }
+  }
+  // 撤销同样受审批策略约束：撤销移除等同于按原权限重新加入，撤销修改按改回的权限计算
+  let (undo_kind, readd, reverts) = match job.kind {
+    BatchJobKind::Add | BatchJobKind::Approve => (BatchJobKind::Remove, Vec::new(), Vec::new()),
+    BatchJobKind::Remove => (BatchJobKind::Add, restore, Vec::new()),
+    BatchJobKind::Update => (BatchJobKind::Add, Vec::new(), restore),
+  };
+  require_approval(
+    &state.db,
+    &cfg,
+    vec![ChangeOperation {
+      kind: undo_kind,
+      source_type: MembershipSourceType::Project,
+      project: job.project.clone(),
+      items: readd.clone(),
+      user_ids: remove_ids.clone(),
+      updates: reverts.clone(),
+      shares: Vec::new(),
+      policy: None,
+    }],
+  )
+  .await?;
+
+  let snapshot_id = take_snapshot(&state.db, &cfg, &job.project, "undo_batch").await?;
+  let mut result = match job.kind {
+    BatchJobKind::Add | BatchJobKind::Approve => {
+      run_batch_remove(&state.db, &cfg, &job.project, snapshot_id, Some(job_id), &remove_ids).await
+    }
+    BatchJobKind::Remove => run_batch_add(&state.db, &cfg, &job.project, snapshot_id, Some(job_id), &readd).await,
+    BatchJobKind::Update => run_batch_update(&state.db, &cfg, &job.project, snapshot_id, Some(job_id), &reverts).await,
+  };
+  result.failed.extend(unknown.into_iter().map(|uid| BatchItemError {
+    user_id: uid,
+    message: "prior access level was not captured".to_string(),
+  }));
+
+  // 有失败项时保留原状态，可以再次撤销；已撤销成功的用户重试时会被记为跳过
+  if result.failed.is_empty() {
+    if let Err(e) = db::set_batch_job_status(&state.db, job_id, BatchJobStatus::Undone).await {
+      tracing::error!(job_id = job_id, error = %e, "undo_batch mark job undone failed");
+    }
+  } else {
+    tracing::warn!(job_id = job_id, failed_count = result.failed.len(), "undo_batch incomplete, job not marked undone");
+  }
+  let detail = serde_json::json!({ "jobId": job_id, "result": result });
+  if let Err(e) = db::record_history(&state.db, "undo_batch", &format!("project:{}", job.project), &detail).await {
+    tracing::error!(error = %e, "undo_batch record history failed");
+  }
+
+  tracing::info!(
+    job_id = job_id,
+    success_count = result.success_user_ids.len(),
+    skipped_count = result.skipped_user_ids.len(),
+    failed_count = result.failed.len(),
+    "undo_batch completed"
+  );
+  Ok(result)
+}
+
+#[tauri::command]
+async fn list_batch_jobs(
+  state: State<'_, AppState>,
+  project: Option<String>,
+  status: Option<BatchJobStatus>,
+  page: Option<u32>,
+  per_page: Option<u32>,
+) -> Result<(Vec<BatchJobSummary>, u64), String> {
+  let page = page.unwrap_or(1);
+  let per_page = per_page.unwrap_or(50).clamp(1, 100);
+  tracing::info!(project = ?project, status = ?status, page = page, per_page = per_page, "list_batch_jobs called");
+
+  db::list_batch_jobs(&state.db, project, status, page, per_page)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_batch_job(state: State<'_, AppState>, job_id: i64) -> Result<BatchJobDetail, String> {
+  tracing::info!(job_id = job_id, "get_batch_job called");
+  let (job, items) = db::get_batch_job(&state.db, job_id)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("batch job {} not found", job_id))?;
+  Ok(BatchJobDetail { job, items })
+}
+
+/// 继续执行任务中尚未处理（pending）的条目；only_failed=true 时改为重试失败条目。
+/// 与新建的批量操作一样先经过审批策略，再原子地把任务置为 running，同一任务不会被并发执行两次
+async fn continue_batch_job(state: &AppState, job_id: i64, only_failed: bool) -> Result<BatchResult, String> {
+  let cfg = require_cfg(state)?;
+  let (job, items) = db::get_batch_job(&state.db, job_id)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("batch job {} not found", job_id))?;
+  let todo = if only_failed {
+    BatchItemStatus::Failed
+  } else {
+    BatchItemStatus::Pending
+  };
+  let todo: Vec<BatchJobItem> = items.into_iter().filter(|i| i.status == todo).collect();
+  require_approval(&state.db, &cfg, vec![job_operation(job.kind, &job.project, &todo)]).await?;
+  let claimed = db::claim_batch_job(&state.db, job_id)
+    .await
+    .map_err(|e| e.to_string())?;
+  if !claimed {
+    return Err(format!("batch job {} is already running or has been undone", job_id));
+  }
+
+  let pending = match load_pending_batch_items(state, job_id, only_failed).await {
+    Ok(items) => items,
+    Err(e) => {
+      // 认领后出错时恢复为可续跑状态
+      if let Err(e) = db::set_batch_job_status(&state.db, job_id, BatchJobStatus::Interrupted).await {
+        tracing::error!(job_id = job_id, error = %e, "release batch job failed");
+      }
+      return Err(e);
+    }
+  };
+  let mut result = execute_batch_items(&state.db, &cfg, job.kind, &job.project, Some(job_id), pending).await;
+  result.snapshot_id = job.snapshot_id;
+  Ok(result)
+}
+
+async fn load_pending_batch_items(state: &AppState, job_id: i64, only_failed: bool) -> Result<Vec<BatchJobItem>, String> {
+  if only_failed {
+    db::reset_failed_batch_items(&state.db, job_id)
+      .await
+      .map_err(|e| e.to_string())?;
+  }
+  let (_, items) = db::get_batch_job(&state.db, job_id)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("batch job {} not found", job_id))?;
+  Ok(items.into_iter().filter(|i| i.status == BatchItemStatus::Pending).collect())
+}
+
+/// 继续执行被中断（应用退出/崩溃）的批量任务
+#[tauri::command]
+async fn resume_batch_job(state: State<'_, AppState>, job_id: i64) -> Result<BatchResult, String> {
+  tracing::info!(job_id = job_id, "resume_batch_job called");
+
+  let result = continue_batch_job(&state, job_id, false).await;
+  match &result {
+    Ok(r) => tracing::info!(
+      job_id = job_id,
+      success_count = r.success_user_ids.len(),
+      skipped_count = r.skipped_user_ids.len(),
+      failed_count = r.failed.len(),
+      "resume_batch_job completed"
+    ),
+    Err(e) => tracing::error!(job_id = job_id, error = %e, "resume_batch_job failed"),
+  }
+  result
+}
+
+/// 只重试任务中失败的条目
+#[tauri::command]
+async fn retry_failed_batch_items(state: State<'_, AppState>, job_id: i64) -> Result<BatchResult, String> {
+  tracing::info!(job_id = job_id, "retry_failed_batch_items called");
+
+  let result = continue_batch_job(&state, job_id, true).await;
+  match &result {
+    Ok(r) => tracing::info!(
+      job_id = job_id,
+      success_count = r.success_user_ids.len(),
+      skipped_count = r.skipped_user_ids.len(),
+      failed_count = r.failed.len(),
+      "retry_failed_batch_items completed"
+    ),
+    Err(e) => tracing::error!(job_id = job_id, error = %e, "retry_failed_batch_items failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn get_renewal_settings(state: State<'_, AppState>) -> Result<RenewalSettings, String> {
+  tracing::info!("get_renewal_settings called");
+  db::get_config_json(&state.db, renewal::SETTINGS_KEY)
+    .await
+    .map(|s| s.unwrap_or_default())
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_renewal_settings(state: State<'_, AppState>, settings: RenewalSettings) -> Result<(), String> {
+  tracing::info!(
+    enabled = settings.enabled,
+    window_days = settings.window_days,
+    extend_days = settings.extend_days,
+    auto_extend = settings.auto_extend,
+    group_count = settings.group_ids.len(),
+    "set_renewal_settings called"
+  );
+  let day_range = 1..=renewal::MAX_DAYS;
+  if !day_range.contains(&settings.window_days) || !day_range.contains(&settings.extend_days) {
+    return Err(format!("windowDays and extendDays must be between 1 and {}", renewal::MAX_DAYS));
+  }
+
+  let result = db::set_config_json(&state.db, renewal::SETTINGS_KEY, &settings)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(_) => tracing::info!("set_renewal_settings success"),
+    Err(e) => tracing::error!(error = %e, "set_renewal_settings failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn get_expiry_settings(state: State<'_, AppState>) -> Result<ExpirySettings, String> {
+  tracing::info!("get_expiry_settings called");
+  db::get_config_json(&state.db, expiry::SETTINGS_KEY)
+    .await
+    .map(|s| s.unwrap_or_default())
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_expiry_settings(state: State<'_, AppState>, settings: ExpirySettings) -> Result<(), String> {
+  tracing::info!(max_days = ?settings.max_days, "set_expiry_settings called");
+  if settings.max_days.is_some_and(|d| !(1..=expiry::MAX_DAYS).contains(&d)) {
+    return Err(format!("maxDays must be between 1 and {}", expiry::MAX_DAYS));
+  }
+
+  let result = db::set_config_json(&state.db, expiry::SETTINGS_KEY, &settings)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(_) => tracing::info!("set_expiry_settings success"),
+    Err(e) => tracing::error!(error = %e, "set_expiry_settings failed"),
+  }
+  result
+}
+
+/// 把过期时间输入（日期或 +30d、end-of-quarter 等相对形式）解析为具体日期并校验，供界面预览
+#[tauri::command]
+async fn resolve_expiry(state: State<'_, AppState>, expires_at: ExpiryInput) -> Result<String, String> {
+  tracing::info!(expires_at = ?expires_at, "resolve_expiry called");
+  expiry::resolve_expiry(&state.db, Some(expires_at))
+    .await
+    .map(|d| d.unwrap_or_default())
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_tracked_projects(state: State<'_, AppState>) -> Result<Vec<TrackedProject>, String> {
+  tracing::info!("list_tracked_projects called");
+  db::list_tracked_projects(&state.db)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 把项目纳入过期巡检
+#[tauri::command]
+async fn add_tracked_project(state: State<'_, AppState>, project: String, name: Option<String>) -> Result<(), String> {
+  tracing::info!(project = %project, "add_tracked_project called");
+  let project = project.trim();
+  if project.is_empty() {
+    return Err("project is required".to_string());
+  }
+
+  let result = db::add_tracked_project(&state.db, project, name)
+    .await
+    .map_err(|e| e.to_string());
+
+  match &result {
+    Ok(_) => tracing::info!(project = %project, "add_tracked_project success"),
+    Err(e) => tracing::error!(project = %project, error = %e, "add_tracked_project failed"),
+  }
+  result
+}
+
+#[tauri::command]
+async fn remove_tracked_project(state: State<'_, AppState>, project: String) -> Result<(), String> {
+  tracing::info!(project = %project, "remove_tracked_project called");
+  db::remove_tracked_project(&state.db, &project)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 立即执行一次过期巡检；auto_extend 为空时沿用设置中的值。结果会保存为最近一次巡检
+#[tauri::command]
+async fn run_expiry_scan_now(state: State<'_, AppState>, auto_extend: Option<bool>) -> Result<ExpiryScanResult, String> {
+  tracing::info!(auto_extend = ?auto_extend, "run_expiry_scan_now called");
+
+  let cfg = require_cfg(&state)?;
+  let settings: RenewalSettings = db::get_config_json(&state.db, renewal::SETTINGS_KEY)
+    .await
+    .map_err(|e| e.to_string())?
+    .unwrap_or_default();
+  let auto_extend = auto_extend.unwrap_or(settings.auto_extend);
+
+  let result = renewal::run_expiry_scan(&state.db, &cfg, &settings, auto_extend)
+    .await
+    .map_err(|e| e.to_string())?;
+  if let Err(e) = db::set_config_json(&state.db, renewal::LAST_SCAN_KEY, &result).await {
+    tracing::error!(error = %e, "run_expiry_scan_now save result failed");
+  }
+  Ok(result)
+}
+
+#[tauri::command]
+async fn get_last_expiry_scan(state: State<'_, AppState>) -> Result<Option<ExpiryScanResult>, String> {
+  tracing::info!("get_last_expiry_scan called");
+  db::get_config_json(&state.db, renewal::LAST_SCAN_KEY)
+    .await
+    .map_err(|e| e.to_string())
+}
+
 fn main() {
   tauri::Builder::default()
     .setup(|app| {
@@ -439,43 +3035,124 @@ fn main() {
         .map_err(|e| Box::<dyn std::error::Error>::from(e.to_string()))?;
 
       let gitlab = match tauri::async_runtime::block_on(db::get_gitlab_config(&db)) {
-        Ok(Some((base_url, token))) => {
+        // 从不含密钥的备份恢复后 token 为空，需要重新在设置页填写
+        Ok(Some((base_url, token))) if !token.is_empty() => {
           tracing::info!("[setup] loaded GitLab config from database");
           Some(GitLabConfig { base_url, token })
         }
-        Ok(None) => None,
+        Ok(_) => None,
         Err(e) => {
           tracing::warn!(error = %e, "[setup] failed to load GitLab config from database");
           None
         }
       };
 
+      match tauri::async_runtime::block_on(db::mark_interrupted_batch_jobs(&db)) {
+        Ok(0) => {}
+        Ok(count) => tracing::warn!(count = count, "[setup] marked unfinished batch jobs as interrupted"),
+        Err(e) => tracing::warn!(error = %e, "[setup] failed to mark interrupted batch jobs"),
+      }
+
       app.manage(AppState {
         db,
         gitlab: Mutex::new(gitlab),
       });
 
+      // 后台过期巡检
+      renewal::spawn_scheduler(app.handle().clone());
+
       tracing::info!("Application initialized successfully");
       Ok(())
     })
     .invoke_handler(tauri::generate_handler![
       get_gitlab_config,
+      backup_database,
+      restore_database,
       set_gitlab_config,
       search_projects,
       list_project_members,
+      list_member_roles,
+      list_project_shared_groups,
+      share_project_with_group,
+      unshare_project_with_group,
       upsert_local_members,
       list_local_members,
       delete_local_members,
+      refresh_local_members,
+      list_member_sources,
+      set_local_member_notes,
+      list_local_tags,
+      add_tags_to_members,
+      remove_tags_from_members,
+      delete_local_tag,
       create_local_group,
       list_local_groups,
       update_local_group,
       delete_local_group,
       add_members_to_group,
       remove_members_from_group,
+      set_group_members_access_level,
+      include_groups,
+      exclude_groups,
+      list_group_includes,
       list_group_members,
+      create_smart_group,
+      set_group_rule,
+      preview_group_rule,
+      export_group_bundle,
+      import_group_bundle,
+      validate_manifest,
+      plan_manifest,
+      apply_manifest,
+      get_approval_policy,
+      set_approval_policy,
+      set_approval_secret,
+      list_change_requests,
+      get_change_request,
+      export_change_request,
+      import_change_request,
+      approve_change_request,
+      reject_change_request,
+      execute_change_request,
+      list_policy_rules,
+      create_policy_rule,
+      update_policy_rule,
+      delete_policy_rule,
       batch_add_members_to_project,
       batch_remove_members_from_project,
       add_member_to_project,
+      batch_apply_group_to_projects,
+      batch_remove_group_from_projects,
+      list_access_requests,
+      approve_access_requests,
+      deny_access_requests,
+      preview_copy_project_members,
+      copy_project_members,
+      compare_project_members,
+      export_comparison_csv,
+      access_review_report,
+      export_access_review,
+      preview_offboard_user,
+      offboard_user,
+      list_history,
+      list_project_snapshots,
+      diff_snapshot,
+      restore_snapshot,
+      undo_batch,
+      list_batch_jobs,
+      get_batch_job,
+      resume_batch_job,
+      retry_failed_batch_items,
+      get_renewal_settings,
+      set_renewal_settings,
+      get_expiry_settings,
+      set_expiry_settings,
+      resolve_expiry,
+      list_tracked_projects,
+      add_tracked_project,
+      remove_tracked_project,
+      run_expiry_scan_now,
+      get_last_expiry_scan,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
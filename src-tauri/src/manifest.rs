@@ -0,0 +1,411 @@
+use crate::gitlab::{self, GitLabConfig};
+use crate::models::{
+  AccessLevel, BatchAddItem, BatchJobKind, ChangeOperation, ExpirySettings, ManifestAction,
+  ManifestApplyError, ManifestApplyResult, ManifestChange, ManifestPlan, ManifestValidation,
+  MembershipSourceType, ProjectBatchResult, ProjectMember, TeamManifest,
+};
+use crate::{db, expiry};
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveDate, TimeDelta};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// 当前清单格式版本
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// expiresInDays 的上限（约十年）
+pub const MAX_EXPIRES_IN_DAYS: i64 = 3650;
+
+/// 读取并解析清单文件（YAML，兼容 JSON）
+pub fn load(path: &Path) -> Result<TeamManifest> {
+  let content = std::fs::read_to_string(path).with_context(|| format!("failed to read manifest {}", path.display()))?;
+  let manifest: TeamManifest = serde_yaml::from_str(&content).context("invalid manifest")?;
+  if manifest.version == 0 || manifest.version > MANIFEST_VERSION {
+    bail!("unsupported manifest version {} (supported: {})", manifest.version, MANIFEST_VERSION);
+  }
+  Ok(manifest)
+}
+
+/// 校验清单的引用关系与取值，不访问 GitLab
+pub fn validate(manifest: &TeamManifest) -> ManifestValidation {
+  let mut errors = Vec::new();
+
+  for (name, team) in &manifest.teams {
+    let mut seen = HashSet::new();
+    for username in &team.members {
+      if username.trim().is_empty() {
+        errors.push(format!("team {}: empty username", name));
+      } else if !seen.insert(username.as_str()) {
+        errors.push(format!("team {}: duplicate member {}", name, username));
+      }
+    }
+  }
+
+  let mut projects = HashSet::new();
+  for p in &manifest.projects {
+    if p.project.trim().is_empty() {
+      errors.push("project with empty path".to_string());
+      continue;
+    }
+    if !projects.insert(p.project.trim()) {
+      errors.push(format!("project {} is listed more than once", p.project));
+    }
+    for (i, grant) in p.access.iter().enumerate() {
+      let at = format!("project {} access[{}]", p.project, i);
+      match (&grant.team, &grant.user) {
+        (Some(team), None) if !manifest.teams.contains_key(team) => errors.push(format!("{}: unknown team {}", at, team)),
+        (Some(_), None) | (None, Some(_)) => {}
+        _ => errors.push(format!("{}: exactly one of team or user is required", at)),
+      }
+      if grant.access_level == AccessLevel::NoAccess {
+        errors.push(format!("{}: access level {} cannot be granted", at, grant.access_level));
+      }
+      if grant.expires_at.is_some() && grant.expires_in_days.is_some() {
+        errors.push(format!("{}: expiresAt and expiresInDays are mutually exclusive", at));
+      }
+      if let Some(date) = &grant.expires_at {
+        if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+          errors.push(format!("{}: invalid expiresAt {}", at, date));
+        }
+      }
+      if grant.expires_in_days.is_some_and(|d| !(1..=MAX_EXPIRES_IN_DAYS).contains(&d)) {
+        errors.push(format!("{}: expiresInDays must be between 1 and {}", at, MAX_EXPIRES_IN_DAYS));
+      }
+    }
+  }
+
+  ManifestValidation {
+    valid: errors.is_empty(),
+    errors,
+  }
+}
+
+/// 按过期时间设置校验清单中的过期时间（expiresAt 与按 expiresInDays 推算的日期）：必须晚于今天且不超过 maxDays
+pub fn check_expiries(manifest: &TeamManifest, today: NaiveDate, settings: &ExpirySettings) -> Vec<String> {
+  let mut errors = Vec::new();
+  for p in &manifest.projects {
+    for (i, grant) in p.access.iter().enumerate() {
+      let date = match (&grant.expires_at, grant.expires_in_days) {
+        (Some(date), _) => NaiveDate::parse_from_str(date, "%Y-%m-%d").ok(),
+        (None, Some(days)) => TimeDelta::try_days(days).and_then(|d| today.checked_add_signed(d)),
+        (None, None) => None,
+      };
+      if let Err(e) = date.map_or(Ok(()), |date| expiry::validate(date, today, settings)) {
+        errors.push(format!("project {} access[{}]: {}", p.project, i, e));
+      }
+    }
+  }
+  errors
+}
+
+/// 清单对某个用户在项目上的期望状态
+#[derive(Debug, Clone)]
+pub struct Desired {
+  pub username: String,
+  pub access_level: AccessLevel,
+  pub expires_at: Option<String>,
+  /// expires_at 由 expiresInDays 推算，只在新增时使用
+  pub relative_expiry: bool,
+}
+
+/// 展开项目的授权：按用户名合并多条授权，权限取最高，过期时间取该权限对应的授权
+pub fn desired_by_username(manifest: &TeamManifest, project: &str, today: NaiveDate) -> BTreeMap<String, Desired> {
+  let mut desired: BTreeMap<String, Desired> = BTreeMap::new();
+  let Some(p) = manifest.projects.iter().find(|p| p.project.trim() == project) else {
+    return desired;
+  };
+
+  for grant in &p.access {
+    let usernames: Vec<&String> = match (&grant.team, &grant.user) {
+      (Some(team), _) => manifest.teams.get(team).map(|t| t.members.iter().collect()).unwrap_or_default(),
+      (None, Some(user)) => vec![user],
+      (None, None) => Vec::new(),
+    };
+    let (expires_at, relative_expiry) = match (&grant.expires_at, grant.expires_in_days) {
+      (Some(date), _) => (Some(date.clone()), false),
+      // 超出范围的天数已被 validate 拒绝，这里只做防御
+      (None, Some(days)) => match TimeDelta::try_days(days).and_then(|d| today.checked_add_signed(d)) {
+        Some(date) => (Some(date.format("%Y-%m-%d").to_string()), true),
+        None => continue,
+      },
+      (None, None) => (None, false),
+    };
+
+    for username in usernames {
+      let username = username.trim().to_string();
+      let candidate = Desired {
+        username: username.clone(),
+        access_level: grant.access_level,
+        expires_at: expires_at.clone(),
+        relative_expiry,
+      };
+      match desired.get(&username) {
+        Some(existing) if existing.access_level >= candidate.access_level => {}
+        _ => {
+          desired.insert(username, candidate);
+        }
+      }
+    }
+  }
+  desired
+}
+
+/// 对比期望状态与项目当前直接成员，得到变更列表
+pub fn diff_project(project: &str, desired: &HashMap<u64, Desired>, live: &[ProjectMember], prune: bool) -> Vec<ManifestChange> {
+  let mut changes = Vec::new();
+
+  for (user_id, want) in desired {
+    match live.iter().find(|m| m.id == *user_id) {
+      None => changes.push(ManifestChange {
+        project: project.to_string(),
+        action: ManifestAction::Add,
+        user_id: *user_id,
+        username: want.username.clone(),
+        access_level: Some(want.access_level),
+        expires_at: want.expires_at.clone(),
+        current_access_level: None,
+        current_expires_at: None,
+      }),
+      Some(m) => {
+        let expiry_differs = !want.relative_expiry && m.expires_at != want.expires_at;
+        if m.access_level != want.access_level || expiry_differs {
+          changes.push(ManifestChange {
+            project: project.to_string(),
+            action: ManifestAction::Update,
+            user_id: *user_id,
+            username: want.username.clone(),
+            access_level: Some(want.access_level),
+            expires_at: if want.relative_expiry { m.expires_at.clone() } else { want.expires_at.clone() },
+            current_access_level: Some(m.access_level),
+            current_expires_at: m.expires_at.clone(),
+          });
+        }
+      }
+    }
+  }
+
+  if prune {
+    for m in live.iter().filter(|m| !desired.contains_key(&m.id)) {
+      changes.push(ManifestChange {
+        project: project.to_string(),
+        action: ManifestAction::Remove,
+        user_id: m.id,
+        username: m.username.clone(),
+        access_level: None,
+        expires_at: None,
+        current_access_level: Some(m.access_level),
+        current_expires_at: m.expires_at.clone(),
+      });
+    }
+  }
+
+  changes.sort_by(|a, b| a.username.cmp(&b.username));
+  changes
+}
+
+/// 按 GitLab 当前状态生成执行计划（只读）；过期时间不符合过期时间设置时拒绝生成
+pub async fn plan(pool: &SqlitePool, cfg: &GitLabConfig, manifest: &TeamManifest) -> Result<ManifestPlan> {
+  let validation = validate(manifest);
+  if !validation.valid {
+    bail!("manifest is invalid: {}", validation.errors.join("; "));
+  }
+
+  let today = chrono::Local::now().date_naive();
+  let settings: ExpirySettings = db::get_config_json(pool, expiry::SETTINGS_KEY).await?.unwrap_or_default();
+  let expiry_errors = check_expiries(manifest, today, &settings);
+  if !expiry_errors.is_empty() {
+    bail!("manifest is invalid: {}", expiry_errors.join("; "));
+  }
+  let mut result = ManifestPlan::default();
+  let mut user_ids: HashMap<String, Option<u64>> = HashMap::new();
+
+  for p in &manifest.projects {
+    let project = p.project.trim();
+    let mut desired = HashMap::new();
+    let mut unresolved = false;
+    for (username, want) in desired_by_username(manifest, project, today) {
+      if !user_ids.contains_key(&username) {
+        let id = gitlab::find_user_by_username(cfg, &username).await?.map(|u| u.id);
+        user_ids.insert(username.clone(), id);
+      }
+      match user_ids[&username] {
+        Some(id) => {
+          desired.insert(id, want);
+        }
+        None => {
+          unresolved = true;
+          result.errors.push(format!("project {}: user {} not found", project, username));
+        }
+      }
+    }
+    // 有用户名无法解析时不做清理，避免误删
+    if p.prune && unresolved {
+      result.errors.push(format!("project {}: prune skipped because some users were not found", project));
+    }
+
+    let live = match gitlab::list_all_project_members(cfg, project, false).await {
+      Ok(m) => m,
+      Err(e) => {
+        result.errors.push(format!("project {}: {}", project, e));
+        continue;
+      }
+    };
+    result.changes.extend(diff_project(project, &desired, &live, p.prune && !unresolved));
+  }
+
+  tracing::info!(changes = result.changes.len(), errors = result.errors.len(), "[manifest] plan generated");
+  Ok(result)
+}
+
+/// 执行预览过的计划：先按当前状态重新生成计划，与预览不一致时拒绝执行；
+/// 每个项目先生成快照，新增、移除与权限 / 过期时间变更各自记为批量任务（可撤销、续跑与重试）
+pub async fn apply(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  manifest: &TeamManifest,
+  previewed: &ManifestPlan,
+) -> Result<ManifestApplyResult> {
+  let plan = plan(pool, cfg, manifest).await?;
+  if plan != *previewed {
+    tracing::warn!(
+      previewed = previewed.changes.len(),
+      current = plan.changes.len(),
+      "[manifest] plan drifted since preview"
+    );
+    bail!("manifest or project members changed since the plan was previewed, run plan_manifest again");
+  }
+
+  let mut by_project: BTreeMap<String, Vec<&ManifestChange>> = BTreeMap::new();
+  for change in &plan.changes {
+    by_project.entry(change.project.clone()).or_default().push(change);
+  }
+
+  // 与单独执行的批量操作一样受审批策略约束，权限 / 过期时间变更按加人计算
+  let mut operations = Vec::new();
+  for (project, changes) in &by_project {
+    let items = grant_items(changes, ManifestAction::Add);
+    let updates = grant_items(changes, ManifestAction::Update);
+    if !items.is_empty() || !updates.is_empty() {
+      operations.push(ChangeOperation {
+        kind: BatchJobKind::Add,
+        source_type: MembershipSourceType::Project,
+        project: project.clone(),
+        items,
+        user_ids: Vec::new(),
+        updates,
+        shares: Vec::new(),
+        policy: None,
+      });
+    }
+    let user_ids: Vec<u64> = changes
+      .iter()
+      .filter(|c| c.action == ManifestAction::Remove)
+      .map(|c| c.user_id)
+      .collect();
+    if !user_ids.is_empty() {
+      operations.push(ChangeOperation {
+        kind: BatchJobKind::Remove,
+        source_type: MembershipSourceType::Project,
+        project: project.clone(),
+        items: Vec::new(),
+        user_ids,
+        updates: Vec::new(),
+        shares: Vec::new(),
+        policy: None,
+      });
+    }
+  }
+  crate::require_approval(pool, cfg, operations).await.map_err(anyhow::Error::msg)?;
+
+  let mut added = Vec::new();
+  let mut removed = Vec::new();
+  let mut changed = Vec::new();
+  let mut updated = Vec::new();
+  let mut failed = Vec::new();
+  for (project, changes) in by_project {
+    let snapshot_id = match crate::take_snapshot(pool, cfg, &project, "apply_manifest").await {
+      Ok(id) => id,
+      Err(message) => {
+        failed.extend(changes.into_iter().map(|c| ManifestApplyError {
+          change: c.clone(),
+          message: message.clone(),
+        }));
+        continue;
+      }
+    };
+
+    let items = grant_items(&changes, ManifestAction::Add);
+    if !items.is_empty() {
+      let result = crate::run_batch_add(pool, cfg, &project, snapshot_id, None, &items).await;
+      added.push(ProjectBatchResult {
+        project: project.clone(),
+        result,
+      });
+    }
+
+    let remove_ids: Vec<u64> = changes
+      .iter()
+      .filter(|c| c.action == ManifestAction::Remove)
+      .map(|c| c.user_id)
+      .collect();
+    if !remove_ids.is_empty() {
+      let result = crate::run_batch_remove(pool, cfg, &project, snapshot_id, None, &remove_ids).await;
+      removed.push(ProjectBatchResult {
+        project: project.clone(),
+        result,
+      });
+    }
+
+    let updates = grant_items(&changes, ManifestAction::Update);
+    if !updates.is_empty() {
+      let result = crate::run_batch_update(pool, cfg, &project, snapshot_id, None, &updates).await;
+      for change in changes.into_iter().filter(|c| c.action == ManifestAction::Update) {
+        if result.success_user_ids.contains(&change.user_id) {
+          updated.push(change.clone());
+        } else if let Some(e) = result.failed.iter().find(|e| e.user_id == change.user_id) {
+          failed.push(ManifestApplyError {
+            change: change.clone(),
+            message: e.message.clone(),
+          });
+        }
+      }
+      changed.push(ProjectBatchResult {
+        project: project.clone(),
+        result,
+      });
+    }
+  }
+
+  tracing::info!(
+    added = added.len(),
+    removed = removed.len(),
+    updated = updated.len(),
+    failed = failed.len(),
+    "[manifest] apply completed"
+  );
+  Ok(ManifestApplyResult {
+    plan,
+    added,
+    removed,
+    changed,
+    updated,
+    failed,
+  })
+}
+
+/// 计划中某类变更对应的加人条目（新增 / 权限变更）
+fn grant_items(changes: &[&ManifestChange], action: ManifestAction) -> Vec<BatchAddItem> {
+  changes
+    .iter()
+    .filter(|c| c.action == action)
+    .filter_map(|c| {
+      c.access_level.map(|level| BatchAddItem {
+        user_id: c.user_id,
+        access_level: level,
+        expires_at: c.expires_at.clone(),
+        member_role_id: None,
+      })
+    })
+    .collect()
+}
@@ -1,4 +1,124 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// GitLab 成员权限级别。序列化为数值（与 GitLab API 一致），反序列化同时接受数值与名称（如 30 / "developer"）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccessLevel {
+  NoAccess,
+  MinimalAccess,
+  Guest,
+  Planner,
+  Reporter,
+  Developer,
+  Maintainer,
+  Owner,
+}
+
+impl AccessLevel {
+  pub const ALL: [AccessLevel; 8] = [
+    AccessLevel::NoAccess,
+    AccessLevel::MinimalAccess,
+    AccessLevel::Guest,
+    AccessLevel::Planner,
+    AccessLevel::Reporter,
+    AccessLevel::Developer,
+    AccessLevel::Maintainer,
+    AccessLevel::Owner,
+  ];
+
+  pub fn value(self) -> i64 {
+    match self {
+      AccessLevel::NoAccess => 0,
+      AccessLevel::MinimalAccess => 5,
+      AccessLevel::Guest => 10,
+      AccessLevel::Planner => 15,
+      AccessLevel::Reporter => 20,
+      AccessLevel::Developer => 30,
+      AccessLevel::Maintainer => 40,
+      AccessLevel::Owner => 50,
+    }
+  }
+
+  pub fn from_value(value: i64) -> Option<Self> {
+    Self::ALL.into_iter().find(|l| l.value() == value)
+  }
+
+  /// GitLab 或旧数据中的未知数值取不高于它的最高级别
+  pub fn from_value_lossy(value: i64) -> Self {
+    Self::ALL
+      .into_iter()
+      .rev()
+      .find(|l| l.value() <= value)
+      .unwrap_or(AccessLevel::NoAccess)
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      AccessLevel::NoAccess => "no_access",
+      AccessLevel::MinimalAccess => "minimal_access",
+      AccessLevel::Guest => "guest",
+      AccessLevel::Planner => "planner",
+      AccessLevel::Reporter => "reporter",
+      AccessLevel::Developer => "developer",
+      AccessLevel::Maintainer => "maintainer",
+      AccessLevel::Owner => "owner",
+    }
+  }
+
+  /// 接受名称（不区分大小写，- / _ / 空格 均可省略）或数值字符串
+  pub fn parse(s: &str) -> Option<Self> {
+    let s = s.trim();
+    if let Ok(value) = s.parse::<i64>() {
+      return Self::from_value(value);
+    }
+    let key: String = s
+      .chars()
+      .filter(|c| !matches!(c, '_' | '-' | ' '))
+      .collect::<String>()
+      .to_lowercase();
+    Self::ALL.into_iter().find(|l| l.as_str().replace('_', "") == key)
+  }
+}
+
+impl std::fmt::Display for AccessLevel {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} ({})", self.as_str(), self.value())
+  }
+}
+
+impl Serialize for AccessLevel {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(self.value())
+  }
+}
+
+impl<'de> Deserialize<'de> for AccessLevel {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+      Number(i64),
+      Name(String),
+    }
+    match Raw::deserialize(deserializer)? {
+      Raw::Number(n) => Self::from_value(n).ok_or_else(|| serde::de::Error::custom(format!("invalid access level {}", n))),
+      Raw::Name(s) => Self::parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid access level {:?}", s))),
+    }
+  }
+}
+
+/// GitLab 自定义成员角色（Ultimate），base_access_level 为其基础权限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberRole {
+  pub id: u64,
+  pub name: String,
+  #[serde(default)]
+  pub description: Option<String>,
+  pub base_access_level: AccessLevel,
+  #[serde(default)]
+  pub group_id: Option<u64>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -11,6 +131,99 @@ pub struct ProjectSummary {
   pub last_activity_at: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProjectVisibility {
+  Public,
+  Internal,
+  Private,
+}
+
+impl ProjectVisibility {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ProjectVisibility::Public => "public",
+      ProjectVisibility::Internal => "internal",
+      ProjectVisibility::Private => "private",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProjectOrderBy {
+  Id,
+  Name,
+  Path,
+  CreatedAt,
+  UpdatedAt,
+  LastActivityAt,
+  StarCount,
+}
+
+impl ProjectOrderBy {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ProjectOrderBy::Id => "id",
+      ProjectOrderBy::Name => "name",
+      ProjectOrderBy::Path => "path",
+      ProjectOrderBy::CreatedAt => "created_at",
+      ProjectOrderBy::UpdatedAt => "updated_at",
+      ProjectOrderBy::LastActivityAt => "last_activity_at",
+      ProjectOrderBy::StarCount => "star_count",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortDirection {
+  Asc,
+  Desc,
+}
+
+impl SortDirection {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      SortDirection::Asc => "asc",
+      SortDirection::Desc => "desc",
+    }
+  }
+}
+
+/// 项目搜索条件。group 不为空时在该 GitLab 分组内搜索（/groups/:id/projects），
+/// 此时 membership 不生效（分组接口不支持），可改用 min_access_level
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSearchOptions {
+  #[serde(default)]
+  pub group: Option<String>,
+  /// 分组搜索是否包含子分组，默认 true
+  #[serde(default)]
+  pub include_subgroups: Option<bool>,
+  /// 只返回当前用户是成员的项目
+  #[serde(default)]
+  pub membership: bool,
+  #[serde(default)]
+  pub owned: bool,
+  /// 为空时不过滤归档状态
+  #[serde(default)]
+  pub archived: Option<bool>,
+  #[serde(default)]
+  pub visibility: Option<ProjectVisibility>,
+  /// 当前用户在项目中至少具备的权限
+  #[serde(default)]
+  pub min_access_level: Option<AccessLevel>,
+  #[serde(default)]
+  pub topic: Option<String>,
+  /// 默认 lastActivityAt
+  #[serde(default)]
+  pub order_by: Option<ProjectOrderBy>,
+  /// 默认 desc
+  #[serde(default)]
+  pub sort: Option<SortDirection>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectMember {
@@ -18,9 +231,17 @@ pub struct ProjectMember {
   pub username: String,
   pub name: String,
   pub avatar_url: Option<String>,
-  pub access_level: i64,
+  pub access_level: AccessLevel,
   pub created_at: Option<String>,
   pub expires_at: Option<String>,
+  /// 用户状态：active / blocked / deactivated 等
+  #[serde(default)]
+  pub state: Option<String>,
+  /// 自定义成员角色（实例支持时）
+  #[serde(default)]
+  pub member_role_id: Option<u64>,
+  #[serde(default)]
+  pub member_role_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +255,12 @@ pub struct LocalMemberUpsert {
   pub project_id: Option<u64>,
   #[serde(default)]
   pub project_name: Option<String>,
+  /// 项目 path_with_namespace，为空时取 project_name
+  #[serde(default)]
+  pub project_path: Option<String>,
+  /// 在该项目中看到的权限
+  #[serde(default)]
+  pub access_level: Option<AccessLevel>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +275,58 @@ pub struct LocalMember {
   pub project_id: Option<u64>,
   #[serde(default)]
   pub project_name: Option<String>,
+  /// GitLab 用户状态：active / blocked / deactivated 等，未同步过为空
+  #[serde(default)]
+  pub state: Option<String>,
+  #[serde(default)]
+  pub last_synced_at: Option<String>,
+  /// 同步时从 GitLab 获取（需管理员 token 或用户公开邮箱）
+  #[serde(default)]
+  pub email: Option<String>,
+  /// 最近一次同步时 GitLab 中已不存在该用户
+  #[serde(default)]
+  pub missing: bool,
+  #[serde(default)]
+  pub notes: Option<String>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+}
+
+/// 本地成员查询条件，各条件之间为“且”
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalMemberQuery {
+  /// 全文检索用户名、昵称、邮箱、备注、标签和来源项目，每个词按前缀匹配
+  #[serde(default)]
+  pub text: Option<String>,
+  /// 带有其中任一标签
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// 是该本地分组的有效成员（含嵌套子分组与动态规则命中的成员）
+  #[serde(default)]
+  pub group_id: Option<i64>,
+  /// 不属于任何本地分组（静态成员与动态规则均未命中）
+  #[serde(default)]
+  pub ungrouped: bool,
+  /// GitLab 用户状态（active / blocked / deactivated）；"missing" 表示 GitLab 中已不存在
+  #[serde(default)]
+  pub state: Option<String>,
+  #[serde(default)]
+  pub sort: LocalMemberSort,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalMemberSort {
+  /// 有检索词时按相关度，否则同 Updated
+  #[default]
+  Relevance,
+  Name,
+  Username,
+  /// 最近更新在前
+  Updated,
+  /// 所在分组多的在前
+  GroupCount,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +336,50 @@ pub struct LocalGroup {
   pub name: String,
   pub created_at: String,
   pub members_count: i64,
+  #[serde(default)]
+  pub includes_count: i64,
+  /// 动态分组规则；为空表示静态分组
+  #[serde(default)]
+  pub rule: Option<GroupRule>,
+  /// 已保存的规则无法解析时的错误信息，此时 rule 为空
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rule_error: Option<String>,
+}
+
+/// 动态分组规则：各条件需同时满足，未设置的条件忽略
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupRule {
+  /// 带有其中任一标签
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// 用户名 GLOB 匹配，例如 ext-*
+  #[serde(default)]
+  pub username_pattern: Option<String>,
+  /// 曾在该项目中出现过（项目 ID 或 path_with_namespace）
+  #[serde(default)]
+  pub source_project: Option<String>,
+  /// 是 GitLab 分组成员（分组 ID 或 full_path，含继承成员）
+  #[serde(default)]
+  pub gitlab_group: Option<String>,
+  /// gitlab_group 成员的最低权限，默认 Developer (30)
+  #[serde(default)]
+  pub gitlab_min_access_level: Option<AccessLevel>,
+  /// 命中规则的成员在该分组中的默认权限
+  #[serde(default)]
+  pub access_level: Option<AccessLevel>,
+}
+
+/// 分组成员视图：direct 表示直接加入该分组，via_groups 为经由哪些子分组间接包含
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMember {
+  #[serde(flatten)]
+  pub member: LocalMember,
+  pub access_level: Option<AccessLevel>,
+  pub direct: bool,
+  #[serde(default)]
+  pub via_groups: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,4 +394,1078 @@ pub struct BatchItemError {
 pub struct BatchResult {
   pub success_user_ids: Vec<u64>,
   pub failed: Vec<BatchItemError>,
+  /// 成员已存在（加人）或本就不是成员（移除），GitLab 未做修改
+  #[serde(default)]
+  pub skipped_user_ids: Vec<u64>,
+  /// 执行前自动生成的项目成员快照
+  #[serde(default)]
+  pub snapshot_id: Option<i64>,
+  /// 对应的批量任务，可用于撤销
+  #[serde(default)]
+  pub job_id: Option<i64>,
+}
+
+/// 批量加人的单个条目（每个用户可有不同的权限与过期时间）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchAddItem {
+  pub user_id: u64,
+  pub access_level: AccessLevel,
+  #[serde(default)]
+  pub expires_at: Option<String>,
+  /// 自定义成员角色，其基础权限需与 access_level 一致
+  #[serde(default)]
+  pub member_role_id: Option<u64>,
+}
+
+/// 目标项目选择：显式列表、关键字搜索结果、某个 GitLab namespace 下全部项目，三者取并集
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTargets {
+  #[serde(default)]
+  pub projects: Vec<String>,
+  #[serde(default)]
+  pub keyword: Option<String>,
+  #[serde(default)]
+  pub namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectBatchResult {
+  pub project: String,
+  #[serde(flatten)]
+  pub result: BatchResult,
+}
+
+/// 分组 × 多项目的执行结果：user_ids 为分组展开后的用户，projects 为每个项目的成功/失败明细
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupApplyResult {
+  pub group_id: i64,
+  pub user_ids: Vec<u64>,
+  pub projects: Vec<ProjectBatchResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentUser {
+  pub id: u64,
+  pub username: String,
+  pub is_admin: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupSummary {
+  pub id: u64,
+  pub name: String,
+  pub full_path: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MembershipSourceType {
+  #[default]
+  Project,
+  Group,
+}
+
+impl MembershipSourceType {
+  pub fn is_project(&self) -> bool {
+    *self == MembershipSourceType::Project
+  }
+}
+
+/// 用户在某个项目/GitLab 分组中的直接成员关系
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserMembership {
+  pub source_type: MembershipSourceType,
+  pub source_id: u64,
+  pub source_name: String,
+  pub access_level: AccessLevel,
+}
+
+/// 项目共享给的 GitLab 分组：分组成员按 group_access_level 与自身分组权限中较低者获得项目权限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedGroup {
+  pub group_id: u64,
+  pub group_name: String,
+  pub group_full_path: String,
+  pub group_access_level: AccessLevel,
+  pub expires_at: Option<String>,
+}
+
+/// 用户在 GitLab 中点击 "Request access" 后待处理的访问申请
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessRequest {
+  /// 申请人的用户 ID
+  pub id: u64,
+  pub username: String,
+  pub name: String,
+  pub avatar_url: Option<String>,
+  pub state: Option<String>,
+  pub requested_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MembershipError {
+  pub membership: UserMembership,
+  pub message: String,
+}
+
+/// 扫描成员关系时因接口报错而跳过的项目/分组
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedSource {
+  pub source_type: MembershipSourceType,
+  pub source_id: u64,
+  pub source_name: String,
+  pub message: String,
+}
+
+/// 离职清理预览：scanned=false 表示来自管理员接口，true 表示逐个扫描可管理的项目/分组得到。
+/// truncated 表示翻页达到上限，memberships 可能不完整；skipped 为扫描失败被跳过的来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OffboardPreview {
+  pub user_id: u64,
+  pub scanned: bool,
+  pub truncated: bool,
+  pub skipped: Vec<SkippedSource>,
+  pub memberships: Vec<UserMembership>,
+  pub local_groups: Vec<LocalGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OffboardResult {
+  pub user_id: u64,
+  pub removed: Vec<UserMembership>,
+  /// 执行时已不是成员（GitLab 返回 404），未做修改
+  #[serde(default)]
+  pub unchanged: Vec<UserMembership>,
+  pub failed: Vec<MembershipError>,
+  pub local_groups_removed: u64,
+}
+
+/// 操作历史，detail 为 JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+  pub id: i64,
+  pub action: String,
+  pub target: String,
+  pub detail: serde_json::Value,
+  pub created_at: String,
+}
+
+/// 复制项目成员的选项：min_access_level / direct_only 过滤源成员，
+/// access_level / expires_at 不为空时覆盖源成员的权限与过期时间
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyMembersOptions {
+  #[serde(default)]
+  pub min_access_level: Option<AccessLevel>,
+  #[serde(default)]
+  pub direct_only: bool,
+  #[serde(default)]
+  pub access_level: Option<AccessLevel>,
+  #[serde(default)]
+  pub expires_at: Option<ExpiryInput>,
+}
+
+/// 复制计划中的一条：existing_access_level 不为空表示目标项目已有该成员（直接成员，
+/// 或继承权限不低于计划权限），执行时跳过
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyPlanItem {
+  pub user_id: u64,
+  pub username: String,
+  pub name: String,
+  pub access_level: AccessLevel,
+  pub expires_at: Option<String>,
+  pub existing_access_level: Option<AccessLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyMembersPreview {
+  pub source: String,
+  pub target: String,
+  pub items: Vec<CopyPlanItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonCell {
+  pub access_level: AccessLevel,
+  pub expires_at: Option<String>,
+}
+
+/// 对比矩阵的一行：cells 与 MembershipComparison.projects 一一对应，None 表示该项目中没有此用户
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonRow {
+  pub user_id: u64,
+  pub username: String,
+  pub name: String,
+  pub cells: Vec<Option<ComparisonCell>>,
+  pub missing: bool,
+  pub level_mismatch: bool,
+  pub expiry_mismatch: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MembershipComparison {
+  pub projects: Vec<String>,
+  pub rows: Vec<ComparisonRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitLabUser {
+  pub id: u64,
+  pub username: String,
+  pub name: String,
+  pub avatar_url: Option<String>,
+  pub state: Option<String>,
+  pub last_activity_on: Option<String>,
+  pub email: Option<String>,
+}
+
+/// 权限审计参数：expiring_within_days 天内过期、privileged_min_level 及以上权限、
+/// inactive_days 天内无活动（仅管理员 token 可查活动时间）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessReviewOptions {
+  #[serde(default = "default_expiring_within_days")]
+  pub expiring_within_days: i64,
+  #[serde(default = "default_privileged_min_level")]
+  pub privileged_min_level: AccessLevel,
+  #[serde(default = "default_inactive_days")]
+  pub inactive_days: i64,
+}
+
+fn default_expiring_within_days() -> i64 {
+  30
+}
+
+fn default_privileged_min_level() -> AccessLevel {
+  AccessLevel::Maintainer
+}
+
+fn default_inactive_days() -> i64 {
+  90
+}
+
+impl Default for AccessReviewOptions {
+  fn default() -> Self {
+    Self {
+      expiring_within_days: default_expiring_within_days(),
+      privileged_min_level: default_privileged_min_level(),
+      inactive_days: default_inactive_days(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewEntry {
+  pub user_id: u64,
+  pub username: String,
+  pub name: String,
+  pub access_level: AccessLevel,
+  pub expires_at: Option<String>,
+  pub state: Option<String>,
+  pub last_activity_on: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectAccessReview {
+  pub project: String,
+  pub total_members: usize,
+  pub expiring: Vec<ReviewEntry>,
+  pub privileged: Vec<ReviewEntry>,
+  pub inactive: Vec<ReviewEntry>,
+  pub blocked: Vec<ReviewEntry>,
+}
+
+/// activity_checked=false 表示 token 不是管理员，未检查最近活动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessReviewReport {
+  pub generated_at: String,
+  pub options: AccessReviewOptions,
+  pub activity_checked: bool,
+  pub projects: Vec<ProjectAccessReview>,
+}
+
+/// 过期巡检设置（保存在 config 表，key = "renewal"）。
+/// auto_extend=false 时只通知；为 true 时对 group_ids 分组（展开嵌套后）中的成员自动续期 extend_days 天
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenewalSettings {
+  #[serde(default)]
+  pub enabled: bool,
+  #[serde(default = "default_renewal_window_days")]
+  pub window_days: i64,
+  #[serde(default = "default_renewal_extend_days")]
+  pub extend_days: i64,
+  #[serde(default)]
+  pub auto_extend: bool,
+  #[serde(default)]
+  pub group_ids: Vec<i64>,
+}
+
+fn default_renewal_window_days() -> i64 {
+  7
+}
+
+fn default_renewal_extend_days() -> i64 {
+  30
+}
+
+impl Default for RenewalSettings {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      window_days: default_renewal_window_days(),
+      extend_days: default_renewal_extend_days(),
+      auto_extend: false,
+      group_ids: Vec::new(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedProject {
+  pub project: String,
+  pub name: Option<String>,
+  pub created_at: String,
+}
+
+/// 即将过期的成员；renewable 表示其属于续期分组
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiringMember {
+  pub project: String,
+  pub user_id: u64,
+  pub username: String,
+  pub name: String,
+  pub access_level: AccessLevel,
+  pub expires_at: String,
+  pub renewable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenewalRecord {
+  pub project: String,
+  pub user_id: u64,
+  pub username: String,
+  pub previous_expires_at: String,
+  pub expires_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenewalFailure {
+  pub project: String,
+  pub user_id: u64,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiryScanResult {
+  pub scanned_at: String,
+  pub expiring: Vec<ExpiringMember>,
+  pub renewed: Vec<RenewalRecord>,
+  pub failed: Vec<RenewalFailure>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotMemberSource {
+  /// 项目直接成员
+  Direct,
+  /// 继承自上级 GitLab 分组
+  Inherited,
+}
+
+impl SnapshotMemberSource {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SnapshotMemberSource::Direct => "direct",
+      SnapshotMemberSource::Inherited => "inherited",
+    }
+  }
+
+  pub fn parse(s: &str) -> Self {
+    match s {
+      "direct" => SnapshotMemberSource::Direct,
+      _ => SnapshotMemberSource::Inherited,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSnapshot {
+  pub id: i64,
+  pub project: String,
+  /// 触发快照的操作，例如 batch_remove_members_from_project
+  pub reason: String,
+  pub member_count: i64,
+  pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotMember {
+  pub user_id: u64,
+  pub username: String,
+  pub name: String,
+  pub access_level: AccessLevel,
+  pub expires_at: Option<String>,
+  pub source: SnapshotMemberSource,
+  #[serde(default)]
+  pub member_role_id: Option<u64>,
+}
+
+/// 快照与当前成员中同一用户的权限或过期时间不同
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotChange {
+  pub before: SnapshotMember,
+  pub after: SnapshotMember,
+}
+
+/// 快照与当前成员的差异：removed 为快照中有、现在没有的成员，added 反之
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+  pub snapshot: ProjectSnapshot,
+  pub added: Vec<SnapshotMember>,
+  pub removed: Vec<SnapshotMember>,
+  pub changed: Vec<SnapshotChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchJobKind {
+  Add,
+  Remove,
+  /// 修改已有成员的权限 / 过期时间（团队清单、审批执行的 updates）；撤销时改回执行前的值
+  Update,
+  /// 批准项目的访问申请（items 为批准的权限）；续跑 / 重试时重新批准，撤销时移除批准加入的用户
+  Approve,
+}
+
+impl BatchJobKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      BatchJobKind::Add => "add",
+      BatchJobKind::Remove => "remove",
+      BatchJobKind::Update => "update",
+      BatchJobKind::Approve => "approve",
+    }
+  }
+
+  pub fn parse(s: &str) -> Self {
+    match s {
+      "remove" => BatchJobKind::Remove,
+      "update" => BatchJobKind::Update,
+      "approve" => BatchJobKind::Approve,
+      _ => BatchJobKind::Add,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchJobStatus {
+  Running,
+  Completed,
+  /// 应用退出/崩溃时仍在执行，可继续执行
+  Interrupted,
+  /// 已被撤销
+  Undone,
+}
+
+impl BatchJobStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      BatchJobStatus::Running => "running",
+      BatchJobStatus::Completed => "completed",
+      BatchJobStatus::Interrupted => "interrupted",
+      BatchJobStatus::Undone => "undone",
+    }
+  }
+
+  pub fn parse(s: &str) -> Self {
+    match s {
+      "running" => BatchJobStatus::Running,
+      "interrupted" => BatchJobStatus::Interrupted,
+      "undone" => BatchJobStatus::Undone,
+      _ => BatchJobStatus::Completed,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchItemStatus {
+  /// 尚未执行
+  Pending,
+  /// GitLab 已修改
+  Applied,
+  /// 已是成员（加人）/ 本就不是成员（移除），未修改
+  Skipped,
+  Failed,
+}
+
+impl BatchItemStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      BatchItemStatus::Pending => "pending",
+      BatchItemStatus::Applied => "applied",
+      BatchItemStatus::Skipped => "skipped",
+      BatchItemStatus::Failed => "failed",
+    }
+  }
+
+  pub fn parse(s: &str) -> Self {
+    match s {
+      "pending" => BatchItemStatus::Pending,
+      "applied" => BatchItemStatus::Applied,
+      "skipped" => BatchItemStatus::Skipped,
+      _ => BatchItemStatus::Failed,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchJob {
+  pub id: i64,
+  pub kind: BatchJobKind,
+  pub project: String,
+  pub status: BatchJobStatus,
+  pub snapshot_id: Option<i64>,
+  /// 本任务撤销的是哪个任务
+  pub undo_of: Option<i64>,
+  pub created_at: String,
+  pub finished_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchJobItem {
+  pub user_id: u64,
+  pub access_level: Option<AccessLevel>,
+  pub expires_at: Option<String>,
+  /// 执行前的权限与过期时间（移除任务撤销时按此重新加入，修改任务撤销时按此改回）
+  pub prior_access_level: Option<AccessLevel>,
+  pub prior_expires_at: Option<String>,
+  #[serde(default)]
+  pub member_role_id: Option<u64>,
+  #[serde(default)]
+  pub prior_member_role_id: Option<u64>,
+  pub status: BatchItemStatus,
+  pub message: Option<String>,
+}
+
+/// 批量任务列表项：任务信息 + 各状态条目数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchJobSummary {
+  #[serde(flatten)]
+  pub job: BatchJob,
+  pub total: i64,
+  pub pending: i64,
+  pub applied: i64,
+  pub skipped: i64,
+  pub failed: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchJobDetail {
+  pub job: BatchJob,
+  pub items: Vec<BatchJobItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshLocalMembersResult {
+  pub refreshed: Vec<u64>,
+  /// GitLab 中已不存在（404）的用户
+  pub missing: Vec<u64>,
+  pub failed: Vec<BatchItemError>,
+}
+
+/// 本地成员出现过的项目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberSource {
+  pub project_id: u64,
+  pub project_path: Option<String>,
+  pub access_level: Option<AccessLevel>,
+  pub first_seen: String,
+  pub last_seen: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalTag {
+  pub id: i64,
+  pub name: String,
+  pub created_at: String,
+  pub members_count: i64,
+}
+
+/// 数据库备份 / 恢复结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+  pub path: String,
+  /// 备份中最后一次执行的迁移版本
+  pub schema_version: i64,
+  pub size_bytes: u64,
+  /// 备份中已清空 GitLab token
+  pub secrets_excluded: bool,
+  pub created_at: String,
+}
+
+/// 本地分组共享包，用于在不同操作员之间导入导出分组
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupBundle {
+  /// 格式版本，见 bundle::BUNDLE_VERSION
+  pub version: u32,
+  /// 导出时的 GitLab 实例地址；导入到同一实例时才比对 user_id
+  #[serde(default)]
+  pub instance_url: Option<String>,
+  #[serde(default)]
+  pub exported_at: Option<String>,
+  pub groups: Vec<BundleGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleGroup {
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rule: Option<GroupRule>,
+  /// 静态成员
+  #[serde(default)]
+  pub members: Vec<BundleMember>,
+  /// 嵌套的子分组名称
+  #[serde(default)]
+  pub includes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleMember {
+  pub username: String,
+  /// 导出实例上的用户 ID，仅用于同实例导入时发现改名
+  #[serde(default)]
+  pub user_id: Option<u64>,
+  #[serde(default)]
+  pub name: Option<String>,
+  #[serde(default)]
+  pub access_level: Option<AccessLevel>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleFormat {
+  #[default]
+  Json,
+  Yaml,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleConflictKind {
+  /// 当前实例中找不到该用户名，成员被跳过
+  UserNotFound,
+  /// 同一实例中 user_id 对应的用户已改名，按 user_id 导入
+  Renamed,
+  /// 同一实例中用户名已属于另一个 user_id，成员被跳过
+  IdMismatch,
+  /// 成员已在本地分组中且权限不同，保留本地权限
+  LevelDiffers,
+  /// 同名本地分组的规则不同，保留本地规则
+  RuleDiffers,
+  /// 嵌套的子分组不存在或会形成循环
+  IncludeFailed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleConflict {
+  pub kind: BundleConflictKind,
+  #[serde(default)]
+  pub group: Option<String>,
+  #[serde(default)]
+  pub username: Option<String>,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportResult {
+  pub groups_created: Vec<String>,
+  pub groups_merged: Vec<String>,
+  pub members_added: u64,
+  pub conflicts: Vec<BundleConflict>,
+}
+
+/// 团队成员清单（YAML）：团队 → 成员，项目 → 授权的团队/用户与权限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamManifest {
+  /// 格式版本，见 manifest::MANIFEST_VERSION
+  pub version: u32,
+  #[serde(default)]
+  pub teams: BTreeMap<String, ManifestTeam>,
+  #[serde(default)]
+  pub projects: Vec<ManifestProject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestTeam {
+  /// GitLab 用户名
+  #[serde(default)]
+  pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestProject {
+  /// 项目 ID 或 path_with_namespace
+  pub project: String,
+  /// true 时移除清单之外的直接成员
+  #[serde(default)]
+  pub prune: bool,
+  #[serde(default)]
+  pub access: Vec<ManifestGrant>,
+}
+
+/// 一条授权：team 与 user 二选一
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestGrant {
+  #[serde(default)]
+  pub team: Option<String>,
+  #[serde(default)]
+  pub user: Option<String>,
+  pub access_level: AccessLevel,
+  /// 固定过期日期 YYYY-MM-DD
+  #[serde(default)]
+  pub expires_at: Option<String>,
+  /// 加入后 N 天过期，只在新增成员时设置，不会修改已有成员的过期时间
+  #[serde(default)]
+  pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestValidation {
+  pub valid: bool,
+  pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ManifestAction {
+  Add,
+  Update,
+  Remove,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestChange {
+  pub project: String,
+  pub action: ManifestAction,
+  pub user_id: u64,
+  pub username: String,
+  /// 目标权限（移除时为空）
+  #[serde(default)]
+  pub access_level: Option<AccessLevel>,
+  #[serde(default)]
+  pub expires_at: Option<String>,
+  #[serde(default)]
+  pub current_access_level: Option<AccessLevel>,
+  #[serde(default)]
+  pub current_expires_at: Option<String>,
+}
+
+/// 清单与 GitLab 当前状态的差异
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestPlan {
+  pub changes: Vec<ManifestChange>,
+  /// 无法解析的用户名、无法读取的项目等
+  pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestApplyResult {
+  pub plan: ManifestPlan,
+  /// 每个项目的加人批量任务结果
+  pub added: Vec<ProjectBatchResult>,
+  /// 每个项目的移除批量任务结果（prune）
+  pub removed: Vec<ProjectBatchResult>,
+  /// 每个项目的权限 / 过期时间修改批量任务结果
+  #[serde(default)]
+  pub changed: Vec<ProjectBatchResult>,
+  pub updated: Vec<ManifestChange>,
+  pub failed: Vec<ManifestApplyError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestApplyError {
+  pub change: ManifestChange,
+  pub message: String,
+}
+
+/// 审批策略：启用后超过阈值的变更需要另一位操作员审批后才能执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalPolicy {
+  #[serde(default)]
+  pub enabled: bool,
+  /// 授予该权限及以上（默认 Maintainer）需要审批
+  #[serde(default = "default_approval_min_access_level")]
+  pub min_access_level: AccessLevel,
+  /// 一次变更涉及的用户数超过该值需要审批
+  #[serde(default = "default_approval_max_users")]
+  pub max_users: usize,
+  /// 这些项目（ID 或 path_with_namespace）的任何成员变更都需要审批
+  #[serde(default)]
+  pub protected_projects: Vec<String>,
+}
+
+fn default_approval_min_access_level() -> AccessLevel {
+  AccessLevel::Maintainer
+}
+
+fn default_approval_max_users() -> usize {
+  20
+}
+
+impl Default for ApprovalPolicy {
+  fn default() -> Self {
+    Self {
+      enabled: false,
+      min_access_level: default_approval_min_access_level(),
+      max_users: default_approval_max_users(),
+      protected_projects: Vec::new(),
+    }
+  }
+}
+
+/// 变更请求中的一个批量操作：加人使用 items，移除使用 user_ids；
+/// updates 为已有成员的权限 / 过期时间变更（团队清单），shares 为把项目共享给 GitLab 分组，审批时都按加人计算；
+/// kind 为 remove 时 shares 为取消共享（group_access 不使用）。
+/// source_type 为 group 时 project 是分组 ID 或 full_path（批准分组的访问申请、离职清理）。
+/// policy 不为空时该操作是修改审批策略本身，其余字段不使用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeOperation {
+  pub kind: BatchJobKind,
+  #[serde(default, skip_serializing_if = "MembershipSourceType::is_project")]
+  pub source_type: MembershipSourceType,
+  pub project: String,
+  #[serde(default)]
+  pub items: Vec<BatchAddItem>,
+  #[serde(default)]
+  pub user_ids: Vec<u64>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub updates: Vec<BatchAddItem>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub shares: Vec<GroupShareItem>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub policy: Option<ApprovalPolicy>,
+}
+
+/// 变更请求中的项目共享：分组成员按 group_access 获得项目权限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupShareItem {
+  pub group_id: u64,
+  pub group_access: AccessLevel,
+  #[serde(default)]
+  pub expires_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeRequestStatus {
+  Pending,
+  Approved,
+  Rejected,
+  Executed,
+}
+
+impl ChangeRequestStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ChangeRequestStatus::Pending => "pending",
+      ChangeRequestStatus::Approved => "approved",
+      ChangeRequestStatus::Rejected => "rejected",
+      ChangeRequestStatus::Executed => "executed",
+    }
+  }
+
+  pub fn parse(s: &str) -> Self {
+    match s {
+      "approved" => ChangeRequestStatus::Approved,
+      "rejected" => ChangeRequestStatus::Rejected,
+      "executed" => ChangeRequestStatus::Executed,
+      _ => ChangeRequestStatus::Pending,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeRequest {
+  pub id: i64,
+  pub uid: String,
+  pub status: ChangeRequestStatus,
+  pub created_at: String,
+  /// 发起人的 GitLab 用户名
+  pub created_by: String,
+  /// 触发审批的原因
+  pub reasons: Vec<String>,
+  pub operations: Vec<ChangeOperation>,
+  pub approved_by: Option<String>,
+  pub approved_at: Option<String>,
+  pub executed_at: Option<String>,
+  /// 执行结果（每个操作一条）
+  #[serde(default)]
+  pub result: Option<Vec<ProjectBatchResult>>,
+}
+
+/// 导出的变更请求文件，signature 为 HMAC-SHA256（十六进制），密钥为团队共享的审批密钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeRequestFile {
+  pub version: u32,
+  pub uid: String,
+  pub created_at: String,
+  pub created_by: String,
+  pub reasons: Vec<String>,
+  pub operations: Vec<ChangeOperation>,
+  pub signature: String,
+}
+
+/// 策略规则类型；pattern 的含义：ProtectedUser 为用户名，其余为项目路径
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PolicyRuleKind {
+  /// 匹配的项目最多授予 value 级权限
+  MaxAccessLevel,
+  /// 在匹配的项目加人必须设置过期时间
+  RequireExpiry,
+  /// 匹配的用户不能被移除（如机器人、Owner）
+  ProtectedUser,
+  /// 禁止修改匹配的项目
+  ForbiddenNamespace,
+  /// 对匹配项目的单次批量操作最多 value 个用户
+  MaxBatchSize,
+}
+
+impl PolicyRuleKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      PolicyRuleKind::MaxAccessLevel => "maxAccessLevel",
+      PolicyRuleKind::RequireExpiry => "requireExpiry",
+      PolicyRuleKind::ProtectedUser => "protectedUser",
+      PolicyRuleKind::ForbiddenNamespace => "forbiddenNamespace",
+      PolicyRuleKind::MaxBatchSize => "maxBatchSize",
+    }
+  }
+
+  pub fn parse(s: &str) -> Option<Self> {
+    match s {
+      "maxAccessLevel" => Some(PolicyRuleKind::MaxAccessLevel),
+      "requireExpiry" => Some(PolicyRuleKind::RequireExpiry),
+      "protectedUser" => Some(PolicyRuleKind::ProtectedUser),
+      "forbiddenNamespace" => Some(PolicyRuleKind::ForbiddenNamespace),
+      "maxBatchSize" => Some(PolicyRuleKind::MaxBatchSize),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyRule {
+  pub id: i64,
+  pub kind: PolicyRuleKind,
+  pub pattern: String,
+  pub value: Option<i64>,
+  pub enabled: bool,
+  pub description: Option<String>,
+  pub created_at: String,
+  pub updated_at: String,
+}
+
+/// 新建 / 修改策略规则的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyRuleInput {
+  pub kind: PolicyRuleKind,
+  pub pattern: String,
+  #[serde(default)]
+  pub value: Option<i64>,
+  #[serde(default = "default_true")]
+  pub enabled: bool,
+  #[serde(default)]
+  pub description: Option<String>,
+}
+
+fn default_true() -> bool {
+  true
+}
+
+/// 过期时间输入：YYYY-MM-DD，或相对形式 +30d / +2w / +3m / +1y、end-of-month / end-of-quarter / end-of-year。
+/// 反序列化时校验格式，执行前再解析为具体日期并校验范围（见 expiry.rs）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ExpiryInput {
+  Date(chrono::NaiveDate),
+  Days(u32),
+  Weeks(u32),
+  Months(u32),
+  Years(u32),
+  EndOfMonth,
+  EndOfQuarter,
+  EndOfYear,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpirySettings {
+  /// 过期时间最多在今天之后多少天，为空表示不限制
+  #[serde(default)]
+  pub max_days: Option<i64>,
 }
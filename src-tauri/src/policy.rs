@@ -0,0 +1,282 @@
+use crate::db;
+use crate::gitlab::{self, GitLabConfig};
+use crate::models::{
+  AccessLevel, BatchItemStatus, BatchJobItem, BatchJobKind, MembershipSourceType, PolicyRule,
+  PolicyRuleInput, PolicyRuleKind,
+};
+use anyhow::{anyhow, bail, Result};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// 保存前校验规则参数
+pub fn validate(input: &PolicyRuleInput) -> Result<()> {
+  if input.pattern.trim().is_empty() {
+    bail!("pattern must not be empty");
+  }
+  match input.kind {
+    PolicyRuleKind::MaxAccessLevel if input.value.and_then(AccessLevel::from_value).is_none() => {
+      bail!("maxAccessLevel requires value to be a valid access level")
+    }
+    PolicyRuleKind::MaxBatchSize if input.value.is_none_or(|v| v < 1) => bail!("maxBatchSize requires value >= 1"),
+    _ => Ok(()),
+  }
+}
+
+/// 通配符匹配（* 任意个字符，? 单个字符），不区分大小写
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+  let p: Vec<char> = pattern.trim().to_lowercase().chars().collect();
+  let t: Vec<char> = text.trim().to_lowercase().chars().collect();
+  let (mut pi, mut ti) = (0, 0);
+  let mut star: Option<(usize, usize)> = None;
+  while ti < t.len() {
+    if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+      pi += 1;
+      ti += 1;
+    } else if pi < p.len() && p[pi] == '*' {
+      star = Some((pi, ti));
+      pi += 1;
+    } else if let Some((sp, st)) = star {
+      pi = sp + 1;
+      ti = st + 1;
+      star = Some((sp, st + 1));
+    } else {
+      return false;
+    }
+  }
+  p[pi..].iter().all(|c| *c == '*')
+}
+
+/// 项目类规则：pattern 匹配项目路径本身，或是它的上级 namespace
+fn matches_project(pattern: &str, path: &str) -> bool {
+  let pattern = pattern.trim().trim_end_matches('/');
+  glob_match(pattern, path) || glob_match(&format!("{}/*", pattern), path)
+}
+
+/// 按规则检查一个项目上的一批条目，返回被拦截的 user_id → 原因（每个用户只保留第一条原因）。
+/// 移除时 usernames 中缺少的用户无法做受保护用户检查，按拦截处理
+pub fn check(
+  rules: &[PolicyRule],
+  kind: BatchJobKind,
+  project_path: &str,
+  items: &[BatchJobItem],
+  usernames: &HashMap<u64, String>,
+) -> HashMap<u64, String> {
+  let mut blocked: HashMap<u64, String> = HashMap::new();
+
+  for rule in rules.iter().filter(|r| r.enabled) {
+    let pattern = rule.pattern.as_str();
+    match rule.kind {
+      PolicyRuleKind::ForbiddenNamespace if matches_project(pattern, project_path) => {
+        for item in items {
+          blocked
+            .entry(item.user_id)
+            .or_insert_with(|| format!("project {} is in forbidden namespace {}", project_path, pattern));
+        }
+      }
+      PolicyRuleKind::MaxBatchSize if matches_project(pattern, project_path) => {
+        let limit = rule.value.unwrap_or(i64::MAX);
+        if items.len() as i64 > limit {
+          for item in items {
+            blocked
+              .entry(item.user_id)
+              .or_insert_with(|| format!("batch of {} users exceeds limit {} for {}", items.len(), limit, pattern));
+          }
+        }
+      }
+      PolicyRuleKind::MaxAccessLevel if kind == BatchJobKind::Add && matches_project(pattern, project_path) => {
+        let max = rule.value.unwrap_or(i64::MAX);
+        for item in items {
+          if let Some(level) = item.access_level.filter(|l| l.value() > max) {
+            blocked
+              .entry(item.user_id)
+              .or_insert_with(|| format!("access level {} exceeds maximum {} for {}", level, max, pattern));
+          }
+        }
+      }
+      PolicyRuleKind::RequireExpiry if kind == BatchJobKind::Add && matches_project(pattern, project_path) => {
+        for item in items.iter().filter(|i| i.expires_at.as_deref().is_none_or(|e| e.trim().is_empty())) {
+          blocked
+            .entry(item.user_id)
+            .or_insert_with(|| format!("expiry date is required for {}", pattern));
+        }
+      }
+      PolicyRuleKind::ProtectedUser if kind == BatchJobKind::Remove => {
+        for item in items {
+          let reason = match usernames.get(&item.user_id) {
+            Some(username) if glob_match(pattern, username) => format!("user {} is protected by {}", username, pattern),
+            Some(_) => continue,
+            None => format!("user {} is unknown, cannot check protected users", item.user_id),
+          };
+          blocked.entry(item.user_id).or_insert(reason);
+        }
+      }
+      _ => {}
+    }
+  }
+  blocked
+}
+
+/// 加载启用的规则并检查；项目路径与用户名按需解析（用户名先查本地成员，再查 GitLab）。
+/// 检查本身出错时拦截全部条目
+pub async fn check_items(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  kind: BatchJobKind,
+  project: &str,
+  items: &[BatchJobItem],
+) -> HashMap<u64, String> {
+  check_source_items(pool, cfg, kind, MembershipSourceType::Project, project, items).await
+}
+
+/// GitLab 分组成员的检查（如离职清理），项目类规则按分组 full_path 匹配
+pub async fn check_group_items(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  kind: BatchJobKind,
+  group: &str,
+  items: &[BatchJobItem],
+) -> HashMap<u64, String> {
+  check_source_items(pool, cfg, kind, MembershipSourceType::Group, group, items).await
+}
+
+async fn check_source_items(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  kind: BatchJobKind,
+  source_type: MembershipSourceType,
+  source: &str,
+  items: &[BatchJobItem],
+) -> HashMap<u64, String> {
+  match try_check_items(pool, cfg, kind, source_type, source, items).await {
+    Ok(blocked) => blocked,
+    Err(e) => {
+      tracing::error!(source = %source, error = %e, "[policy] policy check failed");
+      items
+        .iter()
+        .map(|i| (i.user_id, format!("policy check failed: {}", e)))
+        .collect()
+    }
+  }
+}
+
+async fn try_check_items(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  kind: BatchJobKind,
+  source_type: MembershipSourceType,
+  source: &str,
+  items: &[BatchJobItem],
+) -> Result<HashMap<u64, String>> {
+  let rules: Vec<PolicyRule> = db::list_policy_rules(pool).await?.into_iter().filter(|r| r.enabled).collect();
+  if rules.is_empty() || items.is_empty() {
+    return Ok(HashMap::new());
+  }
+
+  let project_path = match source_type {
+    MembershipSourceType::Project => resolve_project_path(cfg, source).await?,
+    MembershipSourceType::Group => resolve_group_path(cfg, source).await?,
+  };
+
+  let mut usernames = HashMap::new();
+  if kind == BatchJobKind::Remove && rules.iter().any(|r| r.kind == PolicyRuleKind::ProtectedUser) {
+    let user_ids: Vec<u64> = items.iter().map(|i| i.user_id).collect();
+    usernames = db::get_local_usernames(pool, &user_ids).await?;
+    let unknown: Vec<u64> = user_ids.into_iter().filter(|id| !usernames.contains_key(id)).collect();
+    for user_id in unknown {
+      if let Some(user) = gitlab::get_user(cfg, user_id).await? {
+        usernames.insert(user_id, user.username);
+      }
+    }
+  }
+
+  let blocked = check(&rules, kind, &project_path, items, &usernames);
+  if !blocked.is_empty() {
+    tracing::warn!(project = %project_path, kind = kind.as_str(), blocked = blocked.len(), "[policy] items blocked");
+  }
+  Ok(blocked)
+}
+
+/// 项目 ID 需查询 GitLab 得到 path_with_namespace，路径直接使用
+async fn resolve_project_path(cfg: &GitLabConfig, project: &str) -> Result<String> {
+  if project.chars().all(|c| c.is_ascii_digit()) {
+    gitlab::get_project(cfg, project)
+      .await?
+      .map(|p| p.path_with_namespace)
+      .ok_or_else(|| anyhow!("project {} not found", project))
+  } else {
+    Ok(project.trim().to_string())
+  }
+}
+
+/// 分组 ID 需查询 GitLab 得到 full_path，路径直接使用
+async fn resolve_group_path(cfg: &GitLabConfig, group: &str) -> Result<String> {
+  if group.chars().all(|c| c.is_ascii_digit()) {
+    gitlab::get_group(cfg, group)
+      .await?
+      .map(|g| g.full_path)
+      .ok_or_else(|| anyhow!("group {} not found", group))
+  } else {
+    Ok(group.trim().to_string())
+  }
+}
+
+/// 项目共享给 GitLab 分组：共享按加人检查（禁止的 namespace、最高权限、必须过期），
+/// 取消共享只检查禁止的 namespace。返回拦截原因，检查出错时同样拦截
+pub async fn check_share(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  kind: BatchJobKind,
+  project: &str,
+  group_id: u64,
+  group_access: Option<AccessLevel>,
+  expires_at: Option<&str>,
+) -> Option<String> {
+  match try_check_share(pool, cfg, kind, project, group_id, group_access, expires_at).await {
+    Ok(reason) => reason,
+    Err(e) => {
+      tracing::error!(project = %project, error = %e, "[policy] share policy check failed");
+      Some(format!("policy check failed: {}", e))
+    }
+  }
+}
+
+async fn try_check_share(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  kind: BatchJobKind,
+  project: &str,
+  group_id: u64,
+  group_access: Option<AccessLevel>,
+  expires_at: Option<&str>,
+) -> Result<Option<String>> {
+  let rules: Vec<PolicyRule> = db::list_policy_rules(pool)
+    .await?
+    .into_iter()
+    .filter(|r| r.enabled)
+    .filter(|r| match kind {
+      BatchJobKind::Add | BatchJobKind::Update | BatchJobKind::Approve => r.kind != PolicyRuleKind::ProtectedUser,
+      BatchJobKind::Remove => r.kind == PolicyRuleKind::ForbiddenNamespace,
+    })
+    .collect();
+  if rules.is_empty() {
+    return Ok(None);
+  }
+
+  let project_path = resolve_project_path(cfg, project).await?;
+  let item = BatchJobItem {
+    user_id: group_id,
+    access_level: group_access,
+    expires_at: expires_at.map(str::to_string),
+    prior_access_level: None,
+    prior_expires_at: None,
+    member_role_id: None,
+    prior_member_role_id: None,
+    status: BatchItemStatus::Pending,
+    message: None,
+  };
+  let reason = check(&rules, kind, &project_path, &[item], &HashMap::new()).remove(&group_id);
+  if let Some(reason) = &reason {
+    tracing::warn!(project = %project_path, group_id = group_id, reason = %reason, "[policy] share blocked");
+  }
+  Ok(reason)
+}
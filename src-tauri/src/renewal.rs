@@ -0,0 +1,259 @@
+use crate::gitlab::{self, GitLabConfig};
+use crate::models::{
+  ApprovalPolicy, BatchAddItem, BatchJobKind, ChangeOperation, ExpiringMember, ExpiryScanResult,
+  ExpirySettings, MembershipSourceType, ProjectMember, RenewalFailure, RenewalRecord,
+  RenewalSettings,
+};
+use crate::{approval, db, expiry, policy, smart_group};
+use crate::AppState;
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDate, TimeDelta};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use tauri::{Emitter, Manager};
+
+/// config 表中的 key
+pub const SETTINGS_KEY: &str = "renewal";
+pub const LAST_SCAN_KEY: &str = "renewal_last_scan";
+
+/// 巡检完成后推送给前端的事件
+pub const SCAN_EVENT: &str = "expiry-scan";
+
+/// 调度器检查间隔；实际巡检按 SCAN_INTERVAL 每天一次
+const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const SCAN_INTERVAL_HOURS: i64 = 24;
+
+/// window_days / extend_days 的上限（约 10 年）
+pub const MAX_DAYS: i64 = 3650;
+
+fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+  TimeDelta::try_days(days).and_then(|d| date.checked_add_signed(d))
+}
+
+/// 扫描所有纳入巡检的项目，找出 window_days 天内过期的直接成员；
+/// auto_extend=true 时为续期分组中的成员把过期时间顺延 extend_days 天，并写入操作历史。
+/// 续期与手动修改一样校验过期时间上限、按策略规则检查；需要审批的项目不自动续期，记为失败
+pub async fn run_expiry_scan(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  settings: &RenewalSettings,
+  auto_extend: bool,
+) -> Result<ExpiryScanResult> {
+  let today = chrono::Local::now().date_naive();
+  let window_end = add_days(today, settings.window_days)
+    .ok_or_else(|| anyhow!("windowDays {} is out of range", settings.window_days))?;
+
+  let expiry_settings: ExpirySettings = db::get_config_json(pool, expiry::SETTINGS_KEY).await?.unwrap_or_default();
+  let approval_policy: ApprovalPolicy = db::get_config_json(pool, approval::POLICY_KEY).await?.unwrap_or_default();
+
+  let mut renewable_ids: HashSet<u64> = HashSet::new();
+  for group_id in &settings.group_ids {
+    for m in smart_group::resolve_group_members(pool, Some(cfg), *group_id, true).await? {
+      renewable_ids.insert(m.member.user_id);
+    }
+  }
+
+  let mut result = ExpiryScanResult {
+    scanned_at: chrono::Utc::now().to_rfc3339(),
+    expiring: Vec::new(),
+    renewed: Vec::new(),
+    failed: Vec::new(),
+  };
+
+  for tracked in db::list_tracked_projects(pool).await? {
+    // 只有直接成员可以在项目上修改过期时间
+    let members = match gitlab::list_all_project_members(cfg, &tracked.project, false).await {
+      Ok(m) => m,
+      Err(e) => {
+        tracing::warn!(project = %tracked.project, error = %e, "[renewal] list members failed");
+        result.failed.push(RenewalFailure {
+          project: tracked.project.clone(),
+          user_id: 0,
+          message: e.to_string(),
+        });
+        continue;
+      }
+    };
+
+    let mut renewals = Vec::new();
+    for m in members {
+      let Some(expires_at) = m.expires_at.clone() else {
+        continue;
+      };
+      let Ok(expires) = NaiveDate::parse_from_str(&expires_at, "%Y-%m-%d") else {
+        continue;
+      };
+      if expires < today || expires > window_end {
+        continue;
+      }
+
+      let renewable = renewable_ids.contains(&m.id);
+      result.expiring.push(ExpiringMember {
+        project: tracked.project.clone(),
+        user_id: m.id,
+        username: m.username.clone(),
+        name: m.name.clone(),
+        access_level: m.access_level,
+        expires_at: expires_at.clone(),
+        renewable,
+      });
+
+      if !(auto_extend && renewable) {
+        continue;
+      }
+
+      let Some(next) = add_days(expires.max(today), settings.extend_days) else {
+        result.failed.push(RenewalFailure {
+          project: tracked.project.clone(),
+          user_id: m.id,
+          message: format!("extendDays {} is out of range", settings.extend_days),
+        });
+        continue;
+      };
+      if let Err(e) = expiry::validate(next, today, &expiry_settings) {
+        result.failed.push(RenewalFailure {
+          project: tracked.project.clone(),
+          user_id: m.id,
+          message: e.to_string(),
+        });
+        continue;
+      }
+      renewals.push((m, expires_at, next.format("%Y-%m-%d").to_string()));
+    }
+    if !renewals.is_empty() {
+      extend_members(pool, cfg, &approval_policy, &tracked.project, renewals, &mut result).await;
+    }
+  }
+
+  tracing::info!(
+    expiring = result.expiring.len(),
+    renewed = result.renewed.len(),
+    failed = result.failed.len(),
+    "[renewal] expiry scan completed"
+  );
+  Ok(result)
+}
+
+/// 续期一个项目中的成员 (成员, 原过期时间, 新过期时间)。按审批策略需要审批时整个项目跳过
+/// （后台巡检不自动创建变更请求），否则按策略规则逐个检查后更新
+async fn extend_members(
+  pool: &SqlitePool,
+  cfg: &GitLabConfig,
+  approval_policy: &ApprovalPolicy,
+  project: &str,
+  renewals: Vec<(ProjectMember, String, String)>,
+  result: &mut ExpiryScanResult,
+) {
+  let updates: Vec<BatchAddItem> = renewals
+    .iter()
+    .map(|(m, _, next)| BatchAddItem {
+      user_id: m.id,
+      access_level: m.access_level,
+      expires_at: Some(next.clone()),
+      member_role_id: m.member_role_id,
+    })
+    .collect();
+  let reasons = approval::reasons(
+    cfg,
+    approval_policy,
+    &[ChangeOperation {
+      kind: BatchJobKind::Add,
+      source_type: MembershipSourceType::Project,
+      project: project.to_string(),
+      items: Vec::new(),
+      user_ids: Vec::new(),
+      updates: updates.clone(),
+      shares: Vec::new(),
+      policy: None,
+    }],
+  )
+  .await
+  .unwrap_or_else(|e| vec![format!("approval check failed: {}", e)]);
+  if !reasons.is_empty() {
+    tracing::warn!(project = %project, reasons = ?reasons, "[renewal] approval required, renewals skipped");
+    for (m, _, _) in &renewals {
+      result.failed.push(RenewalFailure {
+        project: project.to_string(),
+        user_id: m.id,
+        message: format!("approval required: {}", reasons.join("; ")),
+      });
+    }
+    return;
+  }
+
+  let blocked = policy::check_items(pool, cfg, BatchJobKind::Add, project, &crate::add_job_items(&updates)).await;
+  for (m, expires_at, next) in renewals {
+    if let Some(reason) = blocked.get(&m.id) {
+      result.failed.push(RenewalFailure {
+        project: project.to_string(),
+        user_id: m.id,
+        message: format!("blocked by policy: {}", reason),
+      });
+      continue;
+    }
+    match gitlab::update_member(cfg, project, m.id, m.access_level, Some(next.clone()), m.member_role_id).await {
+      Ok(_) => {
+        let record = RenewalRecord {
+          project: project.to_string(),
+          user_id: m.id,
+          username: m.username,
+          previous_expires_at: expires_at,
+          expires_at: next,
+        };
+        let detail = serde_json::to_value(&record).unwrap_or_default();
+        if let Err(e) = db::record_history(pool, "renew_member", &format!("project:{}", project), &detail).await {
+          tracing::error!(error = %e, "[renewal] record history failed");
+        }
+        result.renewed.push(record);
+      }
+      Err(e) => {
+        tracing::warn!(project = %project, user_id = m.id, error = %e, "[renewal] extend failed");
+        result.failed.push(RenewalFailure {
+          project: project.to_string(),
+          user_id: m.id,
+          message: e.to_string(),
+        });
+      }
+    }
+  }
+}
+
+/// 启动后台巡检：每小时检查一次，距上次巡检满 24 小时且已启用时执行，结果保存并推送 expiry-scan 事件
+pub fn spawn_scheduler(app: tauri::AppHandle) {
+  tauri::async_runtime::spawn(async move {
+    loop {
+      if let Err(e) = scheduled_tick(&app).await {
+        tracing::warn!(error = %e, "[renewal] scheduled scan failed");
+      }
+      tokio::time::sleep(TICK_INTERVAL).await;
+    }
+  });
+}
+
+async fn scheduled_tick(app: &tauri::AppHandle) -> Result<()> {
+  let state = app.state::<AppState>();
+  let settings: RenewalSettings = db::get_config_json(&state.db, SETTINGS_KEY).await?.unwrap_or_default();
+  if !settings.enabled {
+    return Ok(());
+  }
+
+  let last: Option<ExpiryScanResult> = db::get_config_json(&state.db, LAST_SCAN_KEY).await?;
+  let due = last
+    .and_then(|l| chrono::DateTime::parse_from_rfc3339(&l.scanned_at).ok())
+    .is_none_or(|t| chrono::Utc::now().signed_duration_since(t) >= TimeDelta::hours(SCAN_INTERVAL_HOURS));
+  if !due {
+    return Ok(());
+  }
+
+  let Some(cfg) = state.gitlab.lock().ok().and_then(|g| g.clone()) else {
+    return Ok(());
+  };
+
+  tracing::info!("[renewal] running scheduled expiry scan");
+  let result = run_expiry_scan(&state.db, &cfg, &settings, settings.auto_extend).await?;
+  db::set_config_json(&state.db, LAST_SCAN_KEY, &result).await?;
+  if let Err(e) = app.emit(SCAN_EVENT, &result) {
+    tracing::warn!(error = %e, "[renewal] emit scan event failed");
+  }
+  Ok(())
+}
@@ -0,0 +1,271 @@
+use crate::models::{
+  AccessReviewOptions, AccessReviewReport, ComparisonCell, ComparisonRow, MembershipComparison,
+  ProjectAccessReview, ProjectMember, ReviewEntry,
+};
+use anyhow::{bail, Result};
+use chrono::{NaiveDate, TimeDelta};
+use std::collections::{BTreeMap, HashMap};
+
+/// 审计天数参数的上限（约 100 年）
+pub const MAX_REVIEW_DAYS: i64 = 36500;
+
+/// CSV 字段转义：包含逗号、引号或换行时用双引号包裹
+fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+fn csv_line(fields: &[String]) -> String {
+  fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",")
+}
+
+/// 生成 用户 × 项目 的成员矩阵；members 与 projects 一一对应
+pub fn compare_members(projects: Vec<String>, members: Vec<Vec<ProjectMember>>) -> MembershipComparison {
+  let mut rows: BTreeMap<u64, ComparisonRow> = BTreeMap::new();
+  let count = projects.len();
+
+  for (idx, project_members) in members.into_iter().enumerate() {
+    for m in project_members {
+      let row = rows.entry(m.id).or_insert_with(|| ComparisonRow {
+        user_id: m.id,
+        username: m.username.clone(),
+        name: m.name.clone(),
+        cells: vec![None; count],
+        missing: false,
+        level_mismatch: false,
+        expiry_mismatch: false,
+      });
+      row.cells[idx] = Some(ComparisonCell {
+        access_level: m.access_level,
+        expires_at: m.expires_at,
+      });
+    }
+  }
+
+  let mut rows: Vec<ComparisonRow> = rows
+    .into_values()
+    .map(|mut row| {
+      let present: Vec<&ComparisonCell> = row.cells.iter().flatten().collect();
+      row.missing = present.len() < count;
+      row.level_mismatch = present.windows(2).any(|w| w[0].access_level != w[1].access_level);
+      row.expiry_mismatch = present.windows(2).any(|w| w[0].expires_at != w[1].expires_at);
+      row
+    })
+    .collect();
+  rows.sort_by(|a, b| a.username.cmp(&b.username));
+
+  MembershipComparison { projects, rows }
+}
+
+pub fn comparison_to_csv(comparison: &MembershipComparison) -> String {
+  let mut header = vec!["user_id".to_string(), "username".to_string(), "name".to_string()];
+  for p in &comparison.projects {
+    header.push(format!("{} access_level", p));
+    header.push(format!("{} expires_at", p));
+  }
+  header.extend(["missing", "level_mismatch", "expiry_mismatch"].map(String::from));
+
+  let mut out = csv_line(&header);
+  out.push('\n');
+  for row in &comparison.rows {
+    let mut fields = vec![row.user_id.to_string(), row.username.clone(), row.name.clone()];
+    for cell in &row.cells {
+      match cell {
+        Some(c) => {
+          fields.push(c.access_level.value().to_string());
+          fields.push(c.expires_at.clone().unwrap_or_default());
+        }
+        None => {
+          fields.push(String::new());
+          fields.push(String::new());
+        }
+      }
+    }
+    fields.push(row.missing.to_string());
+    fields.push(row.level_mismatch.to_string());
+    fields.push(row.expiry_mismatch.to_string());
+    out.push_str(&csv_line(&fields));
+    out.push('\n');
+  }
+  out
+}
+
+/// 校验审计参数的天数范围
+pub fn validate_options(options: &AccessReviewOptions) -> Result<()> {
+  if !(0..=MAX_REVIEW_DAYS).contains(&options.expiring_within_days) {
+    bail!("expiringWithinDays must be between 0 and {}", MAX_REVIEW_DAYS);
+  }
+  if !(0..=MAX_REVIEW_DAYS).contains(&options.inactive_days) {
+    bail!("inactiveDays must be between 0 and {}", MAX_REVIEW_DAYS);
+  }
+  Ok(())
+}
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+  NaiveDate::parse_from_str(s.get(..10).unwrap_or(s), "%Y-%m-%d").ok()
+}
+
+/// 对单个项目的成员做权限审计分类。activity 为 user_id -> last_activity_on，
+/// 为 None 时表示未检查活动（非管理员 token）
+pub fn review_project(
+  project: String,
+  members: Vec<ProjectMember>,
+  activity: Option<&HashMap<u64, Option<String>>>,
+  options: &AccessReviewOptions,
+  today: NaiveDate,
+) -> ProjectAccessReview {
+  // 参数已由 validate_options 校验；越界时退化为不限制，不在此处 panic
+  let expiring_before = TimeDelta::try_days(options.expiring_within_days)
+    .and_then(|d| today.checked_add_signed(d))
+    .unwrap_or(NaiveDate::MAX);
+  let inactive_before = TimeDelta::try_days(options.inactive_days)
+    .and_then(|d| today.checked_sub_signed(d))
+    .unwrap_or(NaiveDate::MIN);
+
+  let mut review = ProjectAccessReview {
+    project,
+    total_members: members.len(),
+    expiring: Vec::new(),
+    privileged: Vec::new(),
+    inactive: Vec::new(),
+    blocked: Vec::new(),
+  };
+
+  for m in members {
+    let last_activity_on = activity.and_then(|a| a.get(&m.id).cloned().flatten());
+    let entry = ReviewEntry {
+      user_id: m.id,
+      username: m.username,
+      name: m.name,
+      access_level: m.access_level,
+      expires_at: m.expires_at,
+      state: m.state,
+      last_activity_on,
+    };
+
+    if entry
+      .expires_at
+      .as_deref()
+      .and_then(parse_date)
+      .is_some_and(|d| d >= today && d <= expiring_before)
+    {
+      review.expiring.push(entry.clone());
+    }
+    if entry.access_level >= options.privileged_min_level {
+      review.privileged.push(entry.clone());
+    }
+    // 从未活动（last_activity_on 为空）也视为不活跃
+    if activity.is_some()
+      && entry
+        .last_activity_on
+        .as_deref()
+        .and_then(parse_date)
+        .is_none_or(|d| d < inactive_before)
+    {
+      review.inactive.push(entry.clone());
+    }
+    if entry.state.as_deref().is_some_and(|s| s != "active") {
+      review.blocked.push(entry);
+    }
+  }
+
+  review
+}
+
+fn review_categories(p: &ProjectAccessReview) -> [(&'static str, &Vec<ReviewEntry>); 4] {
+  [
+    ("expiring", &p.expiring),
+    ("privileged", &p.privileged),
+    ("inactive", &p.inactive),
+    ("blocked", &p.blocked),
+  ]
+}
+
+pub fn review_to_csv(report: &AccessReviewReport) -> String {
+  let header = [
+    "project", "category", "user_id", "username", "name", "access_level", "expires_at", "state", "last_activity_on",
+  ]
+  .map(String::from);
+
+  let mut out = csv_line(&header);
+  out.push('\n');
+  for p in &report.projects {
+    for (category, entries) in review_categories(p) {
+      for e in entries {
+        let fields = [
+          p.project.clone(),
+          category.to_string(),
+          e.user_id.to_string(),
+          e.username.clone(),
+          e.name.clone(),
+          e.access_level.value().to_string(),
+          e.expires_at.clone().unwrap_or_default(),
+          e.state.clone().unwrap_or_default(),
+          e.last_activity_on.clone().unwrap_or_default(),
+        ];
+        out.push_str(&csv_line(&fields));
+        out.push('\n');
+      }
+    }
+  }
+  out
+}
+
+fn html_escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+/// 生成可直接打开/打印的独立 HTML 报表
+pub fn review_to_html(report: &AccessReviewReport) -> String {
+  let mut out = String::new();
+  out.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Access Review</title>");
+  out.push_str("<style>body{font-family:sans-serif;margin:24px}table{border-collapse:collapse;margin-bottom:16px}");
+  out.push_str("th,td{border:1px solid #ccc;padding:4px 8px;font-size:13px;text-align:left}th{background:#f4f4f4}</style>");
+  out.push_str("</head><body>\n");
+  out.push_str(&format!(
+    "<h1>Access Review</h1>\n<p>Generated at {} · expiring within {} days · privileged &ge; {} · inactive &gt; {} days{}</p>\n",
+    html_escape(&report.generated_at),
+    report.options.expiring_within_days,
+    report.options.privileged_min_level,
+    report.options.inactive_days,
+    if report.activity_checked { "" } else { " (activity not checked: admin token required)" },
+  ));
+
+  for p in &report.projects {
+    out.push_str(&format!(
+      "<h2>{} ({} members)</h2>\n",
+      html_escape(&p.project),
+      p.total_members
+    ));
+    for (category, entries) in review_categories(p) {
+      out.push_str(&format!("<h3>{} ({})</h3>\n", category, entries.len()));
+      if entries.is_empty() {
+        continue;
+      }
+      out.push_str("<table><tr><th>User ID</th><th>Username</th><th>Name</th><th>Access level</th><th>Expires at</th><th>State</th><th>Last activity</th></tr>\n");
+      for e in entries {
+        out.push_str(&format!(
+          "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+          e.user_id,
+          html_escape(&e.username),
+          html_escape(&e.name),
+          e.access_level,
+          html_escape(e.expires_at.as_deref().unwrap_or("")),
+          html_escape(e.state.as_deref().unwrap_or("")),
+          html_escape(e.last_activity_on.as_deref().unwrap_or("")),
+        ));
+      }
+      out.push_str("</table>\n");
+    }
+  }
+
+  out.push_str("</body></html>\n");
+  out
+}
@@ -0,0 +1,105 @@
+use crate::db;
+use crate::gitlab::{self, GitLabConfig};
+use crate::models::{AccessLevel, GroupMember, GroupRule, LocalGroup, LocalMember};
+use anyhow::{anyhow, bail, Result};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+
+/// gitlab_group 条件未指定最低权限时使用 Developer
+const DEFAULT_MIN_ACCESS_LEVEL: AccessLevel = AccessLevel::Developer;
+
+/// 保存前校验规则：至少有一个条件，权限不能为 no_access
+pub fn validate_rule(rule: &GroupRule) -> Result<()> {
+  let non_empty = |v: &Option<String>| v.as_deref().is_some_and(|s| !s.trim().is_empty());
+  if rule.tags.iter().all(|t| t.trim().is_empty())
+    && !non_empty(&rule.username_pattern)
+    && !non_empty(&rule.source_project)
+    && !non_empty(&rule.gitlab_group)
+  {
+    bail!("rule must have at least one condition");
+  }
+  if rule.access_level == Some(AccessLevel::NoAccess) {
+    bail!("rule accessLevel must not be {}", AccessLevel::NoAccess);
+  }
+  if rule.gitlab_min_access_level.is_some() && !non_empty(&rule.gitlab_group) {
+    bail!("gitlabMinAccessLevel requires gitlabGroup");
+  }
+  Ok(())
+}
+
+/// 动态分组的 members_count 改为静态成员与规则命中成员合并后的人数；
+/// 规则无法计算（如缺少 GitLab 配置）时保留静态成员数
+pub async fn fill_members_count(pool: &SqlitePool, cfg: Option<&GitLabConfig>, groups: &mut [LocalGroup]) {
+  for group in groups.iter_mut().filter(|g| g.rule.is_some()) {
+    match resolve_group_members(pool, cfg, group.id, false).await {
+      Ok(members) => group.members_count = members.len() as i64,
+      Err(e) => tracing::warn!(group_id = group.id, error = %e, "count smart group members failed"),
+    }
+  }
+}
+
+/// 属于任一本地分组（静态成员或规则命中）的用户，供成员库的"未分组"筛选使用
+pub async fn grouped_user_ids(pool: &SqlitePool, cfg: Option<&GitLabConfig>) -> Result<Vec<u64>> {
+  let mut ids = HashSet::new();
+  for group in db::list_local_groups(pool).await? {
+    for m in resolve_group_members(pool, cfg, group.id, false).await? {
+      ids.insert(m.member.user_id);
+    }
+  }
+  Ok(ids.into_iter().collect())
+}
+
+/// 在查询时计算规则命中的本地成员；带 gitlab_group 条件时需要 GitLab 配置
+pub async fn evaluate_rule(pool: &SqlitePool, cfg: Option<&GitLabConfig>, rule: &GroupRule) -> Result<Vec<LocalMember>> {
+  let mut members = db::list_members_by_rule(pool, rule).await?;
+
+  if let Some(group) = rule.gitlab_group.as_deref().map(str::trim).filter(|g| !g.is_empty()) {
+    let cfg = cfg.ok_or_else(|| anyhow!("GitLab config is required to evaluate gitlab group rule"))?;
+    let min_level = rule.gitlab_min_access_level.unwrap_or(DEFAULT_MIN_ACCESS_LEVEL);
+    let ids: HashSet<u64> = gitlab::list_all_group_members(cfg, group, true)
+      .await?
+      .into_iter()
+      .filter(|m| m.access_level >= min_level)
+      .map(|m| m.id)
+      .collect();
+    members.retain(|m| ids.contains(&m.user_id));
+  }
+
+  Ok(members)
+}
+
+/// 解析分组成员：静态成员与规则命中的成员合并。
+/// effective=true 时同时展开嵌套分组（含其中的动态分组），规则命中的成员权限取分组规则的 access_level
+pub async fn resolve_group_members(
+  pool: &SqlitePool,
+  cfg: Option<&GitLabConfig>,
+  group_id: i64,
+  effective: bool,
+) -> Result<Vec<GroupMember>> {
+  let mut members = db::list_group_members(pool, group_id, effective).await?;
+
+  for (rule_group_id, rule_group_name, rule) in db::list_rule_groups(pool, group_id, effective).await? {
+    let own = rule_group_id == group_id;
+    for member in evaluate_rule(pool, cfg, &rule).await? {
+      match members.iter_mut().find(|m| m.member.user_id == member.user_id) {
+        Some(existing) => {
+          existing.access_level = existing.access_level.max(rule.access_level);
+          if own {
+            existing.direct = true;
+          } else if !existing.via_groups.contains(&rule_group_name) {
+            existing.via_groups.push(rule_group_name.clone());
+          }
+        }
+        None => members.push(GroupMember {
+          member,
+          access_level: rule.access_level,
+          direct: own,
+          via_groups: if own { Vec::new() } else { vec![rule_group_name.clone()] },
+        }),
+      }
+    }
+  }
+
+  members.sort_by(|a, b| a.member.username.cmp(&b.member.username));
+  Ok(members)
+}
@@ -0,0 +1,88 @@
+use crate::db;
+use crate::gitlab::{self, GitLabConfig};
+use crate::models::{
+  ProjectMember, ProjectSnapshot, SnapshotChange, SnapshotDiff, SnapshotMember, SnapshotMemberSource,
+};
+use anyhow::{anyhow, Result};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+
+/// 拉取项目当前的全部成员（含继承成员），按是否为直接成员标记来源。
+/// /members/all 返回的是有效权限（直接与继承中的最高者），直接成员的权限与过期时间取自 /members，
+/// 否则同时继承了更高权限的直接成员会按继承权限被恢复
+pub async fn fetch_live_members(cfg: &GitLabConfig, project: &str) -> Result<Vec<SnapshotMember>> {
+  let direct = gitlab::list_all_project_members(cfg, project, false).await?;
+  let direct_ids: HashSet<u64> = direct.iter().map(|m| m.id).collect();
+  let inherited = gitlab::list_all_project_members(cfg, project, true)
+    .await?
+    .into_iter()
+    .filter(|m| !direct_ids.contains(&m.id));
+
+  let with_source = |source: SnapshotMemberSource| {
+    move |m: ProjectMember| SnapshotMember {
+      source,
+      user_id: m.id,
+      username: m.username,
+      name: m.name,
+      access_level: m.access_level,
+      expires_at: m.expires_at,
+      member_role_id: m.member_role_id,
+    }
+  };
+  Ok(
+    direct
+      .into_iter()
+      .map(with_source(SnapshotMemberSource::Direct))
+      .chain(inherited.map(with_source(SnapshotMemberSource::Inherited)))
+      .collect(),
+  )
+}
+
+/// 快照统一以项目 ID 为 key，同一项目无论传入 ID 还是路径都能查到同一组快照
+pub async fn resolve_project_id(cfg: &GitLabConfig, project: &str) -> Result<String> {
+  let project = project.trim();
+  if project.chars().all(|c| c.is_ascii_digit()) {
+    return Ok(project.to_string());
+  }
+  gitlab::get_project(cfg, project)
+    .await?
+    .map(|p| p.id.to_string())
+    .ok_or_else(|| anyhow!("project {} not found", project))
+}
+
+/// 为项目生成一份成员快照，返回快照 ID
+pub async fn capture(pool: &SqlitePool, cfg: &GitLabConfig, project: &str, reason: &str) -> Result<i64> {
+  let project_id = resolve_project_id(cfg, project).await?;
+  let members = fetch_live_members(cfg, &project_id).await?;
+  db::create_snapshot(pool, &project_id, reason, &members).await
+}
+
+/// 对比快照与当前成员
+pub fn diff(snapshot: ProjectSnapshot, before: Vec<SnapshotMember>, live: Vec<SnapshotMember>) -> SnapshotDiff {
+  let before_ids: HashSet<u64> = before.iter().map(|m| m.user_id).collect();
+  let mut removed = Vec::new();
+  let mut changed = Vec::new();
+
+  for b in before {
+    match live.iter().find(|l| l.user_id == b.user_id) {
+      None => removed.push(b),
+      Some(l) => {
+        if l.access_level != b.access_level || l.expires_at != b.expires_at || l.source != b.source {
+          changed.push(SnapshotChange {
+            before: b,
+            after: l.clone(),
+          });
+        }
+      }
+    }
+  }
+
+  let added = live.into_iter().filter(|l| !before_ids.contains(&l.user_id)).collect();
+
+  SnapshotDiff {
+    snapshot,
+    added,
+    removed,
+    changed,
+  }
+}
@@ -8,6 +8,7 @@ import { ProjectsPage } from "@/pages/ProjectsPage";
 import { MembersPage } from "@/pages/MembersPage";
 import { LocalMembersPage } from "@/pages/LocalMembersPage";
 import { GroupsPage } from "@/pages/GroupsPage";
+import { BatchJobsPage } from "@/pages/BatchJobsPage";
 
 const pageTitles: Record<string, string> = {
   settings: "配置",
@@ -15,6 +16,7 @@ const pageTitles: Record<string, string> = {
   members: "项目成员",
   local: "本地成员",
   groups: "本地分组",
+  jobs: "批量任务",
 };
 
 export default function App() {
@@ -51,6 +53,7 @@ export default function App() {
             {activeTab === "members" && <MembersPage />}
             {activeTab === "local" && <LocalMembersPage />}
             {activeTab === "groups" && <GroupsPage />}
+            {activeTab === "jobs" && <BatchJobsPage />}
           </div>
         </div>
       </main>
@@ -1,10 +1,55 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
+  AccessLevel,
+  AccessLevelName,
+  AccessRequest,
+  AccessReviewOptions,
+  AccessReviewReport,
+  ApprovalPolicy,
+  BackupInfo,
+  BatchJobDetail,
+  BatchJobStatus,
+  BatchJobSummary,
   BatchResult,
+  BundleFormat,
+  BundleImportResult,
+  ChangeRequest,
+  ChangeRequestStatus,
+  CopyMembersOptions,
+  CopyMembersPreview,
+  ExpiryInput,
+  ExpiryScanResult,
+  ExpirySettings,
+  GroupApplyResult,
+  GroupRule,
+  GroupMember,
+  HistoryEntry,
   LocalGroup,
   LocalMember,
+  LocalMemberQuery,
+  LocalTag,
+  ManifestApplyResult,
+  ManifestPlan,
+  ManifestValidation,
+  MemberRole,
+  MemberSource,
+  MembershipComparison,
+  MembershipSourceType,
+  OffboardPreview,
+  OffboardResult,
+  PolicyRule,
+  PolicyRuleInput,
   ProjectMember,
+  ProjectSearchOptions,
+  ProjectSnapshot,
   ProjectSummary,
+  ProjectTargets,
+  RefreshLocalMembersResult,
+  RenewalSettings,
+  SharedGroup,
+  SnapshotDiff,
+  TrackedProject,
+  UserMembership,
 } from "@/lib/types";
 import { logger } from "@/lib/logger";
 
@@ -85,15 +130,27 @@ export async function setGitLabConfig(baseUrl: string, token: string) {
   return loggedInvoke<void>("set_gitlab_config", { baseUrl, token });
 }
 
+export async function backupDatabase(path: string, excludeSecrets = false) {
+  return loggedInvoke<BackupInfo>("backup_database", { path, excludeSecrets });
+}
+
+/** 校验并暂存备份，重启应用后生效 */
+export async function restoreDatabase(path: string) {
+  return loggedInvoke<BackupInfo>("restore_database", { path });
+}
+
+/** options.group 不为空时在该 GitLab 分组内搜索（默认包含子分组），此时 membership 不生效 */
 export async function searchProjects(
   keyword: string,
   page = 1,
-  perPage = 20
+  perPage = 20,
+  options?: ProjectSearchOptions | null
 ): Promise<{ items: ProjectSummary[]; total: number }> {
   const [items, total] = await loggedInvoke<[ProjectSummary[], number]>("search_projects", {
     keyword,
     page,
     per_page: perPage,
+    options: options ?? null,
   });
   return { items, total };
 }
@@ -111,6 +168,28 @@ export async function listProjectMembers(
   return { members, total };
 }
 
+export async function listProjectSharedGroups(project: string) {
+  return loggedInvoke<SharedGroup[]>("list_project_shared_groups", { project });
+}
+
+export async function shareProjectWithGroup(args: {
+  project: string;
+  groupId: number;
+  groupAccess: AccessLevel | AccessLevelName;
+  expiresAt?: ExpiryInput | null;
+}) {
+  return loggedInvoke<void>("share_project_with_group", args);
+}
+
+export async function unshareProjectWithGroup(args: { project: string; groupId: number }) {
+  return loggedInvoke<void>("unshare_project_with_group", args);
+}
+
+/** 自定义成员角色；group 为空时列出实例级角色，实例不支持时返回空列表 */
+export async function listMemberRoles(group?: string | null) {
+  return loggedInvoke<MemberRole[]>("list_member_roles", { group: group ?? null });
+}
+
 export async function upsertLocalMembers(members: Array<{
   userId: number;
   username: string;
@@ -118,17 +197,23 @@ export async function upsertLocalMembers(members: Array<{
   avatarUrl?: string | null;
   projectId?: number | null;
   projectName?: string | null;
+  projectPath?: string | null;
+  /** 在该项目中看到的权限 */
+  accessLevel?: number | null;
 }>) {
   return loggedInvoke<void>("upsert_local_members", { members });
 }
 
+/**
+ * tags 不为空时只返回带有其中任一标签的成员
+ */
 export async function listLocalMembers(
-  query?: string | null,
+  query: LocalMemberQuery = {},
   page = 1,
   perPage = 50
 ): Promise<{ items: LocalMember[]; total: number }> {
   const [items, total] = await loggedInvoke<[LocalMember[], number]>("list_local_members", {
-    query: query && query.trim() ? query.trim() : null,
+    query: { ...query, text: query.text && query.text.trim() ? query.text.trim() : null },
     page,
     per_page: perPage,
   });
@@ -139,10 +224,60 @@ export async function deleteLocalMembers(userIds: number[]) {
   return loggedInvoke<void>("delete_local_members", { userIds });
 }
 
+export async function setLocalMemberNotes(userId: number, notes: string | null) {
+  return loggedInvoke<void>("set_local_member_notes", { userId, notes });
+}
+
+export async function listLocalTags() {
+  return loggedInvoke<LocalTag[]>("list_local_tags");
+}
+
+/**
+ * 给成员打标签，标签不存在时自动创建
+ */
+export async function addTagsToMembers(userIds: number[], tags: string[]) {
+  return loggedInvoke<void>("add_tags_to_members", { userIds, tags });
+}
+
+export async function removeTagsFromMembers(userIds: number[], tags: string[]) {
+  return loggedInvoke<void>("remove_tags_from_members", { userIds, tags });
+}
+
+export async function deleteLocalTag(tagId: number) {
+  return loggedInvoke<void>("delete_local_tag", { tagId });
+}
+
+/**
+ * 本地成员出现过的所有项目
+ */
+export async function listMemberSources(userId: number) {
+  return loggedInvoke<MemberSource[]>("list_member_sources", { userId });
+}
+
+/**
+ * 从 GitLab 刷新本地成员信息；userIds 为空时刷新全部
+ */
+export async function refreshLocalMembers(userIds?: number[] | null) {
+  return loggedInvoke<RefreshLocalMembersResult>("refresh_local_members", { userIds: userIds ?? null });
+}
+
 export async function createLocalGroup(name: string) {
   return loggedInvoke<LocalGroup>("create_local_group", { name });
 }
 
+export async function createSmartGroup(name: string, rule: GroupRule) {
+  return loggedInvoke<LocalGroup>("create_smart_group", { name, rule });
+}
+
+/** rule 为 null 时转为静态分组 */
+export async function setGroupRule(groupId: number, rule: GroupRule | null) {
+  return loggedInvoke<void>("set_group_rule", { groupId, rule });
+}
+
+export async function previewGroupRule(rule: GroupRule) {
+  return loggedInvoke<LocalMember[]>("preview_group_rule", { rule });
+}
+
 export async function listLocalGroups() {
   return loggedInvoke<LocalGroup[]>("list_local_groups");
 }
@@ -155,23 +290,130 @@ export async function deleteLocalGroup(id: number) {
   return loggedInvoke<void>("delete_local_group", { id });
 }
 
-export async function addMembersToGroup(groupId: number, userIds: number[]) {
-  return loggedInvoke<void>("add_members_to_group", { groupId, userIds });
+export async function addMembersToGroup(groupId: number, userIds: number[], accessLevel?: number | null) {
+  return loggedInvoke<void>("add_members_to_group", { groupId, userIds, accessLevel: accessLevel ?? null });
 }
 
 export async function removeMembersFromGroup(groupId: number, userIds: number[]) {
   return loggedInvoke<void>("remove_members_from_group", { groupId, userIds });
 }
 
-export async function listGroupMembers(groupId: number) {
-  return loggedInvoke<LocalMember[]>("list_group_members", { groupId });
+export async function setGroupMembersAccessLevel(groupId: number, userIds: number[], accessLevel: number | null) {
+  return loggedInvoke<void>("set_group_members_access_level", { groupId, userIds, accessLevel });
+}
+
+export async function includeGroups(groupId: number, childIds: number[]) {
+  return loggedInvoke<void>("include_groups", { groupId, childIds });
+}
+
+export async function excludeGroups(groupId: number, childIds: number[]) {
+  return loggedInvoke<void>("exclude_groups", { groupId, childIds });
+}
+
+export async function listGroupIncludes(groupId: number) {
+  return loggedInvoke<LocalGroup[]>("list_group_includes", { groupId });
+}
+
+export async function validateManifest(path: string) {
+  return loggedInvoke<ManifestValidation>("validate_manifest", { path });
+}
+
+export async function planManifest(path: string) {
+  return loggedInvoke<ManifestPlan>("plan_manifest", { path });
+}
+
+/** 执行预览过的团队清单计划（与当前状态不一致时拒绝）：新增 / 移除走批量任务（可撤销），权限变更逐个更新 */
+export async function applyManifest(path: string, plan: ManifestPlan) {
+  return loggedInvoke<ManifestApplyResult>("apply_manifest", { path, plan });
+}
+
+export async function getApprovalPolicy() {
+  return loggedInvoke<ApprovalPolicy>("get_approval_policy");
+}
+
+export async function setApprovalPolicy(policy: ApprovalPolicy) {
+  return loggedInvoke<void>("set_approval_policy", { policy });
+}
+
+/** 团队共享的签名密钥，导出 / 导入变更请求文件前需设置 */
+export async function setApprovalSecret(secret: string) {
+  return loggedInvoke<void>("set_approval_secret", { secret });
+}
+
+export async function listChangeRequests(status?: ChangeRequestStatus | null) {
+  return loggedInvoke<ChangeRequest[]>("list_change_requests", { status: status ?? null });
+}
+
+export async function getChangeRequest(id: number) {
+  return loggedInvoke<ChangeRequest | null>("get_change_request", { id });
+}
+
+/** 导出为签名的 JSON 文件内容 */
+export async function exportChangeRequest(id: number) {
+  return loggedInvoke<string>("export_change_request", { id });
+}
+
+/** 导入签名的变更请求文件，签名不符时报错 */
+export async function importChangeRequest(content: string) {
+  return loggedInvoke<ChangeRequest>("import_change_request", { content });
+}
+
+/** 审批人须是与发起人不同的 GitLab 用户 */
+export async function approveChangeRequest(id: number) {
+  return loggedInvoke<ChangeRequest>("approve_change_request", { id });
+}
+
+export async function rejectChangeRequest(id: number) {
+  return loggedInvoke<ChangeRequest>("reject_change_request", { id });
+}
+
+/** 执行已审批的变更请求（每个项目先生成快照） */
+export async function executeChangeRequest(id: number) {
+  return loggedInvoke<ChangeRequest>("execute_change_request", { id });
+}
+
+export async function listPolicyRules() {
+  return loggedInvoke<PolicyRule[]>("list_policy_rules");
+}
+
+export async function createPolicyRule(rule: PolicyRuleInput) {
+  return loggedInvoke<PolicyRule>("create_policy_rule", { rule });
+}
+
+export async function updatePolicyRule(id: number, rule: PolicyRuleInput) {
+  return loggedInvoke<void>("update_policy_rule", { id, rule });
+}
+
+export async function deletePolicyRule(id: number) {
+  return loggedInvoke<void>("delete_policy_rule", { id });
+}
+
+/** 导出分组（含嵌套子分组）为共享包内容 */
+export async function exportGroupBundle(groupIds: number[], format: BundleFormat = "json") {
+  return loggedInvoke<string>("export_group_bundle", { groupIds, format });
+}
+
+/** 导入 JSON / YAML 共享包，合并到本地分组 */
+export async function importGroupBundle(content: string) {
+  return loggedInvoke<BundleImportResult>("import_group_bundle", { content });
+}
+
+/**
+ * effective=true 时展开嵌套分组，返回去重后的有效成员
+ */
+export async function listGroupMembers(groupId: number, effective = false) {
+  return loggedInvoke<GroupMember[]>("list_group_members", { groupId, effective });
 }
 
 export async function batchAddMembersToProject(args: {
   project: string;
   userIds: number[];
-  accessLevel: number;
-  expiresAt?: string | null;
+  accessLevel: AccessLevel | AccessLevelName;
+  expiresAt?: ExpiryInput | null;
+  /** 自定义成员角色，需与 accessLevel 匹配其基础权限 */
+  memberRoleId?: number | null;
+  /** 额外选中带有任一标签的本地成员 */
+  tags?: string[] | null;
 }) {
   return loggedInvoke<BatchResult>("batch_add_members_to_project", args);
 }
@@ -179,8 +421,9 @@ export async function batchAddMembersToProject(args: {
 export async function addMemberToProject(args: {
   project: string;
   userId: number;
-  accessLevel: number;
-  expiresAt?: string | null;
+  accessLevel: AccessLevel | AccessLevelName;
+  expiresAt?: ExpiryInput | null;
+  memberRoleId?: number | null;
 }) {
   // 注意：Tauri command 参数名会按 camelCase 进行匹配（例如 user_ids -> userIds）
   return loggedInvoke<void>("add_member_to_project", {
@@ -188,12 +431,215 @@ export async function addMemberToProject(args: {
     userId: args.userId,
     accessLevel: args.accessLevel,
     expiresAt: args.expiresAt,
+    memberRoleId: args.memberRoleId,
   });
 }
 
 export async function batchRemoveMembersFromProject(args: {
   project: string;
   userIds: number[];
+  /** 额外选中带有任一标签的本地成员 */
+  tags?: string[] | null;
 }) {
   return loggedInvoke<BatchResult>("batch_remove_members_from_project", args);
 }
+
+/**
+ * 把分组（展开嵌套后）批量加入多个项目；accessLevel 为空时使用成员在分组中的权限
+ */
+export async function batchApplyGroupToProjects(args: {
+  groupId: number;
+  targets: ProjectTargets;
+  accessLevel?: AccessLevel | AccessLevelName | null;
+  expiresAt?: ExpiryInput | null;
+  memberRoleId?: number | null;
+}) {
+  return loggedInvoke<GroupApplyResult>("batch_apply_group_to_projects", args);
+}
+
+export async function batchRemoveGroupFromProjects(args: { groupId: number; targets: ProjectTargets }) {
+  return loggedInvoke<GroupApplyResult>("batch_remove_group_from_projects", args);
+}
+
+export async function listAccessRequests(sourceType: MembershipSourceType, source: string) {
+  return loggedInvoke<AccessRequest[]>("list_access_requests", { sourceType, source });
+}
+
+/**
+ * 批准访问申请：权限取 accessLevel，为空时取本地分组 groupId 中该成员的默认权限
+ */
+export async function approveAccessRequests(args: {
+  sourceType: MembershipSourceType;
+  source: string;
+  userIds: number[];
+  accessLevel?: AccessLevel | AccessLevelName | null;
+  groupId?: number | null;
+}) {
+  return loggedInvoke<BatchResult>("approve_access_requests", args);
+}
+
+export async function denyAccessRequests(args: { sourceType: MembershipSourceType; source: string; userIds: number[] }) {
+  return loggedInvoke<BatchResult>("deny_access_requests", args);
+}
+
+export async function previewCopyProjectMembers(args: {
+  source: string;
+  target: string;
+  options?: CopyMembersOptions;
+}) {
+  return loggedInvoke<CopyMembersPreview>("preview_copy_project_members", args);
+}
+
+/**
+ * 按复制计划执行；userIds 为预览中勾选的用户，为空时执行全部
+ */
+export async function copyProjectMembers(args: {
+  source: string;
+  target: string;
+  options?: CopyMembersOptions;
+  userIds?: number[] | null;
+}) {
+  return loggedInvoke<BatchResult>("copy_project_members", args);
+}
+
+export async function compareProjectMembers(projects: string[]) {
+  return loggedInvoke<MembershipComparison>("compare_project_members", { projects });
+}
+
+/**
+ * 返回 CSV 文本，配合 downloadText 保存
+ */
+export async function exportComparisonCsv(comparison: MembershipComparison) {
+  return loggedInvoke<string>("export_comparison_csv", { comparison });
+}
+
+export async function accessReviewReport(projects: string[], options?: AccessReviewOptions) {
+  return loggedInvoke<AccessReviewReport>("access_review_report", { projects, options: options ?? null });
+}
+
+/**
+ * 返回 CSV / HTML 文本，配合 downloadText 保存
+ */
+export async function exportAccessReview(report: AccessReviewReport, format: "csv" | "html") {
+  return loggedInvoke<string>("export_access_review", { report, format });
+}
+
+export async function previewOffboardUser(userId: number) {
+  return loggedInvoke<OffboardPreview>("preview_offboard_user", { userId });
+}
+
+/**
+ * 离职清理；memberships 传预览结果（可剔除部分条目），为空时后端重新收集
+ */
+export async function offboardUser(userId: number, memberships?: UserMembership[] | null) {
+  return loggedInvoke<OffboardResult>("offboard_user", { userId, memberships: memberships ?? null });
+}
+
+export async function listHistory(
+  action?: string | null,
+  page = 1,
+  perPage = 50
+): Promise<{ items: HistoryEntry[]; total: number }> {
+  const [items, total] = await loggedInvoke<[HistoryEntry[], number]>("list_history", {
+    action: action ?? null,
+    page,
+    perPage,
+  });
+  return { items, total };
+}
+
+export async function listProjectSnapshots(project: string) {
+  return loggedInvoke<ProjectSnapshot[]>("list_project_snapshots", { project });
+}
+
+export async function diffSnapshot(snapshotId: number) {
+  return loggedInvoke<SnapshotDiff>("diff_snapshot", { snapshotId });
+}
+
+/**
+ * 按快照把已被移除的直接成员重新加入；userIds 为空时恢复全部
+ */
+export async function restoreSnapshot(snapshotId: number, userIds?: number[] | null) {
+  return loggedInvoke<BatchResult>("restore_snapshot", { snapshotId, userIds: userIds ?? null });
+}
+
+/**
+ * 撤销批量任务：加人任务移除新加入的用户，移除任务按原权限与过期时间重新加入
+ */
+export async function undoBatch(jobId: number) {
+  return loggedInvoke<BatchResult>("undo_batch", { jobId });
+}
+
+export async function listBatchJobs(
+  project?: string | null,
+  status?: BatchJobStatus | null,
+  page = 1,
+  perPage = 50
+): Promise<{ items: BatchJobSummary[]; total: number }> {
+  const [items, total] = await loggedInvoke<[BatchJobSummary[], number]>("list_batch_jobs", {
+    project: project ?? null,
+    status: status ?? null,
+    page,
+    perPage,
+  });
+  return { items, total };
+}
+
+export async function getBatchJob(jobId: number) {
+  return loggedInvoke<BatchJobDetail>("get_batch_job", { jobId });
+}
+
+/**
+ * 继续执行被中断任务中尚未处理的条目
+ */
+export async function resumeBatchJob(jobId: number) {
+  return loggedInvoke<BatchResult>("resume_batch_job", { jobId });
+}
+
+export async function retryFailedBatchItems(jobId: number) {
+  return loggedInvoke<BatchResult>("retry_failed_batch_items", { jobId });
+}
+
+export async function getExpirySettings() {
+  return loggedInvoke<ExpirySettings>("get_expiry_settings");
+}
+
+export async function setExpirySettings(settings: ExpirySettings) {
+  return loggedInvoke<void>("set_expiry_settings", { settings });
+}
+
+/** 解析并校验过期时间输入，返回 YYYY-MM-DD */
+export async function resolveExpiry(expiresAt: ExpiryInput) {
+  return loggedInvoke<string>("resolve_expiry", { expiresAt });
+}
+
+export async function getRenewalSettings() {
+  return loggedInvoke<RenewalSettings>("get_renewal_settings");
+}
+
+export async function setRenewalSettings(settings: RenewalSettings) {
+  return loggedInvoke<void>("set_renewal_settings", { settings });
+}
+
+export async function listTrackedProjects() {
+  return loggedInvoke<TrackedProject[]>("list_tracked_projects");
+}
+
+export async function addTrackedProject(project: string, name?: string | null) {
+  return loggedInvoke<void>("add_tracked_project", { project, name: name ?? null });
+}
+
+export async function removeTrackedProject(project: string) {
+  return loggedInvoke<void>("remove_tracked_project", { project });
+}
+
+/**
+ * 立即巡检；autoExtend 为空时沿用设置。后台定时巡检完成后会推送 "expiry-scan" 事件
+ */
+export async function runExpiryScanNow(autoExtend?: boolean | null) {
+  return loggedInvoke<ExpiryScanResult>("run_expiry_scan_now", { autoExtend: autoExtend ?? null });
+}
+
+export async function getLastExpiryScan() {
+  return loggedInvoke<ExpiryScanResult | null>("get_last_expiry_scan");
+}
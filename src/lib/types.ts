@@ -12,14 +12,42 @@ export type ProjectSummary = {
   lastActivityAt: string;
 };
 
+export type ProjectVisibility = "public" | "internal" | "private";
+
+export type ProjectOrderBy = "id" | "name" | "path" | "createdAt" | "updatedAt" | "lastActivityAt" | "starCount";
+
+/** 项目搜索条件；group 不为空时在该 GitLab 分组内搜索，此时 membership 不生效 */
+export type ProjectSearchOptions = {
+  group?: string | null;
+  /** 分组搜索是否包含子分组，默认 true */
+  includeSubgroups?: boolean | null;
+  membership?: boolean;
+  owned?: boolean;
+  /** 为空时不过滤归档状态 */
+  archived?: boolean | null;
+  visibility?: ProjectVisibility | null;
+  /** 当前用户在项目中至少具备的权限 */
+  minAccessLevel?: AccessLevel | AccessLevelName | null;
+  topic?: string | null;
+  /** 默认 lastActivityAt */
+  orderBy?: ProjectOrderBy | null;
+  /** 默认 desc */
+  sort?: "asc" | "desc" | null;
+};
+
 export type ProjectMember = {
   id: number;
   username: string;
   name: string;
   avatarUrl?: string | null;
-  accessLevel: number;
+  accessLevel: AccessLevel;
   createdAt?: string | null;
   expiresAt?: string | null;
+  /** active / blocked / deactivated 等 */
+  state?: string | null;
+  /** 自定义成员角色（实例支持时） */
+  memberRoleId?: number | null;
+  memberRoleName?: string | null;
 };
 
 export type LocalMember = {
@@ -30,6 +58,51 @@ export type LocalMember = {
   updatedAt: string;
   projectId?: number | null;
   projectName?: string | null;
+  /** GitLab 用户状态：active / blocked / deactivated 等，未同步过为空 */
+  state?: string | null;
+  lastSyncedAt?: string | null;
+  email?: string | null;
+  /** 最近一次同步时 GitLab 中已不存在该用户 */
+  missing?: boolean;
+  notes?: string | null;
+  tags?: string[];
+};
+
+export type LocalMemberSort = "relevance" | "name" | "username" | "updated" | "groupCount";
+
+/** 本地成员查询条件，各条件之间为“且” */
+export type LocalMemberQuery = {
+  /** 全文检索用户名、昵称、邮箱、备注、标签和来源项目，按词前缀匹配 */
+  text?: string | null;
+  tags?: string[];
+  /** 本地分组的有效成员（含嵌套子分组与动态规则命中的成员） */
+  groupId?: number | null;
+  /** 不属于任何本地分组（静态成员与动态规则均未命中） */
+  ungrouped?: boolean;
+  /** active / blocked / deactivated，"missing" 表示 GitLab 中已不存在 */
+  state?: string | null;
+  sort?: LocalMemberSort;
+};
+
+export type LocalTag = {
+  id: number;
+  name: string;
+  createdAt: string;
+  membersCount: number;
+};
+
+export type MemberSource = {
+  projectId: number;
+  projectPath: string | null;
+  accessLevel: number | null;
+  firstSeen: string;
+  lastSeen: string;
+};
+
+export type RefreshLocalMembersResult = {
+  refreshed: number[];
+  missing: number[];
+  failed: BatchItemError[];
 };
 
 export type LocalGroup = {
@@ -37,6 +110,31 @@ export type LocalGroup = {
   name: string;
   createdAt: string;
   membersCount: number;
+  includesCount: number;
+  /** 动态分组规则；为空表示静态分组 */
+  rule?: GroupRule | null;
+  /** 已保存的规则无法解析时的错误信息 */
+  ruleError?: string | null;
+};
+
+/** 动态分组规则：各条件同时满足，未设置的条件忽略 */
+export type GroupRule = {
+  tags?: string[];
+  /** GLOB，例如 ext-* */
+  usernamePattern?: string | null;
+  /** 项目 ID 或 path_with_namespace */
+  sourceProject?: string | null;
+  /** GitLab 分组 ID 或 full_path */
+  gitlabGroup?: string | null;
+  /** 默认 30 (Developer) */
+  gitlabMinAccessLevel?: number | null;
+  accessLevel?: number | null;
+};
+
+export type GroupMember = LocalMember & {
+  accessLevel?: number | null;
+  direct: boolean;
+  viaGroups: string[];
 };
 
 export type BatchItemError = {
@@ -47,15 +145,486 @@ export type BatchItemError = {
 export type BatchResult = {
   successUserIds: number[];
   failed: BatchItemError[];
+  /** 已是成员（加人）/ 本就不是成员（移除），未做修改 */
+  skippedUserIds: number[];
+  /** 执行前自动生成的项目成员快照 */
+  snapshotId?: number | null;
+  /** 对应的批量任务，可用于撤销 */
+  jobId?: number | null;
+};
+
+export type BatchAddItem = {
+  userId: number;
+  accessLevel: AccessLevel;
+  expiresAt?: string | null;
+  memberRoleId?: number | null;
+};
+
+/** 目标项目：显式列表 / 关键字搜索 / GitLab namespace 下全部项目，取并集 */
+export type ProjectTargets = {
+  projects?: string[];
+  keyword?: string | null;
+  namespace?: string | null;
+};
+
+export type ProjectBatchResult = BatchResult & {
+  project: string;
+};
+
+export type GroupApplyResult = {
+  groupId: number;
+  userIds: number[];
+  projects: ProjectBatchResult[];
+};
+
+/** 复制成员选项：accessLevel / expiresAt 不为空时覆盖源成员的权限与过期时间 */
+export type CopyMembersOptions = {
+  minAccessLevel?: number | null;
+  directOnly?: boolean;
+  accessLevel?: number | null;
+  expiresAt?: ExpiryInput | null;
+};
+
+export type CopyPlanItem = {
+  userId: number;
+  username: string;
+  name: string;
+  accessLevel: number;
+  expiresAt?: string | null;
+  /** 目标项目已有该成员（直接成员，或继承权限不低于计划权限）时的权限，执行时跳过 */
+  existingAccessLevel?: number | null;
+};
+
+export type CopyMembersPreview = {
+  source: string;
+  target: string;
+  items: CopyPlanItem[];
+};
+
+export type ComparisonCell = {
+  accessLevel: number;
+  expiresAt?: string | null;
+};
+
+/** cells 与 MembershipComparison.projects 一一对应，null 表示该项目中没有此用户 */
+export type ComparisonRow = {
+  userId: number;
+  username: string;
+  name: string;
+  cells: (ComparisonCell | null)[];
+  missing: boolean;
+  levelMismatch: boolean;
+  expiryMismatch: boolean;
+};
+
+export type MembershipComparison = {
+  projects: string[];
+  rows: ComparisonRow[];
+};
+
+export type AccessReviewOptions = {
+  expiringWithinDays?: number;
+  privilegedMinLevel?: number;
+  inactiveDays?: number;
+};
+
+export type ReviewEntry = {
+  userId: number;
+  username: string;
+  name: string;
+  accessLevel: number;
+  expiresAt?: string | null;
+  state?: string | null;
+  lastActivityOn?: string | null;
+};
+
+export type ProjectAccessReview = {
+  project: string;
+  totalMembers: number;
+  expiring: ReviewEntry[];
+  privileged: ReviewEntry[];
+  inactive: ReviewEntry[];
+  blocked: ReviewEntry[];
+};
+
+export type AccessReviewReport = {
+  generatedAt: string;
+  options: Required<AccessReviewOptions>;
+  /** false 表示 token 不是管理员，未检查最近活动 */
+  activityChecked: boolean;
+  projects: ProjectAccessReview[];
+};
+
+export type MembershipSourceType = "project" | "group";
+
+/** 用户在某个项目 / GitLab 分组中的直接成员关系 */
+export type UserMembership = {
+  sourceType: MembershipSourceType;
+  sourceId: number;
+  sourceName: string;
+  accessLevel: number;
+};
+
+/** 项目共享给的 GitLab 分组：分组成员取 groupAccessLevel 与其分组权限中较低者 */
+export type SharedGroup = {
+  groupId: number;
+  groupName: string;
+  groupFullPath: string;
+  groupAccessLevel: AccessLevel;
+  expiresAt?: string | null;
+};
+
+/** 用户在 GitLab 中点击 "Request access" 后待处理的访问申请 */
+export type AccessRequest = {
+  /** 申请人的用户 ID */
+  id: number;
+  username: string;
+  name: string;
+  avatarUrl?: string | null;
+  state?: string | null;
+  requestedAt?: string | null;
+};
+
+export type MembershipError = {
+  membership: UserMembership;
+  message: string;
+};
+
+export type SkippedSource = {
+  sourceType: MembershipSourceType;
+  sourceId: number;
+  sourceName: string;
+  message: string;
+};
+
+export type OffboardPreview = {
+  userId: number;
+  /** false：来自管理员接口；true：逐个扫描可管理的项目/分组得到 */
+  scanned: boolean;
+  /** 翻页达到上限，memberships 可能不完整 */
+  truncated: boolean;
+  /** 扫描失败被跳过的项目/分组 */
+  skipped: SkippedSource[];
+  memberships: UserMembership[];
+  localGroups: LocalGroup[];
+};
+
+export type OffboardResult = {
+  userId: number;
+  removed: UserMembership[];
+  /** 执行时已不是成员，未做修改 */
+  unchanged: UserMembership[];
+  failed: MembershipError[];
+  localGroupsRemoved: number;
+};
+
+export type HistoryEntry = {
+  id: number;
+  action: string;
+  target: string;
+  detail: unknown;
+  createdAt: string;
+};
+
+export type RenewalSettings = {
+  enabled: boolean;
+  /** 提前多少天提醒 */
+  windowDays: number;
+  /** 每次续期顺延的天数 */
+  extendDays: number;
+  autoExtend: boolean;
+  /** 续期分组：只有这些本地分组（含子分组）的成员会被自动续期 */
+  groupIds: number[];
 };
 
-export const ACCESS_LEVELS: { label: string; value: number }[] = [
+export type TrackedProject = {
+  project: string;
+  name: string | null;
+  createdAt: string;
+};
+
+export type ExpiringMember = {
+  project: string;
+  userId: number;
+  username: string;
+  name: string;
+  accessLevel: number;
+  expiresAt: string;
+  renewable: boolean;
+};
+
+export type RenewalRecord = {
+  project: string;
+  userId: number;
+  username: string;
+  previousExpiresAt: string;
+  expiresAt: string;
+};
+
+export type RenewalFailure = {
+  project: string;
+  /** 0 表示整个项目拉取失败 */
+  userId: number;
+  message: string;
+};
+
+export type ExpiryScanResult = {
+  scannedAt: string;
+  expiring: ExpiringMember[];
+  renewed: RenewalRecord[];
+  failed: RenewalFailure[];
+};
+
+export type SnapshotMemberSource = "direct" | "inherited";
+
+export type ProjectSnapshot = {
+  id: number;
+  project: string;
+  /** 触发快照的操作 */
+  reason: string;
+  memberCount: number;
+  createdAt: string;
+};
+
+export type SnapshotMember = {
+  userId: number;
+  username: string;
+  name: string;
+  accessLevel: number;
+  expiresAt: string | null;
+  source: SnapshotMemberSource;
+};
+
+export type SnapshotDiff = {
+  snapshot: ProjectSnapshot;
+  /** 快照之后新增的成员 */
+  added: SnapshotMember[];
+  /** 快照中有、当前已不在项目中的成员 */
+  removed: SnapshotMember[];
+  changed: { before: SnapshotMember; after: SnapshotMember }[];
+};
+
+/** update：修改已有成员的权限 / 过期时间，撤销时改回执行前的值；approve：批准项目的访问申请 */
+export type BatchJobKind = "add" | "remove" | "update" | "approve";
+/** interrupted：应用退出/崩溃时仍在执行，可继续执行 */
+export type BatchJobStatus = "running" | "completed" | "interrupted" | "undone";
+export type BatchItemStatus = "pending" | "applied" | "skipped" | "failed";
+
+export type BatchJob = {
+  id: number;
+  kind: BatchJobKind;
+  project: string;
+  status: BatchJobStatus;
+  snapshotId: number | null;
+  /** 本任务撤销的是哪个任务 */
+  undoOf: number | null;
+  createdAt: string;
+  finishedAt: string | null;
+};
+
+export type BatchJobItem = {
+  userId: number;
+  accessLevel: number | null;
+  expiresAt: string | null;
+  priorAccessLevel: number | null;
+  priorExpiresAt: string | null;
+  memberRoleId?: number | null;
+  priorMemberRoleId?: number | null;
+  status: BatchItemStatus;
+  message: string | null;
+};
+
+export type BatchJobSummary = BatchJob & {
+  total: number;
+  pending: number;
+  applied: number;
+  skipped: number;
+  failed: number;
+};
+
+export type BatchJobDetail = {
+  job: BatchJob;
+  items: BatchJobItem[];
+};
+
+/** GitLab 权限级别：后端返回数值，传参时也可用名称（如 "developer"） */
+export type AccessLevel = number;
+
+export type AccessLevelName =
+  | "no_access"
+  | "minimal_access"
+  | "guest"
+  | "planner"
+  | "reporter"
+  | "developer"
+  | "maintainer"
+  | "owner";
+
+export const ACCESS_LEVELS: { label: string; value: AccessLevel }[] = [
   { label: "Guest (10)", value: 10 },
+  { label: "Planner (15)", value: 15 },
   { label: "Reporter (20)", value: 20 },
   { label: "Developer (30)", value: 30 },
   { label: "Maintainer (40)", value: 40 },
+  { label: "Owner (50)", value: 50 },
 ];
 
-export function accessLevelLabel(level: number): string {
-  return ACCESS_LEVELS.find((x) => x.value === level)?.label ?? String(level);
+const OTHER_ACCESS_LEVELS: { label: string; value: AccessLevel }[] = [
+  { label: "No access (0)", value: 0 },
+  { label: "Minimal access (5)", value: 5 },
+];
+
+export function accessLevelLabel(level: AccessLevel): string {
+  return [...ACCESS_LEVELS, ...OTHER_ACCESS_LEVELS].find((x) => x.value === level)?.label ?? String(level);
 }
+
+/** GitLab 自定义成员角色，基于 baseAccessLevel 追加权限 */
+export type MemberRole = {
+  id: number;
+  name: string;
+  description?: string | null;
+  baseAccessLevel: AccessLevel;
+  /** 所属顶级分组；实例级角色为空 */
+  groupId?: number | null;
+};
+
+/** 数据库备份 / 恢复结果 */
+export type BackupInfo = {
+  path: string;
+  /** 备份中最后一次执行的迁移版本 */
+  schemaVersion: number;
+  sizeBytes: number;
+  /** 备份中已清空 GitLab token */
+  secretsExcluded: boolean;
+  createdAt: string;
+};
+
+export type BundleFormat = "json" | "yaml";
+
+export type BundleConflictKind =
+  | "userNotFound"
+  | "renamed"
+  | "idMismatch"
+  | "levelDiffers"
+  | "ruleDiffers"
+  | "includeFailed";
+
+export type BundleConflict = {
+  kind: BundleConflictKind;
+  group?: string | null;
+  username?: string | null;
+  message: string;
+};
+
+/** 分组共享包导入结果 */
+export type BundleImportResult = {
+  groupsCreated: string[];
+  groupsMerged: string[];
+  membersAdded: number;
+  conflicts: BundleConflict[];
+};
+
+export type ManifestValidation = {
+  valid: boolean;
+  errors: string[];
+};
+
+export type ManifestAction = "add" | "update" | "remove";
+
+export type ManifestChange = {
+  project: string;
+  action: ManifestAction;
+  userId: number;
+  username: string;
+  accessLevel?: number | null;
+  expiresAt?: string | null;
+  currentAccessLevel?: number | null;
+  currentExpiresAt?: string | null;
+};
+
+/** 团队清单与 GitLab 当前状态的差异 */
+export type ManifestPlan = {
+  changes: ManifestChange[];
+  errors: string[];
+};
+
+export type ManifestApplyResult = {
+  plan: ManifestPlan;
+  added: ProjectBatchResult[];
+  removed: ProjectBatchResult[];
+  /** 权限 / 过期时间修改的批量任务结果 */
+  changed: ProjectBatchResult[];
+  updated: ManifestChange[];
+  failed: { change: ManifestChange; message: string }[];
+};
+
+/** 审批策略：启用后，满足任一条件的变更需要另一位操作者审批 */
+export type ApprovalPolicy = {
+  enabled: boolean;
+  minAccessLevel: number;
+  maxUsers: number;
+  protectedProjects: string[];
+};
+
+export type ChangeOperation = {
+  kind: BatchJobKind;
+  /** 为 group 时 project 是 GitLab 分组（批准分组的访问申请、离职清理） */
+  sourceType?: MembershipSourceType;
+  project: string;
+  items: BatchAddItem[];
+  userIds: number[];
+  /** 已有成员的权限 / 过期时间变更（团队清单），审批时按加人计算 */
+  updates?: BatchAddItem[];
+  /** 把项目共享给 GitLab 分组，审批时按加人计算；kind 为 remove 时为取消共享 */
+  shares?: GroupShareItem[];
+  /** 修改审批策略本身（策略启用时修改需要审批） */
+  policy?: ApprovalPolicy | null;
+};
+
+export type GroupShareItem = {
+  groupId: number;
+  groupAccess: AccessLevel;
+  expiresAt?: string | null;
+};
+
+export type ChangeRequestStatus = "pending" | "approved" | "rejected" | "executed";
+
+export type ChangeRequest = {
+  id: number;
+  uid: string;
+  status: ChangeRequestStatus;
+  createdAt: string;
+  createdBy: string;
+  reasons: string[];
+  operations: ChangeOperation[];
+  approvedBy?: string | null;
+  approvedAt?: string | null;
+  executedAt?: string | null;
+  result?: ProjectBatchResult[] | null;
+};
+
+/** 策略规则类型：protectedUser 的 pattern 为用户名，其余为项目路径（也可写上级 namespace） */
+export type PolicyRuleKind = "maxAccessLevel" | "requireExpiry" | "protectedUser" | "forbiddenNamespace" | "maxBatchSize";
+
+export type PolicyRule = {
+  id: number;
+  kind: PolicyRuleKind;
+  /** 通配符：* 任意个字符，? 单个字符，不区分大小写 */
+  pattern: string;
+  /** maxAccessLevel 为权限级别，maxBatchSize 为用户数上限 */
+  value?: number | null;
+  enabled: boolean;
+  description?: string | null;
+  createdAt: string;
+  updatedAt: string;
+};
+
+export type PolicyRuleInput = Pick<PolicyRule, "kind" | "pattern" | "value" | "enabled" | "description">;
+
+/** 过期时间输入：YYYY-MM-DD，或 +30d / +2w / +3m / +1y、end-of-month / end-of-quarter / end-of-year；必须晚于今天 */
+export type ExpiryInput = string;
+
+export type ExpirySettings = {
+  /** 过期时间最多在今天之后多少天，为空表示不限制 */
+  maxDays?: number | null;
+};
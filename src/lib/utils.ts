@@ -13,3 +13,15 @@ export function formatDateTime(s: string | null | undefined): string {
   const pad = (n: number) => String(n).padStart(2, "0");
   return `${d.getFullYear()}-${pad(d.getMonth() + 1)}-${pad(d.getDate())} ${pad(d.getHours())}:${pad(d.getMinutes())}:${pad(d.getSeconds())}`;
 }
+
+/** 把文本内容作为文件下载（CSV / HTML 报表导出） */
+export function downloadText(filename: string, content: string, mime = "text/csv;charset=utf-8") {
+  // CSV 加 BOM，便于 Excel 正确识别中文
+  const body = mime.startsWith("text/csv") ? `\uFEFF${content}` : content;
+  const url = URL.createObjectURL(new Blob([body], { type: mime }));
+  const a = document.createElement("a");
+  a.href = url;
+  a.download = filename;
+  a.click();
+  URL.revokeObjectURL(url);
+}
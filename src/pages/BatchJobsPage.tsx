@@ -0,0 +1,221 @@
+import * as React from "react";
+import { toast } from "sonner";
+
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Panel, PanelBody, PanelHeader } from "@/components/ui/panel";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "@/components/ui/table";
+import { listBatchJobs, resumeBatchJob, retryFailedBatchItems } from "@/lib/invoke";
+import type { BatchJobKind, BatchJobStatus, BatchJobSummary, BatchResult } from "@/lib/types";
+import { formatDateTime } from "@/lib/utils";
+
+const PAGE_SIZE = 20;
+
+const kindLabels: Record<BatchJobKind, string> = {
+  add: "加人",
+  remove: "移除",
+  update: "修改权限",
+  approve: "批准申请",
+};
+
+const statusLabels: Record<BatchJobStatus, string> = {
+  running: "执行中",
+  completed: "已完成",
+  interrupted: "已中断",
+  undone: "已撤销",
+};
+
+/** 执行中或已撤销的任务不能再续跑 / 重试 */
+function isLocked(job: BatchJobSummary) {
+  return job.status === "running" || job.status === "undone";
+}
+
+function summarize(r: BatchResult) {
+  return `成功 ${r.successUserIds.length}，跳过 ${r.skippedUserIds.length}，失败 ${r.failed.length}`;
+}
+
+export function BatchJobsPage() {
+  const [project, setProject] = React.useState("");
+  const [status, setStatus] = React.useState<BatchJobStatus | "">("");
+  const [items, setItems] = React.useState<BatchJobSummary[]>([]);
+  const [total, setTotal] = React.useState(0);
+  const [page, setPage] = React.useState(1);
+  const [loading, setLoading] = React.useState(false);
+  const [busyId, setBusyId] = React.useState<number | null>(null);
+  const [error, setError] = React.useState("");
+
+  async function fetchPage(p: number) {
+    setError("");
+    setLoading(true);
+    try {
+      const res = await listBatchJobs(project.trim() || null, status || null, p, PAGE_SIZE);
+      setItems(res.items);
+      setTotal(res.total);
+    } catch (e) {
+      setError(String(e));
+      setItems([]);
+      setTotal(0);
+    } finally {
+      setLoading(false);
+    }
+  }
+
+  React.useEffect(() => {
+    void fetchPage(1);
+  }, []);
+
+  async function onSearch() {
+    setPage(1);
+    await fetchPage(1);
+  }
+
+  function onPageChange(next: number) {
+    setPage(next);
+    void fetchPage(next);
+  }
+
+  async function onRun(job: BatchJobSummary, onlyFailed: boolean) {
+    setBusyId(job.id);
+    try {
+      const res = onlyFailed ? await retryFailedBatchItems(job.id) : await resumeBatchJob(job.id);
+      toast.success(`任务 #${job.id} ${onlyFailed ? "重试" : "继续执行"}完成：${summarize(res)}`);
+    } catch (e) {
+      toast.error(`任务 #${job.id} 执行失败：${String(e)}`);
+    } finally {
+      setBusyId(null);
+      await fetchPage(page);
+    }
+  }
+
+  const pageCount = Math.max(1, Math.ceil(total / PAGE_SIZE));
+  const safePage = Math.min(page, pageCount);
+
+  return (
+    <div className="space-y-6">
+      <Panel>
+        <PanelHeader className="flex-col items-start gap-1">
+          <p className="text-sm text-muted-foreground">
+            批量加人 / 移除的执行记录。被中断的任务可以继续执行未处理的用户，也可以只重试失败的用户。
+          </p>
+        </PanelHeader>
+        <PanelBody>
+          <div className="flex flex-wrap items-end gap-2">
+            <div className="grid gap-1">
+              <Label>项目</Label>
+              <Input
+                className="w-[260px]"
+                value={project}
+                onChange={(e) => setProject(e.target.value)}
+                placeholder="项目 ID 或路径（可选）"
+              />
+            </div>
+            <div className="grid gap-1">
+              <Label>状态</Label>
+              <Select value={status || "__all__"} onValueChange={(v) => setStatus(v === "__all__" ? "" : (v as BatchJobStatus))}>
+                <SelectTrigger className="w-[160px]">
+                  <SelectValue placeholder="全部" />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="__all__">全部</SelectItem>
+                  {(Object.keys(statusLabels) as BatchJobStatus[]).map((s) => (
+                    <SelectItem key={s} value={s}>
+                      {statusLabels[s]}
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            </div>
+            <Button onClick={onSearch} disabled={loading}>
+              {loading ? "加载中..." : "查询"}
+            </Button>
+          </div>
+
+          {error && <div className="mt-3 text-sm text-destructive">{error}</div>}
+        </PanelBody>
+      </Panel>
+
+      <Panel>
+        <PanelHeader className="flex flex-wrap items-end justify-between gap-2">
+          <div className="flex flex-wrap items-center gap-2 text-sm text-muted-foreground">
+            <span>
+              第 {safePage} / {pageCount} 页（共 {total}）
+            </span>
+            <Button
+              variant="secondary"
+              onClick={() => onPageChange(Math.max(1, safePage - 1))}
+              disabled={safePage <= 1 || loading}
+            >
+              上一页
+            </Button>
+            <Button
+              variant="secondary"
+              onClick={() => onPageChange(Math.min(pageCount, safePage + 1))}
+              disabled={safePage >= pageCount || loading}
+            >
+              下一页
+            </Button>
+          </div>
+        </PanelHeader>
+        <PanelBody>
+          <Table>
+            <TableHeader>
+              <TableRow>
+                <TableHead>ID</TableHead>
+                <TableHead>类型</TableHead>
+                <TableHead>项目</TableHead>
+                <TableHead>状态</TableHead>
+                <TableHead>进度</TableHead>
+                <TableHead>创建时间</TableHead>
+                <TableHead>操作</TableHead>
+              </TableRow>
+            </TableHeader>
+            <TableBody>
+              {items.map((job) => (
+                <TableRow key={job.id} className="transition-colors hover:bg-muted/50">
+                  <TableCell className="font-mono">
+                    {job.id}
+                    {job.undoOf != null && <span className="ml-1 text-xs text-muted-foreground">（撤销 #{job.undoOf}）</span>}
+                  </TableCell>
+                  <TableCell>{kindLabels[job.kind]}</TableCell>
+                  <TableCell className="font-mono text-xs">{job.project}</TableCell>
+                  <TableCell>{statusLabels[job.status]}</TableCell>
+                  <TableCell className="text-xs text-muted-foreground">
+                    共 {job.total} · 成功 {job.applied} · 跳过 {job.skipped} · 失败 {job.failed} · 未处理 {job.pending}
+                  </TableCell>
+                  <TableCell className="font-mono text-xs">{formatDateTime(job.createdAt)}</TableCell>
+                  <TableCell>
+                    <div className="flex gap-2">
+                      <Button
+                        variant="secondary"
+                        onClick={() => onRun(job, false)}
+                        disabled={isLocked(job) || job.pending === 0 || busyId !== null}
+                      >
+                        继续执行
+                      </Button>
+                      <Button
+                        variant="secondary"
+                        onClick={() => onRun(job, true)}
+                        disabled={isLocked(job) || job.failed === 0 || busyId !== null}
+                      >
+                        重试失败
+                      </Button>
+                    </div>
+                  </TableCell>
+                </TableRow>
+              ))}
+              {items.length === 0 && (
+                <TableRow>
+                  <TableCell colSpan={7} className="text-center text-muted-foreground">
+                    {loading ? "加载中..." : "暂无批量任务"}
+                  </TableCell>
+                </TableRow>
+              )}
+            </TableBody>
+          </Table>
+        </PanelBody>
+      </Panel>
+    </div>
+  );
+}
@@ -1,6 +1,7 @@
 import * as React from "react";
 
 import { Button } from "@/components/ui/button";
+import { Checkbox } from "@/components/ui/checkbox";
 import {
   Dialog,
   DialogContent,
@@ -13,6 +14,7 @@ import {
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { Panel, PanelBody, PanelHeader } from "@/components/ui/panel";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "@/components/ui/table";
 import {
   listGroupMembers,
@@ -21,15 +23,22 @@ import {
   updateLocalGroup,
   deleteLocalGroup,
   removeMembersFromGroup,
+  includeGroups,
+  excludeGroups,
+  listGroupIncludes,
 } from "@/lib/invoke";
-import type { LocalGroup, LocalMember } from "@/lib/types";
+import type { GroupMember, LocalGroup } from "@/lib/types";
+import { accessLevelLabel } from "@/lib/types";
 import { formatDateTime } from "@/lib/utils";
 import { toast } from "sonner";
 
 export function GroupsPage() {
   const [groups, setGroups] = React.useState<LocalGroup[]>([]);
   const [activeGroup, setActiveGroup] = React.useState<LocalGroup | null>(null);
-  const [members, setMembers] = React.useState<LocalMember[]>([]);
+  const [members, setMembers] = React.useState<GroupMember[]>([]);
+  const [effective, setEffective] = React.useState(false);
+  const [includes, setIncludes] = React.useState<LocalGroup[]>([]);
+  const [includeId, setIncludeId] = React.useState("");
   const [newName, setNewName] = React.useState("");
   const [createOpen, setCreateOpen] = React.useState(false);
   const [editOpen, setEditOpen] = React.useState(false);
@@ -45,10 +54,35 @@ export function GroupsPage() {
     setGroups(g);
   }
 
-  async function openGroup(g: LocalGroup) {
+  async function openGroup(g: LocalGroup, showEffective = effective) {
     setActiveGroup(g);
-    const ms = await listGroupMembers(g.id);
+    const [ms, inc] = await Promise.all([listGroupMembers(g.id, showEffective), listGroupIncludes(g.id)]);
     setMembers(ms);
+    setIncludes(inc);
+  }
+
+  async function toggleEffective(v: boolean) {
+    setEffective(v);
+    if (activeGroup) await openGroup(activeGroup, v);
+  }
+
+  async function onInclude() {
+    if (!activeGroup || !includeId) return;
+    try {
+      await includeGroups(activeGroup.id, [Number(includeId)]);
+      setIncludeId("");
+      await refresh();
+      await openGroup(activeGroup);
+    } catch (err) {
+      toast.error(`嵌套失败：${String(err)}`);
+    }
+  }
+
+  async function onExclude(childId: number) {
+    if (!activeGroup) return;
+    await excludeGroups(activeGroup.id, [childId]);
+    await refresh();
+    await openGroup(activeGroup);
   }
 
   React.useEffect(() => {
@@ -99,6 +133,7 @@ export function GroupsPage() {
       if (activeGroup?.id === g.id) {
         setActiveGroup(null);
         setMembers([]);
+        setIncludes([]);
       }
       toast.success("已删除分组");
     } catch (err) {
@@ -266,7 +301,14 @@ export function GroupsPage() {
                             onClick={() => void openGroup(g)}
                           >
                             <TableCell className="font-mono">{g.id}</TableCell>
-                            <TableCell className={activeGroup?.id === g.id ? "font-semibold" : ""}>{g.name}</TableCell>
+                            <TableCell className={activeGroup?.id === g.id ? "font-semibold" : ""}>
+                              {g.name}
+                              {g.ruleError && (
+                                <span className="ml-2 text-xs text-destructive" title={g.ruleError}>
+                                  规则无效
+                                </span>
+                              )}
+                            </TableCell>
                             <TableCell className="font-mono text-xs">{formatDateTime(g.createdAt)}</TableCell>
                             <TableCell onClick={(e) => e.stopPropagation()}>
                               <div className="flex gap-1">
@@ -295,6 +337,34 @@ export function GroupsPage() {
       <Panel>
         <PanelHeader className="flex flex-col gap-2">
           <h3 className="font-semibold">分组成员 {activeGroup ? `— ${activeGroup.name}` : ""}</h3>
+                    {activeGroup && (
+                      <div className="flex flex-wrap items-center gap-2 text-sm">
+                        <span className="text-muted-foreground">子分组：</span>
+                        {includes.length === 0 && <span className="text-muted-foreground">无</span>}
+                        {includes.map((c) => (
+                          <Button key={c.id} variant="secondary" size="sm" onClick={() => void onExclude(c.id)}>
+                            {c.name} ×
+                          </Button>
+                        ))}
+                        <Select value={includeId} onValueChange={setIncludeId}>
+                          <SelectTrigger className="w-[200px]">
+                            <SelectValue placeholder="选择要嵌套的分组" />
+                          </SelectTrigger>
+                          <SelectContent>
+                            {groups
+                              .filter((g) => g.id !== activeGroup.id && !includes.some((c) => c.id === g.id))
+                              .map((g) => (
+                                <SelectItem key={g.id} value={String(g.id)}>
+                                  #{g.id} {g.name}
+                                </SelectItem>
+                              ))}
+                          </SelectContent>
+                        </Select>
+                        <Button size="sm" onClick={() => void onInclude()} disabled={!includeId}>
+                          嵌套
+                        </Button>
+                      </div>
+                    )}
                     <div className="flex flex-wrap items-end justify-between gap-2">
                       <div className="grid gap-1">
                         <Label>过滤成员</Label>
@@ -305,6 +375,10 @@ export function GroupsPage() {
                           placeholder="用户名 / 昵称 / ID"
                         />
                       </div>
+                      <div className="flex items-center gap-2 text-sm">
+                        <Checkbox checked={effective} onCheckedChange={(v) => void toggleEffective(Boolean(v))} />
+                        <span>显示有效成员（含子分组）</span>
+                      </div>
                       <div className="flex flex-wrap items-center gap-2 text-sm text-muted-foreground">
                         <span>
                           第 {safeMemberPage} / {memberPageCount} 页（共 {filteredMembers.length}）
@@ -333,6 +407,8 @@ export function GroupsPage() {
                           <TableHead>UserID</TableHead>
                           <TableHead>用户名</TableHead>
                           <TableHead>昵称</TableHead>
+                          <TableHead>权限</TableHead>
+                          <TableHead>来源</TableHead>
                           <TableHead>操作</TableHead>
                         </TableRow>
                       </TableHeader>
@@ -342,8 +418,17 @@ export function GroupsPage() {
                             <TableCell className="font-mono">{m.userId}</TableCell>
                             <TableCell className="font-mono">{m.username}</TableCell>
                             <TableCell>{m.name}</TableCell>
+                            <TableCell>{m.accessLevel != null ? accessLevelLabel(m.accessLevel) : "-"}</TableCell>
+                            <TableCell className="text-xs">
+                              {[m.direct ? "直接" : null, ...m.viaGroups].filter(Boolean).join(" / ")}
+                            </TableCell>
                             <TableCell>
-                              <Button variant="destructive" size="sm" onClick={() => void removeOne(m.userId)}>
+                              <Button
+                                variant="destructive"
+                                size="sm"
+                                disabled={!m.direct}
+                                onClick={() => void removeOne(m.userId)}
+                              >
                                 移除
                               </Button>
                             </TableCell>
@@ -351,12 +436,12 @@ export function GroupsPage() {
                         ))}
                         {!activeGroup && (
                           <TableRow>
-                            <TableCell colSpan={4} className="text-center text-muted-foreground">请选择左侧分组</TableCell>
+                            <TableCell colSpan={6} className="text-center text-muted-foreground">请选择左侧分组</TableCell>
                           </TableRow>
                         )}
                         {activeGroup && filteredMembers.length === 0 && (
                           <TableRow>
-                            <TableCell colSpan={4} className="text-center text-muted-foreground">
+                            <TableCell colSpan={6} className="text-center text-muted-foreground">
                               {members.length === 0 ? "该分组暂无成员" : "无匹配结果"}
                             </TableCell>
                           </TableRow>
@@ -7,9 +7,19 @@ import { Label } from "@/components/ui/label";
 import { Panel, PanelBody, PanelHeader } from "@/components/ui/panel";
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "@/components/ui/table";
-import { addMembersToGroup, deleteLocalMembers, listLocalGroups, listLocalMembers } from "@/lib/invoke";
+import {
+  addMembersToGroup,
+  addTagsToMembers,
+  deleteLocalMembers,
+  listLocalGroups,
+  listLocalMembers,
+  listLocalTags,
+  refreshLocalMembers,
+  removeTagsFromMembers,
+  setLocalMemberNotes,
+} from "@/lib/invoke";
 import { toast } from "sonner";
-import type { LocalGroup, LocalMember } from "@/lib/types";
+import type { LocalGroup, LocalMember, LocalTag } from "@/lib/types";
 import { formatDateTime } from "@/lib/utils";
 
 const PAGE_SIZE = 50;
@@ -23,12 +33,15 @@ export function LocalMembersPage() {
   const [groupId, setGroupId] = React.useState<string>("");
   const [selected, setSelected] = React.useState<Set<number>>(new Set());
   const [page, setPage] = React.useState(1);
+  const [tags, setTags] = React.useState<LocalTag[]>([]);
+  const [tagFilter, setTagFilter] = React.useState<string>("");
+  const [tagInput, setTagInput] = React.useState("");
 
-  async function fetch(pageNum: number) {
+  async function fetch(pageNum: number, tag = tagFilter) {
     setLoading(true);
     try {
       const res = await listLocalMembers(
-        query.trim() || undefined,
+        { text: query.trim() || null, tags: tag ? [tag] : [] },
         pageNum,
         PAGE_SIZE
       );
@@ -47,11 +60,58 @@ export function LocalMembersPage() {
     setGroups(res);
   }
 
+  async function refreshTags() {
+    const res = await listLocalTags();
+    setTags(res);
+  }
+
   React.useEffect(() => {
     void fetch(1);
     void refreshGroups();
+    void refreshTags();
   }, []);
 
+  function onTagFilterChange(value: string) {
+    const tag = value === "__all__" ? "" : value;
+    setTagFilter(tag);
+    setPage(1);
+    setSelected(new Set());
+    void fetch(1, tag);
+  }
+
+  function parseTags(input: string) {
+    return input
+      .split(/[,，]/)
+      .map((t) => t.trim())
+      .filter(Boolean);
+  }
+
+  async function onTagSelected(remove: boolean) {
+    const userIds = Array.from(selected);
+    const names = parseTags(tagInput);
+    if (userIds.length === 0 || names.length === 0) return;
+    try {
+      if (remove) await removeTagsFromMembers(userIds, names);
+      else await addTagsToMembers(userIds, names);
+      toast.success(`${remove ? "已移除标签" : "已打标签"}：${names.join("、")}（${userIds.length} 人）`);
+      await refreshTags();
+      await fetch(page);
+    } catch (e) {
+      toast.error(`标签操作失败：${String(e)}`);
+    }
+  }
+
+  async function onEditNotes(m: LocalMember) {
+    const notes = prompt(`备注：${m.username}`, m.notes ?? "");
+    if (notes === null) return;
+    try {
+      await setLocalMemberNotes(m.userId, notes.trim() || null);
+      await fetch(page);
+    } catch (e) {
+      toast.error(`保存备注失败：${String(e)}`);
+    }
+  }
+
   async function onSearch() {
     setPage(1);
     setSelected(new Set());
@@ -92,6 +152,20 @@ export function LocalMembersPage() {
     }
   }
 
+  async function onRefresh() {
+    const userIds = Array.from(selected);
+    setLoading(true);
+    try {
+      const res = await refreshLocalMembers(userIds.length > 0 ? userIds : null);
+      toast.success(`刷新完成：更新 ${res.refreshed.length}，已不存在 ${res.missing.length}，失败 ${res.failed.length}`);
+    } catch (e) {
+      toast.error(`刷新失败：${String(e)}`);
+    } finally {
+      setLoading(false);
+    }
+    await fetch(page);
+  }
+
   async function onDeleteSelected() {
     const userIds = Array.from(selected);
     if (userIds.length === 0) return;
@@ -133,6 +207,39 @@ export function LocalMembersPage() {
           搜索
         </Button>
 
+        <div className="grid gap-1">
+          <Label>标签</Label>
+          <Select value={tagFilter || "__all__"} onValueChange={onTagFilterChange}>
+            <SelectTrigger className="w-[180px]">
+              <SelectValue placeholder="全部" />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="__all__">全部</SelectItem>
+              {tags.map((t) => (
+                <SelectItem key={t.id} value={t.name}>
+                  {t.name}【{t.membersCount}人】
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+        </div>
+
+        <div className="grid gap-1">
+          <Label>打标签</Label>
+          <Input
+            className="w-[200px]"
+            value={tagInput}
+            onChange={(e) => setTagInput(e.target.value)}
+            placeholder="多个标签用逗号分隔"
+          />
+        </div>
+        <Button variant="secondary" onClick={() => onTagSelected(false)} disabled={selected.size === 0 || !tagInput.trim()}>
+          添加标签
+        </Button>
+        <Button variant="secondary" onClick={() => onTagSelected(true)} disabled={selected.size === 0 || !tagInput.trim()}>
+          移除标签
+        </Button>
+
         <div className="grid gap-1">
           <Label>添加到分组</Label>
           <Select value={groupId} onValueChange={setGroupId}>
@@ -152,6 +259,10 @@ export function LocalMembersPage() {
           加入分组
         </Button>
 
+        <Button variant="secondary" onClick={onRefresh} disabled={loading}>
+          {selected.size > 0 ? "刷新所选" : "从 GitLab 刷新"}
+        </Button>
+
         <Button variant="destructive" onClick={onDeleteSelected} disabled={selected.size === 0}>
           批量删除
         </Button>
@@ -197,6 +308,8 @@ export function LocalMembersPage() {
             <TableHead>UserID</TableHead>
             <TableHead>用户名</TableHead>
             <TableHead>昵称</TableHead>
+            <TableHead>状态</TableHead>
+            <TableHead>标签 / 备注</TableHead>
             <TableHead>项目</TableHead>
             <TableHead>更新时间</TableHead>
           </TableRow>
@@ -218,6 +331,26 @@ export function LocalMembersPage() {
               <TableCell className="font-mono">{m.userId}</TableCell>
               <TableCell className="font-mono">{m.username}</TableCell>
               <TableCell>{m.name}</TableCell>
+              <TableCell className="text-xs" title={m.lastSyncedAt ? `同步于 ${formatDateTime(m.lastSyncedAt)}` : undefined}>
+                {m.missing ? <span className="text-destructive">已不存在</span> : (m.state ?? "—")}
+              </TableCell>
+              <TableCell className="max-w-[240px] text-xs">
+                <div className="flex flex-wrap gap-1">
+                  {(m.tags ?? []).map((t) => (
+                    <span key={t} className="rounded bg-muted px-1.5 py-0.5">
+                      {t}
+                    </span>
+                  ))}
+                </div>
+                <button
+                  type="button"
+                  className="mt-1 block max-w-full truncate text-left text-muted-foreground hover:underline"
+                  title={m.notes ?? undefined}
+                  onClick={() => onEditNotes(m)}
+                >
+                  {m.notes || "添加备注"}
+                </button>
+              </TableCell>
               <TableCell className="text-muted-foreground max-w-[200px] truncate" title={m.projectName ?? undefined}>
                 {m.projectName ?? "—"}
               </TableCell>
@@ -226,7 +359,7 @@ export function LocalMembersPage() {
           ))}
           {items.length === 0 && (
             <TableRow>
-              <TableCell colSpan={6} className="text-center text-muted-foreground">
+              <TableCell colSpan={8} className="text-center text-muted-foreground">
                 {loading ? "加载中..." : "暂无本地成员，可在项目成员页保存"}
               </TableCell>
             </TableRow>
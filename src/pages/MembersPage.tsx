@@ -17,9 +17,12 @@ import {
   listGroupMembers,
   listLocalGroups,
   listProjectMembers,
+  listProjectSharedGroups,
+  undoBatch,
+  unshareProjectWithGroup,
   upsertLocalMembers,
 } from "@/lib/invoke";
-import type { LocalGroup, ProjectMember, ProjectSummary } from "@/lib/types";
+import type { BatchResult, LocalGroup, ProjectMember, ProjectSummary, SharedGroup } from "@/lib/types";
 import { ACCESS_LEVELS, accessLevelLabel } from "@/lib/types";
 import { formatDateTime } from "@/lib/utils";
 
@@ -101,6 +104,7 @@ function analyzeAddMemberError(message: string): { treatedAsSuccess: boolean; re
 export function MembersPage() {
   const [selectedProject, setSelectedProject] = React.useState<ProjectSummary | null>(null);
   const [members, setMembers] = React.useState<ProjectMember[]>([]);
+  const [sharedGroups, setSharedGroups] = React.useState<SharedGroup[]>([]);
   const [loading, setLoading] = React.useState(false);
   const [error, setError] = React.useState<string>("");
 
@@ -151,6 +155,24 @@ export function MembersPage() {
     } finally {
       setLoading(false);
     }
+    try {
+      setSharedGroups(await listProjectSharedGroups(String(p.id)));
+    } catch {
+      setSharedGroups([]);
+    }
+  }
+
+  async function unshareGroup(g: SharedGroup) {
+    if (!selectedProject) return;
+    const ok = confirm(`确认取消项目与分组 ${g.groupFullPath} 的共享？`);
+    if (!ok) return;
+    try {
+      await unshareProjectWithGroup({ project: String(selectedProject.id), groupId: g.groupId });
+      toast.success(`已取消与 ${g.groupFullPath} 的共享`);
+      await loadMembers(selectedProject, memberPage);
+    } catch (e) {
+      toast.error(`取消共享失败：${String(e)}`);
+    }
   }
 
   const membersPageCount = Math.max(1, Math.ceil(memberTotal / memberPageSize));
@@ -189,7 +211,9 @@ export function MembersPage() {
           name: m.name,
           avatarUrl: m.avatarUrl ?? null,
           projectId: selectedProject.id,
-          projectName: selectedProject.pathWithNamespace || selectedProject.name,
+          projectName: selectedProject.name || selectedProject.pathWithNamespace,
+          projectPath: selectedProject.pathWithNamespace || null,
+          accessLevel: m.accessLevel,
         }))
       );
       toast.success(`已保存到本地成员：${selected.length} 个，已记录项目「${selectedProject.pathWithNamespace || selectedProject.name}」`);
@@ -210,7 +234,7 @@ export function MembersPage() {
     const gid = Number(groupId);
     setActionLoading(true);
     try {
-      const groupMembers = await listGroupMembers(gid);
+      const groupMembers = await listGroupMembers(gid, true);
       const userIds = groupMembers.map((m) => m.userId);
       if (userIds.length === 0) {
         toast.error("该分组没有成员");
@@ -292,6 +316,30 @@ export function MembersPage() {
     }
   }
 
+  function notifyRemoveResult(res: BatchResult) {
+    const jobId = res.jobId;
+    toast.success(
+      `批量移除完成：成功 ${res.successUserIds.length}，跳过 ${res.skippedUserIds.length}，失败 ${res.failed.length}`,
+      jobId != null && res.successUserIds.length > 0
+        ? { action: { label: "撤销", onClick: () => void undoRemove(jobId) } }
+        : undefined
+    );
+    if (res.failed.length > 0) {
+      const msg = res.failed.slice(0, 3).map((f) => `用户 ${f.userId}: ${f.message}`).join("；");
+      toast.error(`部分失败：${msg}${res.failed.length > 3 ? " …" : ""}`);
+    }
+  }
+
+  async function undoRemove(jobId: number) {
+    try {
+      const res = await undoBatch(jobId);
+      toast.success(`已撤销：重新加入 ${res.successUserIds.length}，失败 ${res.failed.length}`);
+      if (selectedProject) await loadMembers(selectedProject, memberPage);
+    } catch (e) {
+      toast.error(`撤销失败：${String(e)}`);
+    }
+  }
+
   async function batchRemoveSelected() {
     if (!selectedProject) return;
     const userIds = Array.from(selectedIds);
@@ -301,11 +349,7 @@ export function MembersPage() {
     setActionLoading(true);
     try {
       const res = await batchRemoveMembersFromProject({ project: String(selectedProject.id), userIds });
-      toast.success(`批量移除完成：成功 ${res.successUserIds.length}，失败 ${res.failed.length}`);
-      if (res.failed.length > 0) {
-        const msg = res.failed.slice(0, 3).map((f) => `用户 ${f.userId}: ${f.message}`).join("；");
-        toast.error(`部分失败：${msg}${res.failed.length > 3 ? " …" : ""}`);
-      }
+      notifyRemoveResult(res);
       await loadMembers(selectedProject, memberPage);
     } catch (e) {
       toast.error(`批量移除失败：${String(e)}`);
@@ -323,7 +367,7 @@ export function MembersPage() {
     const gid = Number(groupId);
     setActionLoading(true);
     try {
-      const groupMembers = await listGroupMembers(gid);
+      const groupMembers = await listGroupMembers(gid, true);
       const userIds = groupMembers.map((m) => m.userId);
       if (userIds.length === 0) {
         toast.error("该分组没有成员");
@@ -332,11 +376,7 @@ export function MembersPage() {
       const ok = confirm(`确认从项目移除分组(${gid})下的 ${userIds.length} 个成员？`);
       if (!ok) return;
       const res = await batchRemoveMembersFromProject({ project: String(selectedProject.id), userIds });
-      toast.success(`批量移除完成：成功 ${res.successUserIds.length}，失败 ${res.failed.length}`);
-      if (res.failed.length > 0) {
-        const msg = res.failed.slice(0, 3).map((f) => `用户 ${f.userId}: ${f.message}`).join("；");
-        toast.error(`部分失败：${msg}${res.failed.length > 3 ? " …" : ""}`);
-      }
+      notifyRemoveResult(res);
       await loadMembers(selectedProject, memberPage);
     } catch (e) {
       toast.error(`批量移除失败：${String(e)}`);
@@ -414,8 +454,8 @@ export function MembersPage() {
         <div className="grid gap-1">
           <Label>过期时间（可选）</Label>
           <Input
-            type="date"
             className="w-[220px]"
+            placeholder="2026-12-31 / +30d / end-of-quarter"
             value={expiresAt}
             onChange={(e) => setExpiresAt(e.target.value)}
           />
@@ -520,11 +560,35 @@ export function MembersPage() {
                   <TableCell className="font-mono">{m.id}</TableCell>
                   <TableCell className="font-mono">{m.username}</TableCell>
                   <TableCell>{m.name}</TableCell>
-                  <TableCell>{accessLevelLabel(m.accessLevel)}</TableCell>
+                  <TableCell>
+                    {accessLevelLabel(m.accessLevel)}
+                    {m.memberRoleName ? <span className="ml-1 text-xs text-muted-foreground">· {m.memberRoleName}</span> : null}
+                  </TableCell>
                   <TableCell className="font-mono text-xs">{formatDateTime(m.createdAt)}</TableCell>
                   <TableCell className="font-mono text-xs">{formatDateTime(m.expiresAt)}</TableCell>
                 </TableRow>
               ))}
+              {sharedGroups.map((g) => (
+                <TableRow key={`group-${g.groupId}`} className="bg-muted/30 transition-colors hover:bg-muted/50">
+                  <TableCell />
+                  <TableCell>
+                    <Avatar className="h-8 w-8 rounded-full overflow-hidden">
+                      <AvatarFallback>组</AvatarFallback>
+                    </Avatar>
+                  </TableCell>
+                  <TableCell className="font-mono">{g.groupId}</TableCell>
+                  <TableCell className="font-mono">{g.groupFullPath}</TableCell>
+                  <TableCell>
+                    <span className="text-muted-foreground">共享分组</span>
+                    <Button variant="ghost" size="sm" className="ml-2 h-7" onClick={() => void unshareGroup(g)}>
+                      取消共享
+                    </Button>
+                  </TableCell>
+                  <TableCell>{accessLevelLabel(g.groupAccessLevel)}</TableCell>
+                  <TableCell className="font-mono text-xs">-</TableCell>
+                  <TableCell className="font-mono text-xs">{formatDateTime(g.expiresAt)}</TableCell>
+                </TableRow>
+              ))}
               {members.length === 0 && (
                 <TableRow>
                   <TableCell colSpan={8} className="text-center text-muted-foreground">
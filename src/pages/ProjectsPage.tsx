@@ -1,7 +1,9 @@
 import * as React from "react";
 
 import { Button } from "@/components/ui/button";
+import { Checkbox } from "@/components/ui/checkbox";
 import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
 import { Panel, PanelBody, PanelHeader } from "@/components/ui/panel";
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "@/components/ui/table";
 import { searchProjects } from "@/lib/invoke";
@@ -12,6 +14,9 @@ const PAGE_SIZE = 20;
 
 export function ProjectsPage() {
   const [keyword, setKeyword] = React.useState("");
+  const [group, setGroup] = React.useState("");
+  const [membership, setMembership] = React.useState(false);
+  const [hideArchived, setHideArchived] = React.useState(false);
   const [items, setItems] = React.useState<ProjectSummary[]>([]);
   const [total, setTotal] = React.useState(0);
   const [loading, setLoading] = React.useState(false);
@@ -22,7 +27,11 @@ export function ProjectsPage() {
     setError("");
     setLoading(true);
     try {
-      const res = await searchProjects(keyword.trim(), p, PAGE_SIZE);
+      const res = await searchProjects(keyword.trim(), p, PAGE_SIZE, {
+        group: group.trim() || null,
+        membership,
+        archived: hideArchived ? false : null,
+      });
       setItems(res.items);
       setTotal(res.total);
     } catch (e) {
@@ -53,16 +62,27 @@ export function ProjectsPage() {
         <PanelHeader className="flex-col items-start gap-1">
       <div className="flex flex-col gap-2">
         {/* <h2 className="text-xl font-semibold">项目搜索</h2> */}
-        <p className="text-sm text-muted-foreground">输入关键字搜索项目。</p>
+        <p className="text-sm text-muted-foreground">输入关键字搜索项目，可限定在某个 GitLab 分组（含子分组）内。</p>
       </div>
         </PanelHeader>
         <PanelBody>
       <div className="flex flex-wrap gap-2 max-w-2xl">
         <Input value={keyword} onChange={(e) => setKeyword(e.target.value)} placeholder="关键字（项目名/namespace）" />
+        <Input value={group} onChange={(e) => setGroup(e.target.value)} placeholder="GitLab 分组（可选，如 group/sub）" />
         <Button onClick={onSearch} disabled={loading}>
           {loading ? "搜索中..." : "搜索"}
         </Button>
       </div>
+      <div className="mt-3 flex flex-wrap items-center gap-4 text-sm">
+        <Label className="flex items-center gap-2">
+          <Checkbox checked={membership} onCheckedChange={(v) => setMembership(Boolean(v))} disabled={group.trim() !== ""} />
+          仅我参与的项目
+        </Label>
+        <Label className="flex items-center gap-2">
+          <Checkbox checked={hideArchived} onCheckedChange={(v) => setHideArchived(Boolean(v))} />
+          隐藏已归档
+        </Label>
+      </div>
 
       {error && <div className="text-sm text-destructive">{error}</div>}
         </PanelBody>